@@ -556,6 +556,7 @@ static HOST_COMPILETESTS: &[Test] = &[
     },
     Test { path: "src/test/run-make", mode: "run-make", suite: "run-make" },
     Test { path: "src/test/rustdoc", mode: "rustdoc", suite: "rustdoc" },
+    Test { path: "src/test/rustdoc-ui", mode: "ui", suite: "rustdoc-ui" },
 
     Test { path: "src/test/pretty", mode: "pretty", suite: "pretty" },
     Test { path: "src/test/run-pass/pretty", mode: "pretty", suite: "run-pass" },
@@ -691,7 +692,8 @@ impl Step for Compiletest {
             // fulldeps test suites with mode = pretty as well.
             mode == "pretty" ||
             mode == "rustdoc" ||
-            mode == "run-make" {
+            mode == "run-make" ||
+            suite == "rustdoc-ui" {
             builder.ensure(compile::Rustc { compiler, target });
         }
 
@@ -712,7 +714,7 @@ impl Step for Compiletest {
         cmd.arg("--rustc-path").arg(builder.rustc(compiler));
 
         // Avoid depending on rustdoc when we don't need it.
-        if mode == "rustdoc" || mode == "run-make" {
+        if mode == "rustdoc" || mode == "run-make" || suite == "rustdoc-ui" {
             cmd.arg("--rustdoc-path").arg(builder.rustdoc(compiler.host));
         }
 
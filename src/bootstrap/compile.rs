@@ -140,6 +140,12 @@ pub fn std_cargo(build: &Build,
                  cargo: &mut Command) {
     let mut features = build.std_features();
 
+    // There's no unwinder, libbacktrace or jemalloc to be had when targeting
+    // wasm without emscripten, so build the bare standard library there.
+    if target.contains("wasm32") && !target.contains("emscripten") {
+        features = String::new();
+    }
+
     if let Some(target) = env::var_os("MACOSX_STD_DEPLOYMENT_TARGET") {
         cargo.env("MACOSX_DEPLOYMENT_TARGET", target);
     }
@@ -378,6 +384,10 @@ pub fn test_cargo(build: &Build,
     if let Some(target) = env::var_os("MACOSX_STD_DEPLOYMENT_TARGET") {
         cargo.env("MACOSX_DEPLOYMENT_TARGET", target);
     }
+    // libtest's unstable flags are only accepted on the nightly channel.
+    if !build.unstable_features() {
+        cargo.env("CFG_DISABLE_UNSTABLE_FEATURES", "1");
+    }
     cargo.arg("--manifest-path")
         .arg(build.src.join("src/libtest/Cargo.toml"));
 }
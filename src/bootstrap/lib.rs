@@ -668,7 +668,7 @@ impl Build {
 
         let mut base = Vec::new();
         if target != self.config.build && !target.contains("msvc") &&
-            !target.contains("emscripten") {
+            !target.contains("emscripten") && !target.contains("wasm32") {
             base.push(format!("-Clinker={}", self.cc(target).display()));
         }
         base
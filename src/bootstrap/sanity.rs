@@ -134,8 +134,9 @@ pub fn check(build: &mut Build) {
     for target in &build.targets {
         // On emscripten we don't actually need the C compiler to just
         // build the target artifacts, only for testing. For the sake
-        // of easier bot configuration, just skip detection.
-        if target.contains("emscripten") {
+        // of easier bot configuration, just skip detection. Likewise wasm
+        // without emscripten has no C toolchain involved at all.
+        if target.contains("emscripten") || target.contains("wasm32") {
             continue;
         }
 
@@ -196,6 +197,29 @@ pub fn check(build: &mut Build) {
             }
         }
 
+        // The PNaCl ABI simplification passes, verifier and frozen bitcode
+        // writer only exist in the PNaCl fork of LLVM, not in the one we build,
+        // so the compiler has to be linked against an external one.
+        if target.contains("nacl") {
+            for host in &build.hosts {
+                let llvm_config = build.config.target_config.get(host)
+                    .and_then(|config| config.llvm_config.as_ref());
+                let llvm_config = match llvm_config {
+                    Some(llvm_config) => llvm_config,
+                    None => {
+                        panic!("the {} target requires an LLVM with PNaCl support, \
+                                set target.{}.llvm-config in config.toml", target, host)
+                    }
+                };
+                let components = output(Command::new(llvm_config).arg("--components"));
+                if !components.split_whitespace().any(|c| c == "pnacl") {
+                    panic!("the {} target requires an LLVM with PNaCl support, \
+                            but {} lacks the pnacl component",
+                           target, llvm_config.display());
+                }
+            }
+        }
+
         if target.contains("msvc") {
             // There are three builds of cmake on windows: MSVC, MinGW, and
             // Cygwin. The Cygwin build does not have generators for Visual
@@ -0,0 +1,90 @@
+#!/usr/bin/env python
+#
+# Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+# file at the top-level directory of this distribution and at
+# http://rust-lang.org/COPYRIGHT.
+#
+# Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+# http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+# <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+# option. This file may not be copied, modified, or distributed
+# except according to those terms.
+"""
+This script measures the effect of the MIR inliner on compile times. Each
+crate is compiled several times at -C opt-level=2 with the inliner turned
+off and on (-Z inline-mir=no/yes), and the median wall-clock time of each
+is reported along with the relative change.
+
+Crates are given by their root source file and are compiled on their own, so
+they must only depend on the standard library. Arguments after `--` are
+passed to every rustc invocation.
+
+sample usage:
+    src/etc/bench-mir-inline.py \\
+        --rustc build/x86_64-unknown-linux-gnu/stage1/bin/rustc \\
+        --runs 5 src/test/run-pass/*.rs -- -C codegen-units=1
+"""
+
+import argparse
+import os
+import shutil
+import subprocess
+import sys
+import tempfile
+import time
+
+
+def compile_time(rustc, krate, inline, out_dir, extra_args):
+    args = [rustc, krate, "-C", "opt-level=2", "-Z", "inline-mir=%s" % inline,
+            "--out-dir", out_dir] + extra_args
+    start = time.time()
+    subprocess.check_call(args, stdout=open(os.devnull, "w"))
+    return time.time() - start
+
+
+def median(values):
+    values = sorted(values)
+    mid = len(values) // 2
+    if len(values) % 2 == 1:
+        return values[mid]
+    return (values[mid - 1] + values[mid]) / 2.0
+
+
+def main():
+    rest = []
+    argv = sys.argv[1:]
+    if "--" in argv:
+        rest = argv[argv.index("--") + 1:]
+        argv = argv[:argv.index("--")]
+
+    parser = argparse.ArgumentParser(
+        description="Compare compile times with and without MIR inlining.")
+    parser.add_argument("--rustc", required=True, help="the rustc to measure")
+    parser.add_argument("--runs", type=int, default=3,
+                        help="compilations per crate and setting")
+    parser.add_argument("crates", nargs="+", help="crate root files")
+    args = parser.parse_args(argv)
+
+    print("%-50s %10s %10s %8s" % ("crate", "no inline", "inline", "change"))
+    totals = [0.0, 0.0]
+    out_dir = tempfile.mkdtemp()
+    try:
+        for krate in args.crates:
+            times = []
+            for inline in ["no", "yes"]:
+                runs = [compile_time(args.rustc, krate, inline, out_dir, rest)
+                        for _ in range(args.runs)]
+                times.append(median(runs))
+            totals[0] += times[0]
+            totals[1] += times[1]
+            change = (times[1] - times[0]) / times[0] * 100
+            print("%-50s %9.3fs %9.3fs %+7.1f%%" % (krate, times[0], times[1], change))
+    finally:
+        shutil.rmtree(out_dir)
+
+    change = (totals[1] - totals[0]) / totals[0] * 100
+    print("%-50s %9.3fs %9.3fs %+7.1f%%" % ("total", totals[0], totals[1], change))
+
+
+if __name__ == "__main__":
+    main()
@@ -22,6 +22,8 @@
 #![feature(staged_api)]
 #![feature(rustc_attrs)]
 #![cfg_attr(any(unix, target_os = "redox"), feature(libc))]
+#![cfg_attr(all(target_arch = "wasm32", not(target_os = "emscripten")),
+            feature(link_llvm_intrinsics))]
 #![rustc_alloc_kind = "lib"]
 
 // The minimum alignment guaranteed by the architecture. This value is used to
@@ -260,6 +262,124 @@ mod platform {
     }
 }
 
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+mod platform {
+    use core::cmp;
+    use core::ptr;
+
+    use MIN_ALIGN;
+    use System;
+    use alloc::heap::{Alloc, AllocErr, Layout};
+
+    // The size of a WebAssembly page, the unit in which linear memory grows.
+    const PAGE_SIZE: usize = 64 * 1024;
+
+    extern {
+        #[link_name = "llvm.wasm.grow.memory.i32"]
+        fn grow_memory(pages: u32) -> i32;
+    }
+
+    // There's no libc to hand out memory on wasm, so this is a simple bump
+    // allocator carving allocations out of freshly grown linear memory. Memory
+    // is only given back when the most recent allocation is freed, so this is
+    // not suitable for long-running programs which churn through the heap.
+    //
+    // wasm has no threads, so there's no need to synchronize access to these.
+    static mut NEXT: usize = 0;
+    static mut END: usize = 0;
+
+    unsafe fn bump(layout: &Layout) -> *mut u8 {
+        // Like malloc, hand out memory aligned to at least `MIN_ALIGN`.
+        let align = cmp::max(layout.align(), MIN_ALIGN);
+        let mut start = (NEXT + align - 1) & !(align - 1);
+        if start > END || END - start < layout.size() {
+            let pages = (layout.size() + align + PAGE_SIZE - 1) / PAGE_SIZE;
+            let prev = grow_memory(pages as u32);
+            if prev < 0 {
+                return ptr::null_mut()
+            }
+            // If someone else grew memory since we last did then the new pages
+            // aren't contiguous with our region, so start over from them.
+            let base = prev as usize * PAGE_SIZE;
+            if base != END {
+                NEXT = base;
+            }
+            END = base + pages * PAGE_SIZE;
+            start = (NEXT + align - 1) & !(align - 1);
+        }
+        NEXT = start + layout.size();
+        start as *mut u8
+    }
+
+    #[unstable(feature = "allocator_api", issue = "32838")]
+    unsafe impl<'a> Alloc for &'a System {
+        #[inline]
+        unsafe fn alloc(&mut self, layout: Layout) -> Result<*mut u8, AllocErr> {
+            let ptr = bump(&layout);
+            if !ptr.is_null() {
+                Ok(ptr)
+            } else {
+                Err(AllocErr::Exhausted { request: layout })
+            }
+        }
+
+        #[inline]
+        unsafe fn alloc_zeroed(&mut self, layout: Layout)
+            -> Result<*mut u8, AllocErr>
+        {
+            // Freshly grown memory is zeroed, but memory handed back to us by
+            // `dealloc` may not be.
+            let ret = self.alloc(layout.clone());
+            if let Ok(ptr) = ret {
+                ptr::write_bytes(ptr, 0, layout.size());
+            }
+            ret
+        }
+
+        #[inline]
+        unsafe fn dealloc(&mut self, ptr: *mut u8, layout: Layout) {
+            if ptr as usize + layout.size() == NEXT {
+                NEXT = ptr as usize;
+            }
+        }
+
+        #[inline]
+        unsafe fn realloc(&mut self,
+                          ptr: *mut u8,
+                          old_layout: Layout,
+                          new_layout: Layout) -> Result<*mut u8, AllocErr> {
+            if old_layout.align() != new_layout.align() {
+                return Err(AllocErr::Unsupported {
+                    details: "cannot change alignment on `realloc`",
+                })
+            }
+
+            // The last allocation can be resized in place as long as there's
+            // room left in the region.
+            if ptr as usize + old_layout.size() == NEXT &&
+               END - (ptr as usize) >= new_layout.size() {
+                NEXT = ptr as usize + new_layout.size();
+                return Ok(ptr)
+            }
+
+            let res = self.alloc(new_layout.clone());
+            if let Ok(new_ptr) = res {
+                let size = cmp::min(old_layout.size(), new_layout.size());
+                ptr::copy_nonoverlapping(ptr, new_ptr, size);
+                self.dealloc(ptr, old_layout);
+            }
+            res
+        }
+
+        fn oom(&mut self, _err: AllocErr) -> ! {
+            // There's nowhere to print a message to, so just trap.
+            unsafe {
+                ::core::intrinsics::abort();
+            }
+        }
+    }
+}
+
 #[cfg(windows)]
 #[allow(bad_style)]
 mod platform {
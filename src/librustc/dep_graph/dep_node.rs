@@ -440,6 +440,7 @@ define_dep_nodes!( <'tcx>
     [] MirConst(DefId),
     [] MirValidated(DefId),
     [] MirOptimized(DefId),
+    [] MirForCtfe(DefId),
     [] MirShim { instance_def: InstanceDef<'tcx> },
 
     [] BorrowCheckKrate,
@@ -12,7 +12,6 @@ use rustc_data_structures::stable_hasher::{HashStable, StableHasher,
                                            StableHashingContextProvider};
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_data_structures::indexed_vec::{Idx, IndexVec};
-use session::config::OutputType;
 use std::cell::{Ref, RefCell};
 use std::hash::Hash;
 use std::rc::Rc;
@@ -425,7 +424,26 @@ pub struct WorkProduct {
     pub input_hash: u64,
 
     /// Saved files associated with this CGU
-    pub saved_files: Vec<(OutputType, String)>,
+    pub saved_files: Vec<(WorkProductFileKind, String)>,
+}
+
+#[derive(Clone, Copy, Debug, RustcEncodable, RustcDecodable, PartialEq)]
+pub enum WorkProductFileKind {
+    Object,
+    Bytecode,
+    /// The bitcode of a CGU as it was handed to ThinLTO, i.e. before any
+    /// cross-module importing happened.
+    PreThinLtoBytecode,
+}
+
+impl WorkProductFileKind {
+    pub fn extension(&self) -> &'static str {
+        match *self {
+            WorkProductFileKind::Object => "o",
+            WorkProductFileKind::Bytecode => "bc",
+            WorkProductFileKind::PreThinLtoBytecode => "pre-lto.bc",
+        }
+    }
 }
 
 pub(super) struct CurrentDepGraph {
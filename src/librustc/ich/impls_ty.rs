@@ -367,6 +367,17 @@ for ::middle::const_val::ErrKind<'gcx> {
             MiscBinaryOp |
             MiscCatchAll |
             IndexOpFeatureGated |
+            DanglingPointerDeref |
+            DerefFunctionPointer |
+            InvalidPointerMath |
+            ReadPointerAsBytes |
+            ReadUndefBytes |
+            ModifiedConstantMemory |
+            InvalidBool |
+            InvalidDiscriminant |
+            UnreachableCode |
+            StackFrameLimitReached |
+            StepLimitReached |
             TypeckError => {
                 // nothing to do
             }
@@ -386,6 +397,18 @@ for ::middle::const_val::ErrKind<'gcx> {
             ErroneousReferencedConstant(ref const_val) => {
                 const_val.hash_stable(hcx, hasher);
             }
+            ErroneousConstFnCall(def_id, ref const_val) => {
+                def_id.hash_stable(hcx, hasher);
+                const_val.hash_stable(hcx, hasher);
+            }
+            PointerOutOfBounds { offset, size, allocation_size } => {
+                offset.hash_stable(hcx, hasher);
+                size.hash_stable(hcx, hasher);
+                allocation_size.hash_stable(hcx, hasher);
+            }
+            InvalidChar(c) => {
+                c.hash_stable(hcx, hasher);
+            }
         }
     }
 }
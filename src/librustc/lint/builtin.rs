@@ -222,6 +222,39 @@ declare_lint! {
     "unnecessary use of an `unsafe` block"
 }
 
+// The following lints are checked by rustdoc, they're declared here so that
+// the compiler knows about them.
+
+declare_lint! {
+    pub UNCLOSED_DOC_CODE_BLOCKS,
+    Warn,
+    "code blocks in documentation which are never closed"
+}
+
+declare_lint! {
+    pub INVALID_CODEBLOCK_ATTRIBUTES,
+    Warn,
+    "unknown attributes on documentation code blocks, likely misspelled"
+}
+
+declare_lint! {
+    pub EMPTY_DOCS,
+    Warn,
+    "doc comments without any content"
+}
+
+declare_lint! {
+    pub INVALID_DOC_ATTRIBUTES,
+    Warn,
+    "malformed or unknown `#[doc]` attributes"
+}
+
+declare_lint! {
+    pub INTRA_DOC_LINK_RESOLUTION_FAILURE,
+    Warn,
+    "links in documentation which look like paths but can't be resolved"
+}
+
 /// Does nothing as a lint pass, but registers some `Lint`s
 /// which are used by other parts of the compiler.
 #[derive(Copy, Clone)]
@@ -263,7 +296,12 @@ impl LintPass for HardwiredLints {
             PARENTHESIZED_PARAMS_IN_TYPES_AND_MODULES,
             LATE_BOUND_LIFETIME_ARGUMENTS,
             DEPRECATED,
-            UNUSED_UNSAFE
+            UNUSED_UNSAFE,
+            UNCLOSED_DOC_CODE_BLOCKS,
+            INVALID_CODEBLOCK_ATTRIBUTES,
+            EMPTY_DOCS,
+            INVALID_DOC_ATTRIBUTES,
+            INTRA_DOC_LINK_RESOLUTION_FAILURE
         )
     }
 }
@@ -10,7 +10,7 @@
 
 use std::cmp;
 
-use errors::DiagnosticBuilder;
+use errors::{Applicability, DiagnosticBuilder};
 use hir::HirId;
 use ich::StableHashingContext;
 use lint::builtin;
@@ -23,11 +23,12 @@ use syntax::ast;
 use syntax::attr;
 use syntax::codemap::MultiSpan;
 use syntax::symbol::Symbol;
-use util::nodemap::FxHashMap;
+use util::nodemap::{FxHashMap, FxHashSet};
 
 pub struct LintLevelSets {
     list: Vec<LintSet>,
     lint_cap: Level,
+    uncapped_lints: FxHashSet<LintId>,
 }
 
 enum LintSet {
@@ -48,6 +49,7 @@ impl LintLevelSets {
         let mut me = LintLevelSets {
             list: Vec::new(),
             lint_cap: Level::Forbid,
+            uncapped_lints: FxHashSet(),
         };
         me.process_command_line(sess);
         return me
@@ -62,20 +64,32 @@ impl LintLevelSets {
         let mut specs = FxHashMap();
         self.lint_cap = sess.opts.lint_cap.unwrap_or(Level::Forbid);
 
+        // The lints are looked up here rather than when the options are built
+        // so that those registered by plugins are found too.
+        for lint_name in &sess.opts.uncapped_lints {
+            if let Ok(ids) = store.find_lints(lint_name) {
+                self.uncapped_lints.extend(ids);
+            }
+        }
+
         for &(ref lint_name, level) in &sess.opts.lint_opts {
             store.check_lint_name_cmdline(sess, &lint_name, level);
 
-            // If the cap is less than this specified level, e.g. if we've got
-            // `--cap-lints allow` but we've also got `-D foo` then we ignore
-            // this specification as the lint cap will set it to allow anyway.
-            let level = cmp::min(level, self.lint_cap);
-
             let lint_flag_val = Symbol::intern(lint_name);
             let ids = match store.find_lints(&lint_name) {
                 Ok(ids) => ids,
                 Err(_) => continue, // errors handled in check_lint_name_cmdline above
             };
             for id in ids {
+                // If the cap is less than this specified level, e.g. if we've
+                // got `--cap-lints allow` but we've also got `-D foo` then we
+                // ignore this specification as the lint cap will set it to
+                // allow anyway.
+                let level = if self.uncapped_lints.contains(&id) {
+                    level
+                } else {
+                    cmp::min(level, self.lint_cap)
+                };
                 let src = LintSource::CommandLine(lint_flag_val);
                 specs.insert(id, (level, src));
             }
@@ -97,17 +111,23 @@ impl LintLevelSets {
         // If `level` is none then we actually assume the default level for this
         // lint.
         let mut level = level.unwrap_or(lint.default_level);
+        let uncapped = self.uncapped_lints.contains(&LintId::of(lint));
 
         // If we're about to issue a warning, check at the last minute for any
         // directives against the warnings "lint". If, for example, there's an
         // `allow(warnings)` in scope then we want to respect that instead.
+        // Uncapped lints only respect the ones in the source.
         if level == Level::Warn {
             let (warnings_level, warnings_src) =
                 self.get_lint_id_level(LintId::of(lint::builtin::WARNINGS),
                                        idx,
                                        aux);
+            let from_command_line = match warnings_src {
+                LintSource::CommandLine(_) => true,
+                _ => false,
+            };
             if let Some(configured_warning_level) = warnings_level {
-                if configured_warning_level != Level::Warn {
+                if configured_warning_level != Level::Warn && !(uncapped && from_command_line) {
                     level = configured_warning_level;
                     src = warnings_src;
                 }
@@ -115,7 +135,9 @@ impl LintLevelSets {
         }
 
         // Ensure that we never exceed the `--cap-lints` argument.
-        level = cmp::min(level, self.lint_cap);
+        if !uncapped {
+            level = cmp::min(level, self.lint_cap);
+        }
 
         return (level, src)
     }
@@ -262,10 +284,11 @@ impl<'a> LintLevelsBuilder<'a> {
                                     store.check_lint_name(&name_lower) {
                                 db.emit();
                             } else {
-                                db.span_suggestion(
+                                db.span_suggestion_with_applicability(
                                     li.span,
                                     "lowercase the lint name",
-                                    name_lower
+                                    name_lower,
+                                    Applicability::MachineApplicable
                                 ).emit();
                             }
                         } else {
@@ -406,9 +429,11 @@ impl<'gcx> HashStable<StableHashingContext<'gcx>> for LintLevelMap {
         let LintLevelSets {
             ref list,
             lint_cap,
+            ref uncapped_lints,
         } = *sets;
 
         lint_cap.hash_stable(hcx, hasher);
+        uncapped_lints.hash_stable(hcx, hasher);
 
         hcx.while_hashing_spans(true, |hcx| {
             list.len().hash_stable(hcx, hasher);
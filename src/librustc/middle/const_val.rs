@@ -105,6 +105,22 @@ pub enum ErrKind<'tcx> {
     LayoutError(layout::LayoutError<'tcx>),
 
     ErroneousReferencedConstant(Box<ConstEvalErr<'tcx>>),
+    /// An error inside the body of a `const fn`, reported at the call site.
+    ErroneousConstFnCall(DefId, Box<ConstEvalErr<'tcx>>),
+
+    DanglingPointerDeref,
+    DerefFunctionPointer,
+    InvalidPointerMath,
+    ReadPointerAsBytes,
+    ReadUndefBytes,
+    PointerOutOfBounds { offset: u64, size: u64, allocation_size: u64 },
+    ModifiedConstantMemory,
+    InvalidBool,
+    InvalidChar(u128),
+    InvalidDiscriminant,
+    UnreachableCode,
+    StackFrameLimitReached,
+    StepLimitReached,
 
     TypeckError
 }
@@ -166,6 +182,34 @@ impl<'a, 'gcx, 'tcx> ConstEvalErr<'tcx> {
             LayoutError(ref err) => Simple(err.to_string().into_cow()),
 
             ErroneousReferencedConstant(_) => simple!("could not evaluate referenced constant"),
+            ErroneousConstFnCall(..) => simple!("could not evaluate constant function call"),
+
+            DanglingPointerDeref => simple!("dangling pointer was dereferenced"),
+            DerefFunctionPointer => simple!("tried to dereference a function pointer"),
+            InvalidPointerMath => {
+                simple!("tried to do math or comparisons on pointers into different allocations")
+            }
+            ReadPointerAsBytes => {
+                simple!("a raw memory access tried to access part of a pointer value as raw bytes")
+            }
+            ReadUndefBytes => simple!("attempted to read undefined bytes"),
+            PointerOutOfBounds { offset, size, allocation_size } => {
+                simple!("memory access of {}..{} outside bounds of allocation which has size {}",
+                        offset, offset + size, allocation_size)
+            }
+            ModifiedConstantMemory => simple!("tried to modify constant memory"),
+            InvalidBool => simple!("invalid boolean value read"),
+            InvalidChar(c) => {
+                simple!("tried to interpret an invalid 32-bit value as a char: {}", c)
+            }
+            InvalidDiscriminant => simple!("invalid enum discriminant value read"),
+            UnreachableCode => simple!("entered unreachable code"),
+            StackFrameLimitReached => {
+                simple!("reached the configured maximum number of stack frames")
+            }
+            StepLimitReached => {
+                simple!("constant evaluation took too long; it may contain an infinite loop")
+            }
 
             TypeckError => simple!("type-checking failed"),
         }
@@ -178,14 +222,24 @@ impl<'a, 'gcx, 'tcx> ConstEvalErr<'tcx> {
         -> DiagnosticBuilder<'gcx>
     {
         let mut err = self;
-        while let &ConstEvalErr {
-            kind: ErrKind::ErroneousReferencedConstant(box ref i_err), ..
-        } = err {
-            err = i_err;
+        let mut calls = vec![];
+        loop {
+            match err.kind {
+                ErrKind::ErroneousReferencedConstant(box ref i_err) => err = i_err,
+                ErrKind::ErroneousConstFnCall(def_id, box ref i_err) => {
+                    calls.push((err.span, def_id));
+                    err = i_err;
+                }
+                _ => break,
+            }
         }
 
         let mut diag = struct_span_err!(tcx.sess, err.span, E0080, "constant evaluation error");
         err.note(tcx, primary_span, primary_kind, &mut diag);
+        // Innermost call first, like a backtrace.
+        for &(span, def_id) in calls.iter().rev() {
+            diag.span_note(span, &format!("inside call to `{}`", tcx.item_path_str(def_id)));
+        }
         diag
     }
 
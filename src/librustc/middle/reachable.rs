@@ -150,6 +150,12 @@ impl<'a, 'tcx> Visitor<'tcx> for ReachableContext<'a, 'tcx> {
 }
 
 impl<'a, 'tcx> ReachableContext<'a, 'tcx> {
+    // Returns true if the MIR of every function is encoded into the crate
+    // metadata, which lets other crates inline any of them.
+    fn all_fns_might_be_inlined(&self) -> bool {
+        self.tcx.sess.opts.debugging_opts.always_encode_mir
+    }
+
     // Returns true if the given def ID represents a local item that is
     // eligible for inlining and false otherwise.
     fn def_id_represents_local_inlined_item(&self, def_id: DefId) -> bool {
@@ -161,7 +167,9 @@ impl<'a, 'tcx> ReachableContext<'a, 'tcx> {
         match self.tcx.hir.find(node_id) {
             Some(hir_map::NodeItem(item)) => {
                 match item.node {
-                    hir::ItemFn(..) => item_might_be_inlined(&item),
+                    hir::ItemFn(..) => {
+                        item_might_be_inlined(&item) || self.all_fns_might_be_inlined()
+                    }
                     _ => false,
                 }
             }
@@ -178,7 +186,8 @@ impl<'a, 'tcx> ReachableContext<'a, 'tcx> {
                     hir::ImplItemKind::Const(..) => true,
                     hir::ImplItemKind::Method(ref sig, _) => {
                         if generics_require_inlining(&sig.generics) ||
-                                attr::requests_inline(&impl_item.attrs) {
+                                attr::requests_inline(&impl_item.attrs) ||
+                                self.all_fns_might_be_inlined() {
                             true
                         } else {
                             let impl_did = self.tcx
@@ -251,7 +260,7 @@ impl<'a, 'tcx> ReachableContext<'a, 'tcx> {
             hir_map::NodeItem(item) => {
                 match item.node {
                     hir::ItemFn(.., body) => {
-                        if item_might_be_inlined(&item) {
+                        if item_might_be_inlined(&item) || self.all_fns_might_be_inlined() {
                             self.visit_nested_body(body);
                         }
                     }
@@ -295,7 +304,8 @@ impl<'a, 'tcx> ReachableContext<'a, 'tcx> {
                     }
                     hir::ImplItemKind::Method(ref sig, body) => {
                         let did = self.tcx.hir.get_parent_did(search_item);
-                        if method_might_be_inlined(self.tcx, sig, impl_item, did) {
+                        if method_might_be_inlined(self.tcx, sig, impl_item, did) ||
+                                self.all_fns_might_be_inlined() {
                             self.visit_nested_body(body)
                         }
                     }
@@ -62,6 +62,20 @@ pub enum Sanitizer {
     Thread,
 }
 
+#[derive(Clone, Copy, PartialEq, Hash, Debug)]
+pub enum Lto {
+    /// Don't do any LTO whatsoever
+    No,
+
+    /// Merge every crate's bitcode into a single module and optimize it as a
+    /// whole ("fat" LTO)
+    Fat,
+
+    /// Keep each codegen unit separate, but compute summaries for all of them
+    /// and import across modules before optimizing them in parallel
+    Thin,
+}
+
 #[derive(Clone, Copy, PartialEq, Hash)]
 pub enum OptLevel {
     No, // -O0
@@ -156,6 +170,7 @@ impl OutputType {
 pub enum ErrorOutputType {
     HumanReadable(ColorConfig),
     Json,
+    Short(ColorConfig),
 }
 
 impl Default for ErrorOutputType {
@@ -313,6 +328,10 @@ top_level_options!(
         debuginfo: DebugInfoLevel [TRACKED],
         lint_opts: Vec<(String, lint::Level)> [TRACKED],
         lint_cap: Option<lint::Level> [TRACKED],
+        // Lints which neither `lint_cap` nor a level for `warnings` in
+        // `lint_opts` apply to, such as rustdoc's own lints when it silences
+        // the compiler's.
+        uncapped_lints: Vec<String> [TRACKED],
         describe_lints: bool [UNTRACKED],
         output_types: OutputTypes [TRACKED],
         // FIXME(mw): We track this for now but it actually doesn't make too
@@ -353,6 +372,10 @@ top_level_options!(
 
         // Number of object files/codegen units to produce on the backend
         codegen_units: usize [UNTRACKED],
+
+        // Rewrite the source files with the machine-applicable suggestions
+        // of the diagnostics emitted during compilation
+        apply_suggestions: bool [UNTRACKED],
     }
 );
 
@@ -497,6 +520,7 @@ pub fn basic_options() -> Options {
         debuginfo: NoDebugInfo,
         lint_opts: Vec::new(),
         lint_cap: None,
+        uncapped_lints: Vec::new(),
         describe_lints: false,
         output_types: OutputTypes(BTreeMap::new()),
         search_paths: SearchPaths::new(),
@@ -516,6 +540,7 @@ pub fn basic_options() -> Options {
         debug_assertions: true,
         actually_rustdoc: false,
         codegen_units: 1,
+        apply_suggestions: false,
     }
 }
 
@@ -685,6 +710,8 @@ macro_rules! options {
             Some("a space-separated list of passes, or `all`");
         pub const parse_opt_uint: Option<&'static str> =
             Some("a number");
+        pub const parse_lto: Option<&'static str> =
+            Some("one of `thin`, `fat`, or omitted");
         pub const parse_panic_strategy: Option<&'static str> =
             Some("either `panic` or `abort`");
         pub const parse_relro_level: Option<&'static str> =
@@ -699,7 +726,7 @@ macro_rules! options {
 
     #[allow(dead_code)]
     mod $mod_set {
-        use super::{$struct_name, Passes, SomePasses, AllPasses, Sanitizer};
+        use super::{$struct_name, Passes, SomePasses, AllPasses, Sanitizer, Lto};
         use rustc_back::{LinkerFlavor, PanicStrategy, RelroLevel};
 
         $(
@@ -812,6 +839,16 @@ macro_rules! options {
             }
         }
 
+        fn parse_lto(slot: &mut Lto, v: Option<&str>) -> bool {
+            *slot = match v {
+                None | Some("y") | Some("yes") | Some("on") | Some("fat") => Lto::Fat,
+                Some("n") | Some("no") | Some("off") => Lto::No,
+                Some("thin") => Lto::Thin,
+                _ => return false,
+            };
+            true
+        }
+
         fn parse_panic_strategy(slot: &mut Option<PanicStrategy>, v: Option<&str>) -> bool {
             match v {
                 Some("unwind") => *slot = Some(PanicStrategy::Unwind),
@@ -883,7 +920,7 @@ options! {CodegenOptions, CodegenSetter, basic_codegen_options,
         "extra arguments to append to the linker invocation (space separated)"),
     link_dead_code: bool = (false, parse_bool, [UNTRACKED],
         "don't let linker strip dead code (turning it on can be used for code coverage)"),
-    lto: bool = (false, parse_bool, [TRACKED],
+    lto: Lto = (Lto::No, parse_lto, [TRACKED],
         "perform LLVM link-time optimizations"),
     target_cpu: Option<String> = (None, parse_opt_string, [TRACKED],
         "select target processor (rustc --print target-cpus for details)"),
@@ -1060,6 +1097,8 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
           "print the result of the translation item collection pass"),
     mir_opt_level: usize = (1, parse_uint, [TRACKED],
           "set the MIR optimization level (0-3, default: 1)"),
+    inline_mir: Option<bool> = (None, parse_opt_bool, [TRACKED],
+          "enable or disable MIR inlining (default: enabled at opt-level 2 and above)"),
     dump_mir: Option<String> = (None, parse_opt_string, [UNTRACKED],
           "dump MIR state at various points in translation"),
     dump_mir_dir: Option<String> = (None, parse_opt_string, [UNTRACKED],
@@ -1366,12 +1405,15 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
         opt::multi("Z", "", "Set internal debugging options", "FLAG"),
         opt::opt_s("", "error-format",
                       "How errors and other messages are produced",
-                      "human|json"),
+                      "human|json|short"),
         opt::opt_s("", "color", "Configure coloring of output:
                                  auto   = colorize, if output goes to a tty (default);
                                  always = always colorize output;
                                  never  = never colorize output", "auto|always|never"),
 
+        opt::flag("", "apply-suggestions",
+                  "Rewrite the source files with the machine-applicable suggestions \
+                   of the emitted diagnostics"),
         opt::opt("", "pretty",
                  "Pretty-print the input instead of compiling;
                   valid types are: `normal` (un-annotated source),
@@ -1435,13 +1477,14 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
         match matches.opt_str("error-format").as_ref().map(|s| &s[..]) {
             Some("human")   => ErrorOutputType::HumanReadable(color),
             Some("json") => ErrorOutputType::Json,
+            Some("short") => ErrorOutputType::Short(color),
 
             None => ErrorOutputType::HumanReadable(color),
 
             Some(arg) => {
                 early_error(ErrorOutputType::HumanReadable(color),
-                            &format!("argument for --error-format must be human or json (instead \
-                                      was `{}`)",
+                            &format!("argument for --error-format must be `human`, `json` or \
+                                      `short` (instead was `{}`)",
                                      arg))
             }
         }
@@ -1552,8 +1595,8 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
         early_error(error_format, "Value for codegen units must be a positive nonzero integer");
     }
 
-    if cg.lto && debugging_opts.incremental.is_some() {
-        early_error(error_format, "can't perform LTO when compiling incrementally");
+    if cg.lto == Lto::Fat && debugging_opts.incremental.is_some() {
+        early_error(error_format, "can't perform fat LTO when compiling incrementally");
     }
 
     let mut prints = Vec::<PrintRequest>::new();
@@ -1660,6 +1703,7 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
 
     let cfg = parse_cfgspecs(matches.opt_strs("cfg"));
     let test = matches.opt_present("test");
+    let apply_suggestions = matches.opt_present("apply-suggestions");
 
     prints.extend(matches.opt_strs("print").into_iter().map(|s| {
         match &*s {
@@ -1748,6 +1792,7 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
         debuginfo,
         lint_opts,
         lint_cap,
+        uncapped_lints: Vec::new(),
         describe_lints,
         output_types: OutputTypes(output_types),
         search_paths,
@@ -1767,6 +1812,7 @@ pub fn build_session_options_and_crate_config(matches: &getopts::Matches)
         debug_assertions,
         actually_rustdoc: false,
         codegen_units,
+        apply_suggestions,
     },
     cfg)
 }
@@ -1885,7 +1931,7 @@ mod dep_tracking {
     use std::path::PathBuf;
     use std::collections::hash_map::DefaultHasher;
     use super::{Passes, CrateType, OptLevel, DebugInfoLevel,
-                OutputTypes, Externs, ErrorOutputType, Sanitizer};
+                OutputTypes, Externs, ErrorOutputType, Sanitizer, Lto};
     use syntax::feature_gate::UnstableFeatures;
     use rustc_back::{PanicStrategy, RelroLevel};
 
@@ -1938,6 +1984,7 @@ mod dep_tracking {
     impl_dep_tracking_hash_via_hash!(RelroLevel);
     impl_dep_tracking_hash_via_hash!(Passes);
     impl_dep_tracking_hash_via_hash!(OptLevel);
+    impl_dep_tracking_hash_via_hash!(Lto);
     impl_dep_tracking_hash_via_hash!(DebugInfoLevel);
     impl_dep_tracking_hash_via_hash!(UnstableFeatures);
     impl_dep_tracking_hash_via_hash!(Externs);
@@ -2014,7 +2061,7 @@ mod tests {
     use std::collections::{BTreeMap, BTreeSet};
     use std::iter::FromIterator;
     use std::path::PathBuf;
-    use super::{OutputType, OutputTypes, Externs};
+    use super::{OutputType, OutputTypes, Externs, Lto};
     use rustc_back::{PanicStrategy, RelroLevel};
     use syntax::symbol::Symbol;
 
@@ -2069,6 +2116,21 @@ mod tests {
         assert!(test_items.next().is_none());
     }
 
+    #[test]
+    fn test_lto_flavors() {
+        let lto = |args: &[&str]| {
+            let args = args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+            let matches = optgroups().parse(&args).unwrap();
+            let (sessopts, _) = build_session_options_and_crate_config(&matches);
+            sessopts.cg.lto
+        };
+        assert_eq!(lto(&[]), Lto::No);
+        assert_eq!(lto(&["-Clto"]), Lto::Fat);
+        assert_eq!(lto(&["-Clto=fat"]), Lto::Fat);
+        assert_eq!(lto(&["-Clto=thin"]), Lto::Thin);
+        assert_eq!(lto(&["-Clto=off"]), Lto::No);
+    }
+
     #[test]
     fn test_can_print_warnings() {
         {
@@ -2483,7 +2545,7 @@ mod tests {
 
         // Make sure changing a [TRACKED] option changes the hash
         opts = reference.clone();
-        opts.cg.lto = true;
+        opts.cg.lto = Lto::Fat;
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
 
         opts = reference.clone();
@@ -2685,6 +2747,10 @@ mod tests {
         opts.debugging_opts.mir_opt_level = 3;
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
 
+        opts = reference.clone();
+        opts.debugging_opts.inline_mir = Some(false);
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
         opts = reference.clone();
         opts.debugging_opts.relro_level = Some(RelroLevel::Full);
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
@@ -26,6 +26,7 @@ use util::common::{duration_to_secs_str, ErrorReported};
 
 use syntax::ast::NodeId;
 use errors::{self, DiagnosticBuilder};
+use errors::apply::SuggestionCollector;
 use errors::emitter::{Emitter, EmitterWriter};
 use syntax::json::JsonEmitter;
 use syntax::feature_gate;
@@ -145,6 +146,10 @@ pub struct Session {
 
     /// Metadata about the allocators for the current crate being compiled
     pub has_global_allocator: Cell<bool>,
+
+    /// The machine-applicable suggestions of all diagnostics emitted so far,
+    /// only collected with `--apply-suggestions`.
+    pub collected_suggestions: Rc<RefCell<Vec<errors::CodeSuggestion>>>,
 }
 
 pub struct PerfStats {
@@ -418,9 +423,29 @@ impl Session {
             (self.opts.debugging_opts.mir_emit_validate > 0) ||
             self.opts.debugging_opts.borrowck_mir
     }
-    pub fn lto(&self) -> bool {
+    pub fn lto(&self) -> config::Lto {
         self.opts.cg.lto
     }
+    /// Rewrites the source files with the suggestions collected for
+    /// `--apply-suggestions`.
+    pub fn apply_suggestions(&self) {
+        // Reporting what was done emits diagnostics, so don't hold on to
+        // the collection meanwhile.
+        let suggestions = self.collected_suggestions.borrow().clone();
+        let codemap: &errors::CodeMapper = self.codemap();
+        for (file, result) in errors::apply::apply_suggestions(codemap, &suggestions) {
+            match result {
+                Ok(0) => {}
+                Ok(n) => {
+                    self.note_without_error(&format!("applied {} suggestion{} to {}",
+                                                     n, if n == 1 { "" } else { "s" }, file));
+                }
+                Err(e) => {
+                    self.warn(&format!("could not apply suggestions to {}: {}", file, e));
+                }
+            }
+        }
+    }
     /// Returns the panic strategy for this compile session. If the user explicitly selected one
     /// using '-C panic', use that, otherwise use the panic strategy defined by the target.
     pub fn panic_strategy(&self) -> PanicStrategy {
@@ -677,14 +702,16 @@ pub fn build_session_with_codemap(sopts: config::Options,
 
     let treat_err_as_bug = sopts.debugging_opts.treat_err_as_bug;
 
-    let emitter: Box<Emitter> = match (sopts.error_format, emitter_dest) {
+    let mut emitter: Box<Emitter> = match (sopts.error_format, emitter_dest) {
         (config::ErrorOutputType::HumanReadable(color_config), None) => {
             Box::new(EmitterWriter::stderr(color_config,
-                                           Some(codemap.clone())))
+                                           Some(codemap.clone()),
+                                           false))
         }
         (config::ErrorOutputType::HumanReadable(_), Some(dst)) => {
             Box::new(EmitterWriter::new(dst,
-                                        Some(codemap.clone())))
+                                        Some(codemap.clone()),
+                                        false))
         }
         (config::ErrorOutputType::Json, None) => {
             Box::new(JsonEmitter::stderr(Some(registry), codemap.clone()))
@@ -692,17 +719,33 @@ pub fn build_session_with_codemap(sopts: config::Options,
         (config::ErrorOutputType::Json, Some(dst)) => {
             Box::new(JsonEmitter::new(dst, Some(registry), codemap.clone()))
         }
+        (config::ErrorOutputType::Short(color_config), None) => {
+            Box::new(EmitterWriter::stderr(color_config,
+                                           Some(codemap.clone()),
+                                           true))
+        }
+        (config::ErrorOutputType::Short(_), Some(dst)) => {
+            Box::new(EmitterWriter::new(dst,
+                                        Some(codemap.clone()),
+                                        true))
+        }
     };
 
+    let collected_suggestions = Rc::new(RefCell::new(Vec::new()));
+    if sopts.apply_suggestions {
+        emitter = Box::new(SuggestionCollector::new(emitter, collected_suggestions.clone()));
+    }
+
     let diagnostic_handler =
         errors::Handler::with_emitter(can_print_warnings,
                                       treat_err_as_bug,
                                       emitter);
 
-    build_session_(sopts,
-                   local_crate_source_file,
-                   diagnostic_handler,
-                   codemap)
+    build_session_with_suggestions(sopts,
+                                   local_crate_source_file,
+                                   diagnostic_handler,
+                                   codemap,
+                                   collected_suggestions)
 }
 
 pub fn build_session_(sopts: config::Options,
@@ -710,6 +753,21 @@ pub fn build_session_(sopts: config::Options,
                       span_diagnostic: errors::Handler,
                       codemap: Rc<codemap::CodeMap>)
                       -> Session {
+    build_session_with_suggestions(sopts,
+                                   local_crate_source_file,
+                                   span_diagnostic,
+                                   codemap,
+                                   Rc::new(RefCell::new(Vec::new())))
+}
+
+/// Builds the session, sharing `collected_suggestions` with the
+/// `SuggestionCollector` of `span_diagnostic`, if it has one.
+fn build_session_with_suggestions(sopts: config::Options,
+                                  local_crate_source_file: Option<PathBuf>,
+                                  span_diagnostic: errors::Handler,
+                                  codemap: Rc<codemap::CodeMap>,
+                                  collected_suggestions: Rc<RefCell<Vec<errors::CodeSuggestion>>>)
+                                  -> Session {
     let host = match Target::search(config::host_triple()) {
         Ok(t) => t,
         Err(e) => {
@@ -804,6 +862,7 @@ pub fn build_session_(sopts: config::Options,
             (*GLOBAL_JOBSERVER).clone()
         },
         has_global_allocator: Cell::new(false),
+        collected_suggestions,
     };
 
     sess
@@ -839,9 +898,15 @@ pub fn early_error(output: config::ErrorOutputType, msg: &str) -> ! {
     let emitter: Box<Emitter> = match output {
         config::ErrorOutputType::HumanReadable(color_config) => {
             Box::new(EmitterWriter::stderr(color_config,
-                                           None))
+                                           None,
+                                           false))
         }
         config::ErrorOutputType::Json => Box::new(JsonEmitter::basic()),
+        config::ErrorOutputType::Short(color_config) => {
+            Box::new(EmitterWriter::stderr(color_config,
+                                           None,
+                                           true))
+        }
     };
     let handler = errors::Handler::with_emitter(true, false, emitter);
     handler.emit(&MultiSpan::new(), msg, errors::Level::Fatal);
@@ -852,9 +917,15 @@ pub fn early_warn(output: config::ErrorOutputType, msg: &str) {
     let emitter: Box<Emitter> = match output {
         config::ErrorOutputType::HumanReadable(color_config) => {
             Box::new(EmitterWriter::stderr(color_config,
-                                           None))
+                                           None,
+                                           false))
         }
         config::ErrorOutputType::Json => Box::new(JsonEmitter::basic()),
+        config::ErrorOutputType::Short(color_config) => {
+            Box::new(EmitterWriter::stderr(color_config,
+                                           None,
+                                           true))
+        }
     };
     let handler = errors::Handler::with_emitter(true, false, emitter);
     handler.emit(&MultiSpan::new(), msg, errors::Level::Warning);
@@ -1707,13 +1707,13 @@ impl<'a, 'tcx> Layout {
                 let element_size = element.size(dl);
                 let i = i as u64;
                 assert!(i < count);
-                Size::from_bytes(element_size.bytes() * count)
+                Size::from_bytes(element_size.bytes() * i)
             }
 
             Array { element_size, count, .. } => {
                 let i = i as u64;
                 assert!(i < count);
-                Size::from_bytes(element_size.bytes() * count)
+                Size::from_bytes(element_size.bytes() * i)
             }
 
             FatPointer { metadata, .. } => {
@@ -155,6 +155,11 @@ define_maps! { <'tcx>
     /// for trans. This is also the only query that can fetch non-local MIR, at present.
     [] fn optimized_mir: MirOptimized(DefId) -> &'tcx mir::Mir<'tcx>,
 
+    /// MIR of a constant, static or `const fn` as it is before the optimization
+    /// passes run, which is what constant evaluation interprets. Unlike
+    /// `mir_validated` this is never stolen, and it can be fetched for other crates.
+    [] fn mir_for_ctfe: MirForCtfe(DefId) -> &'tcx mir::Mir<'tcx>,
+
     /// Type of each closure. The def ID is the ID of the
     /// expression defining the closure.
     [] fn closure_kind: ClosureKind(DefId) -> ty::ClosureKind,
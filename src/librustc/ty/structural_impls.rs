@@ -471,6 +471,25 @@ impl<'a, 'tcx> Lift<'tcx> for const_val::ErrKind<'a> {
             ErroneousReferencedConstant(ref e) => {
                 return tcx.lift(e).map(ErroneousReferencedConstant)
             }
+            ErroneousConstFnCall(def_id, ref e) => {
+                return tcx.lift(e).map(|e| ErroneousConstFnCall(def_id, e))
+            }
+
+            DanglingPointerDeref => DanglingPointerDeref,
+            DerefFunctionPointer => DerefFunctionPointer,
+            InvalidPointerMath => InvalidPointerMath,
+            ReadPointerAsBytes => ReadPointerAsBytes,
+            ReadUndefBytes => ReadUndefBytes,
+            PointerOutOfBounds { offset, size, allocation_size } => {
+                PointerOutOfBounds { offset, size, allocation_size }
+            }
+            ModifiedConstantMemory => ModifiedConstantMemory,
+            InvalidBool => InvalidBool,
+            InvalidChar(c) => InvalidChar(c),
+            InvalidDiscriminant => InvalidDiscriminant,
+            UnreachableCode => UnreachableCode,
+            StackFrameLimitReached => StackFrameLimitReached,
+            StepLimitReached => StepLimitReached,
 
             TypeckError => TypeckError,
         })
@@ -0,0 +1,45 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use LinkerFlavor;
+use super::{Target, TargetOptions, TargetResult, PanicStrategy};
+
+pub fn target() -> TargetResult {
+    let opts = TargetOptions {
+        // Objects are plain LLVM bitcode which gets merged, lowered to the
+        // PNaCl ABI and frozen by rustc when producing the final `.pexe`, so
+        // neither the pnacl toolchain's linker nor its archiver are involved.
+        // The passes doing so only exist in PNaCl's LLVM though, so rustc has
+        // to be built against it to use this target.
+        dynamic_linking: false,
+        executables: true,
+        exe_suffix: ".pexe".to_string(),
+        obj_is_bitcode: true,
+        is_like_pnacl: true,
+        allow_asm: false,
+        max_atomic_width: Some(32),
+        // The stable ABI has no notion of zero-cost exceptions.
+        panic_strategy: PanicStrategy::Abort,
+        .. Default::default()
+    };
+    Ok(Target {
+        llvm_target: "le32-unknown-nacl".to_string(),
+        target_endian: "little".to_string(),
+        target_pointer_width: "32".to_string(),
+        target_c_int_width: "32".to_string(),
+        target_os: "nacl".to_string(),
+        target_env: "newlib".to_string(),
+        target_vendor: "unknown".to_string(),
+        data_layout: "e-i64:64:64-p:32:32:32-v128:32:32".to_string(),
+        arch: "le32".to_string(),
+        linker_flavor: LinkerFlavor::Gcc,
+        options: opts,
+    })
+}
@@ -0,0 +1,56 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// The wasm32-unknown-unknown target is a bare-bones wasm target which doesn't
+// go through emscripten at all. LLVM's own wasm backend produces object files
+// which are then linked with LLD's wasm personality, so the output is a plain
+// `.wasm` module with no JS shim and no assumptions about the host
+// environment.
+
+use {LinkerFlavor, LldFlavor};
+use super::{Target, TargetOptions, PanicStrategy};
+
+pub fn target() -> Result<Target, String> {
+    let opts = TargetOptions {
+        linker: "rust-lld".to_string(),
+
+        // There's no dynamic linking in wasm, every module is self-contained.
+        dynamic_linking: false,
+        executables: true,
+        exe_suffix: ".wasm".to_string(),
+        dll_prefix: "".to_string(),
+        dll_suffix: ".wasm".to_string(),
+        linker_is_gnu: false,
+
+        // There's no unwinding support for wasm yet, nor are there threads,
+        // so atomics are lowered to plain loads and stores.
+        panic_strategy: PanicStrategy::Abort,
+        max_atomic_width: Some(32),
+        allow_asm: false,
+
+        // Everything is statically placed in linear memory.
+        relocation_model: "static".to_string(),
+
+        .. Default::default()
+    };
+    Ok(Target {
+        llvm_target: "wasm32-unknown-unknown".to_string(),
+        target_endian: "little".to_string(),
+        target_pointer_width: "32".to_string(),
+        target_c_int_width: "32".to_string(),
+        target_os: "unknown".to_string(),
+        target_env: "".to_string(),
+        target_vendor: "unknown".to_string(),
+        data_layout: "e-m:e-p:32:32-i64:64-n32:64-S128".to_string(),
+        arch: "wasm32".to_string(),
+        linker_flavor: LinkerFlavor::Lld(LldFlavor::Wasm),
+        options: opts,
+    })
+}
@@ -34,6 +34,9 @@ use syntax_pos::Span;
 use std::cmp::Ordering;
 
 use rustc_const_math::*;
+
+use interpret;
+
 macro_rules! signal {
     ($e:expr, $exn:expr) => {
         return Err(ConstEvalErr { span: $e.span, kind: $exn })
@@ -615,10 +618,10 @@ fn cast_const_float<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     }
 }
 
-fn cast_const<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                        val: ConstVal<'tcx>,
-                        ty: Ty<'tcx>)
-                        -> CastResult<'tcx> {
+pub(crate) fn cast_const<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                   val: ConstVal<'tcx>,
+                                   ty: Ty<'tcx>)
+                                   -> CastResult<'tcx> {
     match val {
         Integral(i) => cast_const_int(tcx, i, ty),
         Bool(b) => cast_const_int(tcx, U8(b as u8), ty),
@@ -785,8 +788,22 @@ fn const_eval<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     };
 
     let tables = tcx.typeck_tables_of(def_id);
-    let body = if let Some(id) = tcx.hir.as_local_node_id(def_id) {
+    if tables.tainted_by_errors {
+        return Err(ConstEvalErr {
+            span: tcx.def_span(def_id),
+            kind: TypeckError
+        });
+    }
+
+    let local_id = tcx.hir.as_local_node_id(def_id);
+    if local_id.is_some() {
         tcx.mir_const_qualif(def_id);
+    }
+    if tcx.is_mir_available(def_id) {
+        return interpret::eval_body(tcx, key.param_env, def_id, substs);
+    }
+
+    let body = if let Some(id) = local_id {
         tcx.hir.body(tcx.hir.body_owned_by(id))
     } else {
         tcx.extern_const_body(def_id).body
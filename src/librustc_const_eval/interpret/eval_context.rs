@@ -0,0 +1,731 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use rustc::hir::def::Def;
+use rustc::hir::def_id::DefId;
+use rustc::infer::TransNormalize;
+use rustc::middle::const_val::{ByteArray, ConstAggregate, ConstEvalErr, ConstVal, ErrKind};
+use rustc::middle::const_val::EvalResult;
+use rustc::mir::{self, Local, Mir};
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc::ty::layout::{Layout, LayoutCx, LayoutTyper, TyLayout};
+use rustc::ty::subst::{Subst, Substs};
+use rustc::util::nodemap::FxHashMap;
+use rustc_const_math::{ConstFloat, ConstInt};
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use syntax::symbol::Symbol;
+
+use eval::lookup_const_by_id;
+
+use super::InterpResult;
+use super::memory::{Memory, Pointer};
+use super::value::PrimVal;
+
+/// The number of terminators a single evaluation may execute before it is
+/// assumed not to terminate.
+const STEP_LIMIT: usize = 1_000_000;
+
+pub struct EvalContext<'a, 'tcx: 'a> {
+    pub tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    pub param_env: ty::ParamEnv<'tcx>,
+    pub memory: Memory<'tcx>,
+    pub stack: Vec<Frame<'tcx>>,
+
+    /// The frozen allocations of the constants and statics used so far.
+    globals: FxHashMap<(DefId, &'tcx Substs<'tcx>), Pointer>,
+
+    pub steps_remaining: usize,
+}
+
+pub struct Frame<'tcx> {
+    pub mir: &'tcx Mir<'tcx>,
+    pub substs: &'tcx Substs<'tcx>,
+    pub locals: IndexVec<Local, Pointer>,
+}
+
+/// The result of evaluating a MIR lvalue.
+#[derive(Copy, Clone, Debug)]
+pub struct Lvalue {
+    pub ptr: Pointer,
+    pub extra: LvalueExtra,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LvalueExtra {
+    None,
+    /// The length of an unsized slice or `str`.
+    Length(u64),
+    /// The variant an enum was downcast to, for projecting its fields.
+    DowncastVariant(usize),
+}
+
+impl Lvalue {
+    pub fn from_ptr(ptr: Pointer) -> Lvalue {
+        Lvalue { ptr, extra: LvalueExtra::None }
+    }
+}
+
+/// Evaluates the constant `def_id` by interpreting its `mir_for_ctfe`.
+pub fn eval_body<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                           param_env: ty::ParamEnv<'tcx>,
+                           def_id: DefId,
+                           substs: &'tcx Substs<'tcx>)
+                           -> EvalResult<'tcx> {
+    let mut ecx = EvalContext::new(tcx, param_env);
+    let mir = tcx.mir_for_ctfe(def_id);
+    let span = mir.span;
+    let substs = tcx.erase_regions(&substs);
+    let ty = ecx.monomorphize_with(&mir.return_ty, substs);
+    let ptr = ecx.allocate_for(ty).map_err(|kind| ConstEvalErr { span, kind })?;
+    ecx.run_frame(mir, substs, true, ptr, &[])?;
+    ecx.read_const(ptr, ty).map_err(|kind| ConstEvalErr { span, kind })
+}
+
+impl<'a, 'tcx> EvalContext<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>, param_env: ty::ParamEnv<'tcx>) -> Self {
+        EvalContext {
+            tcx,
+            param_env,
+            memory: Memory::new(&tcx.data_layout),
+            stack: vec![],
+            globals: FxHashMap(),
+            steps_remaining: STEP_LIMIT,
+        }
+    }
+
+    pub fn frame(&self) -> &Frame<'tcx> {
+        self.stack.last().expect("no frame on the stack")
+    }
+
+    pub fn monomorphize<T: TransNormalize<'tcx>>(&self, value: &T) -> T {
+        self.monomorphize_with(value, self.frame().substs)
+    }
+
+    fn monomorphize_with<T>(&self, value: &T, substs: &'tcx Substs<'tcx>) -> T
+        where T: TransNormalize<'tcx>
+    {
+        let substituted = value.subst(self.tcx, substs);
+        self.tcx.normalize_associated_type_in_env(&substituted, self.param_env)
+    }
+
+    pub fn layout_of(&self, ty: Ty<'tcx>) -> InterpResult<'tcx, TyLayout<'tcx>> {
+        LayoutCx::new(self.tcx, self.param_env).layout_of(ty).map_err(ErrKind::LayoutError)
+    }
+
+    pub fn size_of(&self, ty: Ty<'tcx>) -> InterpResult<'tcx, u64> {
+        Ok(self.layout_of(ty)?.size(self.tcx).bytes())
+    }
+
+    pub fn allocate_for(&mut self, ty: Ty<'tcx>) -> InterpResult<'tcx, Pointer> {
+        let layout = self.layout_of(ty)?;
+        let size = layout.size(self.tcx).bytes();
+        let align = layout.align(self.tcx).abi();
+        Ok(self.memory.allocate(size, align))
+    }
+
+    /// The offset of field `i` of `layout` and the field's own layout.
+    pub fn field(&self, layout: TyLayout<'tcx>, i: usize)
+                 -> InterpResult<'tcx, (u64, TyLayout<'tcx>)> {
+        let offset = layout.field_offset(self.tcx, i).bytes();
+        let field = layout.field(LayoutCx::new(self.tcx, self.param_env), i)
+            .map_err(ErrKind::LayoutError)?;
+        Ok((offset, field))
+    }
+
+    /// Runs `mir` to completion in a new frame, leaving its return value in
+    /// `return_ptr`. The locals of the frame are deallocated on return
+    /// unless `keep_locals` is set, as they must be for the body of a
+    /// constant, which may return references to its temporaries.
+    pub fn run_frame(&mut self,
+                     mir: &'tcx Mir<'tcx>,
+                     substs: &'tcx Substs<'tcx>,
+                     keep_locals: bool,
+                     return_ptr: Pointer,
+                     args: &[Pointer])
+                     -> Result<(), ConstEvalErr<'tcx>> {
+        let span = mir.span;
+        if self.stack.len() >= self.tcx.sess.recursion_limit.get() {
+            return Err(ConstEvalErr { span, kind: ErrKind::StackFrameLimitReached });
+        }
+
+        let mut locals = IndexVec::with_capacity(mir.local_decls.len());
+        for (local, decl) in mir.local_decls.iter_enumerated() {
+            let ptr = if local == mir::RETURN_POINTER {
+                return_ptr
+            } else {
+                let ty = self.monomorphize_with(&decl.ty, substs);
+                self.allocate_for(ty).map_err(|kind| ConstEvalErr { span, kind })?
+            };
+            locals.push(ptr);
+        }
+        for (i, &arg) in args.iter().enumerate() {
+            let local = Local::new(i + 1);
+            let ty = self.monomorphize_with(&mir.local_decls[local].ty, substs);
+            self.size_of(ty)
+                .and_then(|size| self.memory.copy(arg, locals[local], size))
+                .map_err(|kind| ConstEvalErr { span, kind })?;
+        }
+
+        self.stack.push(Frame { mir, substs, locals });
+        let result = self.run_blocks();
+        let frame = self.stack.pop().unwrap();
+        if !keep_locals {
+            for (local, ptr) in frame.locals.into_iter_enumerated() {
+                if local != mir::RETURN_POINTER {
+                    self.memory.deallocate(ptr).expect("locals are always deallocatable");
+                }
+            }
+        }
+        result
+    }
+
+    fn run_blocks(&mut self) -> Result<(), ConstEvalErr<'tcx>> {
+        let mir = self.frame().mir;
+        let mut block = mir::START_BLOCK;
+        loop {
+            let data = &mir[block];
+            for statement in &data.statements {
+                let span = statement.source_info.span;
+                self.statement(statement).map_err(|kind| ConstEvalErr { span, kind })?;
+            }
+            let terminator = data.terminator();
+            let span = terminator.source_info.span;
+            match self.terminator(terminator).map_err(|kind| ConstEvalErr { span, kind })? {
+                Some(target) => block = target,
+                None => return Ok(()),
+            }
+        }
+    }
+
+    pub fn lvalue_ty(&self, lvalue: &mir::Lvalue<'tcx>) -> Ty<'tcx> {
+        let ty = lvalue.ty(self.frame().mir, self.tcx).to_ty(self.tcx);
+        self.monomorphize(&ty)
+    }
+
+    pub fn operand_ty(&self, operand: &mir::Operand<'tcx>) -> Ty<'tcx> {
+        let ty = operand.ty(self.frame().mir, self.tcx);
+        self.monomorphize(&ty)
+    }
+
+    pub fn eval_lvalue(&mut self, lvalue: &mir::Lvalue<'tcx>) -> InterpResult<'tcx, Lvalue> {
+        match *lvalue {
+            mir::Lvalue::Local(local) => Ok(Lvalue::from_ptr(self.frame().locals[local])),
+            mir::Lvalue::Static(ref s) => Ok(Lvalue::from_ptr(self.static_ptr(s.def_id)?)),
+            mir::Lvalue::Projection(ref proj) => {
+                let base = self.eval_lvalue(&proj.base)?;
+                let base_ty = self.lvalue_ty(&proj.base);
+                self.eval_projection(base, base_ty, &proj.elem)
+            }
+        }
+    }
+
+    fn eval_projection(&mut self,
+                       base: Lvalue,
+                       base_ty: Ty<'tcx>,
+                       elem: &mir::LvalueElem<'tcx>)
+                       -> InterpResult<'tcx, Lvalue> {
+        use rustc::mir::ProjectionElem::*;
+
+        match *elem {
+            Field(field, _) => {
+                let mut layout = self.layout_of(base_ty)?;
+                if let LvalueExtra::DowncastVariant(variant) = base.extra {
+                    layout = layout.for_variant(variant);
+                }
+                let offset = layout.field_offset(self.tcx, field.index()).bytes();
+                // Only the last field of a struct can be unsized, so the
+                // length of the whole also belongs to it.
+                let extra = match base.extra {
+                    LvalueExtra::Length(_) => base.extra,
+                    _ => LvalueExtra::None,
+                };
+                Ok(Lvalue { ptr: base.ptr.offset(offset), extra })
+            }
+
+            Deref => {
+                let pointee = match base_ty.builtin_deref(true, ty::NoPreference) {
+                    Some(tam) => tam.ty,
+                    None => bug!("deref of non-pointer type `{}`", base_ty),
+                };
+                let pointer_size = self.memory.pointer_size();
+                let ptr = self.memory.read_primval(base.ptr, pointer_size, false)?.to_ptr()?;
+                let extra = match self.tcx.struct_tail(pointee).sty {
+                    ty::TySlice(_) | ty::TyStr => {
+                        let len_ptr = base.ptr.offset(pointer_size);
+                        let len = self.memory.read_primval(len_ptr, pointer_size, false)?;
+                        LvalueExtra::Length(len.to_u64()?)
+                    }
+                    ty::TyDynamic(..) => {
+                        return Err(ErrKind::UnimplementedConstVal("trait objects"));
+                    }
+                    _ => LvalueExtra::None,
+                };
+                Ok(Lvalue { ptr, extra })
+            }
+
+            Index(local) => {
+                if !self.tcx.sess.features.borrow().const_indexing {
+                    return Err(ErrKind::IndexOpFeatureGated);
+                }
+                let pointer_size = self.memory.pointer_size();
+                let index_ptr = self.frame().locals[local];
+                let index = self.memory.read_primval(index_ptr, pointer_size, false)?.to_u64()?;
+                let (element_size, len) = self.element_size_and_len(base, base_ty)?;
+                if index >= len {
+                    return Err(ErrKind::IndexOutOfBounds { len, index });
+                }
+                Ok(Lvalue::from_ptr(base.ptr.offset(index * element_size)))
+            }
+
+            ConstantIndex { offset, from_end, .. } => {
+                let (element_size, len) = self.element_size_and_len(base, base_ty)?;
+                let index = if from_end {
+                    len - offset as u64
+                } else {
+                    offset as u64
+                };
+                Ok(Lvalue::from_ptr(base.ptr.offset(index * element_size)))
+            }
+
+            Subslice { from, to } => {
+                let (element_size, len) = self.element_size_and_len(base, base_ty)?;
+                let extra = match base_ty.sty {
+                    ty::TyArray(..) => LvalueExtra::None,
+                    _ => LvalueExtra::Length(len - from as u64 - to as u64),
+                };
+                Ok(Lvalue { ptr: base.ptr.offset(from as u64 * element_size), extra })
+            }
+
+            Downcast(_, variant) => {
+                Ok(Lvalue { ptr: base.ptr, extra: LvalueExtra::DowncastVariant(variant) })
+            }
+        }
+    }
+
+    pub fn element_size_and_len(&self, base: Lvalue, ty: Ty<'tcx>)
+                                -> InterpResult<'tcx, (u64, u64)> {
+        match *self.layout_of(ty)?.layout {
+            Layout::Array { sized: true, element_size, count, .. } => {
+                Ok((element_size.bytes(), count))
+            }
+            Layout::Array { sized: false, element_size, .. } => {
+                match base.extra {
+                    LvalueExtra::Length(len) => Ok((element_size.bytes(), len)),
+                    _ => bug!("unsized lvalue of type `{}` without a length", ty),
+                }
+            }
+            _ => bug!("indexing into non-array type `{}`", ty),
+        }
+    }
+
+    /// Evaluates `operand`, returning a pointer to its value.
+    pub fn eval_operand(&mut self, operand: &mir::Operand<'tcx>) -> InterpResult<'tcx, Pointer> {
+        match *operand {
+            mir::Operand::Consume(ref lvalue) => Ok(self.eval_lvalue(lvalue)?.ptr),
+            mir::Operand::Constant(ref constant) => match constant.literal {
+                mir::Literal::Value { value } => {
+                    let value = self.monomorphize(&value);
+                    self.const_to_ptr(value)
+                }
+                mir::Literal::Promoted { index } => {
+                    let mir = self.frame().mir;
+                    let substs = self.frame().substs;
+                    let promoted = &mir.promoted[index];
+                    let ty = self.monomorphize(&promoted.return_ty);
+                    let ptr = self.allocate_for(ty)?;
+                    // The promoted value lives in the same body, so report its
+                    // errors at the span the evaluation failed at.
+                    self.run_frame(promoted, substs, true, ptr, &[]).map_err(|e| e.kind)?;
+                    self.memory.freeze(ptr.alloc_id)?;
+                    Ok(ptr)
+                }
+            },
+        }
+    }
+
+    fn const_to_ptr(&mut self, value: &'tcx ty::Const<'tcx>) -> InterpResult<'tcx, Pointer> {
+        if let ConstVal::Unevaluated(def_id, substs) = value.val {
+            return self.global_ptr(def_id, substs);
+        }
+        let ptr = self.allocate_for(value.ty)?;
+        self.write_const(ptr, value)?;
+        self.memory.freeze(ptr.alloc_id)?;
+        Ok(ptr)
+    }
+
+    /// Evaluates the constant `def_id`, returning a pointer to its value.
+    /// Every use of the same constant shares one allocation.
+    ///
+    /// Constants with MIR are run in this context rather than through
+    /// `const_eval`, so their value doesn't have to be representable as a
+    /// `ConstVal`: it may contain references or enum variants with fields.
+    fn global_ptr(&mut self, def_id: DefId, substs: &'tcx Substs<'tcx>)
+                  -> InterpResult<'tcx, Pointer> {
+        let key = self.param_env.and((def_id, substs));
+        let (def_id, substs) = match lookup_const_by_id(self.tcx, key) {
+            Some(resolved) => resolved,
+            None => return Err(ErrKind::TypeckError),
+        };
+        if let Some(&ptr) = self.globals.get(&(def_id, substs)) {
+            return Ok(ptr);
+        }
+
+        let ptr = if self.tcx.is_mir_available(def_id) {
+            if def_id.is_local() && self.tcx.typeck_tables_of(def_id).tainted_by_errors {
+                return Err(ErrKind::TypeckError);
+            }
+            let mir = self.tcx.mir_for_ctfe(def_id);
+            let ty = self.monomorphize_with(&mir.return_ty, substs);
+            let ptr = self.allocate_for(ty)?;
+            self.run_frame(mir, substs, true, ptr, &[]).map_err(|e| match e.kind {
+                ErrKind::TypeckError => ErrKind::TypeckError,
+                _ => ErrKind::ErroneousReferencedConstant(box e),
+            })?;
+            self.memory.freeze(ptr.alloc_id)?;
+            ptr
+        } else {
+            let value = match self.tcx.const_eval(self.param_env.and((def_id, substs))) {
+                Ok(value) => value,
+                Err(ConstEvalErr { kind: ErrKind::TypeckError, .. }) => {
+                    return Err(ErrKind::TypeckError);
+                }
+                Err(err) => return Err(ErrKind::ErroneousReferencedConstant(box err)),
+            };
+            self.const_to_ptr(value)?
+        };
+        self.globals.insert((def_id, substs), ptr);
+        Ok(ptr)
+    }
+
+    fn static_ptr(&mut self, def_id: DefId) -> InterpResult<'tcx, Pointer> {
+        let substs = self.tcx.intern_substs(&[]);
+        if let Some(&ptr) = self.globals.get(&(def_id, substs)) {
+            return Ok(ptr);
+        }
+        if self.tcx.has_attr(def_id, "thread_local") {
+            return Err(ErrKind::UnimplementedConstVal("thread-local statics"));
+        }
+        if !self.tcx.is_mir_available(def_id) {
+            return Err(ErrKind::UnimplementedConstVal("statics without MIR"));
+        }
+
+        let mir = self.tcx.mir_for_ctfe(def_id);
+        let ty = self.monomorphize_with(&mir.return_ty, substs);
+        let ptr = self.allocate_for(ty)?;
+        // Statics may refer to themselves, so make the allocation known
+        // before running the initializer.
+        self.globals.insert((def_id, substs), ptr);
+        self.run_frame(mir, substs, true, ptr, &[]).map_err(|e| match e.kind {
+            ErrKind::TypeckError => ErrKind::TypeckError,
+            _ => ErrKind::ErroneousReferencedConstant(box e),
+        })?;
+        match self.tcx.describe_def(def_id) {
+            Some(Def::Static(_, true)) => {}
+            _ => self.memory.freeze(ptr.alloc_id)?,
+        }
+        Ok(ptr)
+    }
+
+    pub fn write_fat_ptr(&mut self, dest: Pointer, ptr: Pointer, len: u64)
+                         -> InterpResult<'tcx, ()> {
+        let pointer_size = self.memory.pointer_size();
+        self.memory.write_primval(dest, PrimVal::Ptr(ptr), pointer_size)?;
+        self.memory.write_primval(dest.offset(pointer_size), PrimVal::Bytes(len as u128),
+                                  pointer_size)
+    }
+
+    /// Reads the discriminant of the enum at `ptr`, as `Rvalue::Discriminant`
+    /// produces it: sign-extended for enums with a signed representation.
+    pub fn read_discriminant(&mut self, ptr: Pointer, ty: Ty<'tcx>) -> InterpResult<'tcx, u128> {
+        let adt = match ty.sty {
+            ty::TyAdt(adt, _) => adt,
+            _ => return Ok(0),
+        };
+        let layout = self.layout_of(ty)?;
+        let value = match *layout.layout {
+            Layout::CEnum { discr, signed, .. } => {
+                self.memory.read_primval(ptr, discr.size().bytes(), signed)?.to_bytes()?
+            }
+            Layout::General { discr, .. } => {
+                self.memory.read_primval(ptr, discr.size().bytes(), false)?.to_bytes()?
+            }
+            Layout::RawNullablePointer { nndiscr, value } => {
+                let size = value.size(self.tcx).bytes();
+                self.nullable_discriminant(ptr, size, nndiscr)?
+            }
+            Layout::StructWrappedNullablePointer { nndiscr, ref discrfield_source, .. } => {
+                let (offset, size) = self.nonnull_field(layout, nndiscr, discrfield_source)?;
+                self.nullable_discriminant(ptr.offset(offset), size, nndiscr)?
+            }
+            _ if adt.variants.is_empty() => return Err(ErrKind::UnreachableCode),
+            _ => return Ok(adt.discriminant_for_variant(self.tcx, 0).to_u128_unchecked()),
+        };
+        Ok(value)
+    }
+
+    fn nullable_discriminant(&mut self, ptr: Pointer, size: u64, nndiscr: u64)
+                             -> InterpResult<'tcx, u128> {
+        let null = match self.memory.read_primval(ptr, size, false)? {
+            PrimVal::Bytes(bits) => bits == 0,
+            PrimVal::Ptr(_) => false,
+            PrimVal::Undef => return Err(ErrKind::ReadUndefBytes),
+        };
+        Ok(if null { 1 - nndiscr as u128 } else { nndiscr as u128 })
+    }
+
+    /// The offset and size of the field whose null value marks the
+    /// dataless variant of a `StructWrappedNullablePointer` enum.
+    fn nonnull_field(&self, layout: TyLayout<'tcx>, nndiscr: u64, path: &[u32])
+                     -> InterpResult<'tcx, (u64, u64)> {
+        let mut layout = layout.for_variant(nndiscr as usize);
+        let mut offset = 0;
+        // The path starts with a 0 for LLVM's GEP, which we don't need.
+        for &i in &path[1..] {
+            let (field_offset, field) = self.field(layout, i as usize)?;
+            offset += field_offset;
+            layout = field;
+        }
+        Ok((offset, layout.size(self.tcx).bytes()))
+    }
+
+    pub fn read_variant_index(&mut self, ptr: Pointer, ty: Ty<'tcx>) -> InterpResult<'tcx, usize> {
+        let adt = match ty.sty {
+            ty::TyAdt(adt, _) => adt,
+            _ => bug!("reading the variant of non-enum type `{}`", ty),
+        };
+        let discr = self.read_discriminant(ptr, ty)?;
+        adt.discriminants(self.tcx)
+            .position(|d| d.to_u128_unchecked() == discr)
+            .ok_or(ErrKind::InvalidDiscriminant)
+    }
+
+    pub fn write_discriminant(&mut self, ptr: Pointer, ty: Ty<'tcx>, variant_index: usize)
+                              -> InterpResult<'tcx, ()> {
+        let adt = match ty.sty {
+            ty::TyAdt(adt, _) => adt,
+            _ => bug!("setting the discriminant of non-enum type `{}`", ty),
+        };
+        let layout = self.layout_of(ty)?;
+        match *layout.layout {
+            Layout::CEnum { discr, .. } | Layout::General { discr, .. } => {
+                let value = adt.discriminant_for_variant(self.tcx, variant_index);
+                self.memory.write_primval(ptr,
+                                          PrimVal::Bytes(value.to_u128_unchecked()),
+                                          discr.size().bytes())
+            }
+            Layout::RawNullablePointer { nndiscr, value } => {
+                if variant_index as u64 != nndiscr {
+                    let size = value.size(self.tcx).bytes();
+                    self.memory.write_primval(ptr, PrimVal::Bytes(0), size)?;
+                }
+                Ok(())
+            }
+            Layout::StructWrappedNullablePointer { nndiscr, ref discrfield_source, .. } => {
+                if variant_index as u64 != nndiscr {
+                    let (offset, size) = self.nonnull_field(layout, nndiscr, discrfield_source)?;
+                    self.memory.write_primval(ptr.offset(offset), PrimVal::Bytes(0), size)?;
+                }
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Writes a value computed by the HIR evaluator or found in the MIR
+    /// to memory.
+    pub fn write_const(&mut self, ptr: Pointer, value: &'tcx ty::Const<'tcx>)
+                       -> InterpResult<'tcx, ()> {
+        let ty = value.ty;
+        let size = self.size_of(ty)?;
+        match value.val {
+            ConstVal::Integral(i) => {
+                self.memory.write_primval(ptr, PrimVal::Bytes(i.to_u128_unchecked()), size)
+            }
+            ConstVal::Float(f) => self.memory.write_primval(ptr, PrimVal::Bytes(f.bits), size),
+            ConstVal::Bool(b) => self.memory.write_primval(ptr, PrimVal::from_bool(b), size),
+            ConstVal::Char(c) => self.memory.write_primval(ptr, PrimVal::Bytes(c as u128), size),
+            ConstVal::Str(ref s) => {
+                let bytes = self.memory.allocate_bytes(s.as_bytes());
+                self.write_fat_ptr(ptr, bytes, s.len() as u64)
+            }
+            ConstVal::ByteStr(b) => {
+                if let ty::TyArray(..) = ty.sty {
+                    return self.memory.write_bytes(ptr, b.data);
+                }
+                let bytes = self.memory.allocate_bytes(b.data);
+                let pointee = ty.builtin_deref(true, ty::NoPreference).map(|tam| tam.ty);
+                match pointee.map(|ty| &ty.sty) {
+                    Some(&ty::TySlice(_)) => self.write_fat_ptr(ptr, bytes, b.data.len() as u64),
+                    _ => self.memory.write_primval(ptr, PrimVal::Ptr(bytes), size),
+                }
+            }
+            ConstVal::Variant(did) => {
+                let index = match ty.sty {
+                    ty::TyAdt(adt, _) => adt.variant_index_with_id(did),
+                    _ => bug!("variant constant of non-enum type `{}`", ty),
+                };
+                self.write_discriminant(ptr, ty, index)
+            }
+            // Function items are zero-sized.
+            ConstVal::Function(..) => Ok(()),
+            ConstVal::Aggregate(ConstAggregate::Struct(fields)) => {
+                let variant = match ty.sty {
+                    ty::TyAdt(adt, _) => adt.struct_variant(),
+                    _ => bug!("struct constant of non-struct type `{}`", ty),
+                };
+                let layout = self.layout_of(ty)?;
+                for &(name, field) in fields {
+                    let i = variant.index_of_field_named(name)
+                        .expect("struct constant with unknown field");
+                    let offset = layout.field_offset(self.tcx, i).bytes();
+                    self.write_const(ptr.offset(offset), field)?;
+                }
+                Ok(())
+            }
+            ConstVal::Aggregate(ConstAggregate::Tuple(fields)) => {
+                let layout = self.layout_of(ty)?;
+                for (i, &field) in fields.iter().enumerate() {
+                    let offset = layout.field_offset(self.tcx, i).bytes();
+                    self.write_const(ptr.offset(offset), field)?;
+                }
+                Ok(())
+            }
+            ConstVal::Aggregate(ConstAggregate::Array(elements)) => {
+                let layout = self.layout_of(ty)?;
+                for (i, &element) in elements.iter().enumerate() {
+                    let offset = layout.field_offset(self.tcx, i).bytes();
+                    self.write_const(ptr.offset(offset), element)?;
+                }
+                Ok(())
+            }
+            ConstVal::Aggregate(ConstAggregate::Repeat(element, count)) => {
+                let element_size = self.size_of(element.ty)?;
+                for i in 0..count {
+                    self.write_const(ptr.offset(i * element_size), element)?;
+                }
+                Ok(())
+            }
+            ConstVal::Unevaluated(def_id, substs) => {
+                let src = self.global_ptr(def_id, substs)?;
+                self.memory.copy(src, ptr, size)
+            }
+        }
+    }
+
+    /// Reads a value of type `ty` back out of memory, once evaluation is
+    /// done. Only values the rest of the compiler can represent as a
+    /// `ConstVal` are supported: references to `str` and byte strings are,
+    /// but other references, raw and function pointers, and enum variants
+    /// with fields are reported as unimplemented.
+    pub fn read_const(&mut self, ptr: Pointer, ty: Ty<'tcx>)
+                      -> InterpResult<'tcx, &'tcx ty::Const<'tcx>> {
+        let tcx = self.tcx;
+        let size = self.size_of(ty)?;
+        let val = match ty.sty {
+            ty::TyBool => ConstVal::Bool(self.memory.read_primval(ptr, size, false)?.to_bool()?),
+            ty::TyChar => {
+                let bits = self.memory.read_primval(ptr, size, false)?.to_bytes()?;
+                match ::std::char::from_u32(bits as u32) {
+                    Some(c) if c as u128 == bits => ConstVal::Char(c),
+                    _ => return Err(ErrKind::InvalidChar(bits)),
+                }
+            }
+            ty::TyInt(ity) => {
+                let bits = self.memory.read_primval(ptr, size, true)?.to_bytes()?;
+                let isize_ty = tcx.sess.target.isize_ty;
+                ConstVal::Integral(ConstInt::new_signed_truncating(bits as i128, ity, isize_ty))
+            }
+            ty::TyUint(uty) => {
+                let bits = self.memory.read_primval(ptr, size, false)?.to_bytes()?;
+                let usize_ty = tcx.sess.target.usize_ty;
+                ConstVal::Integral(ConstInt::new_unsigned_truncating(bits, uty, usize_ty))
+            }
+            ty::TyFloat(fty) => {
+                let bits = self.memory.read_primval(ptr, size, false)?.to_bytes()?;
+                ConstVal::Float(ConstFloat { ty: fty, bits })
+            }
+            ty::TyFnDef(def_id, substs) => ConstVal::Function(def_id, substs),
+            ty::TyTuple(tys, _) => {
+                let layout = self.layout_of(ty)?;
+                let mut fields = Vec::with_capacity(tys.len());
+                for (i, &field_ty) in tys.iter().enumerate() {
+                    let offset = layout.field_offset(tcx, i).bytes();
+                    fields.push(self.read_const(ptr.offset(offset), field_ty)?);
+                }
+                ConstVal::Aggregate(ConstAggregate::Tuple(tcx.alloc_const_slice(&fields)))
+            }
+            ty::TyArray(element_ty, _) => {
+                let (element_size, count) = match *self.layout_of(ty)?.layout {
+                    Layout::Array { element_size, count, .. } => (element_size.bytes(), count),
+                    _ => bug!("array type `{}` without an array layout", ty),
+                };
+                let mut elements = Vec::with_capacity(count as usize);
+                for i in 0..count {
+                    elements.push(self.read_const(ptr.offset(i * element_size), element_ty)?);
+                }
+                ConstVal::Aggregate(ConstAggregate::Array(tcx.alloc_const_slice(&elements)))
+            }
+            ty::TyAdt(adt, substs) if adt.is_struct() => {
+                let layout = self.layout_of(ty)?;
+                let variant = adt.struct_variant();
+                let mut fields = Vec::with_capacity(variant.fields.len());
+                for (i, field) in variant.fields.iter().enumerate() {
+                    let field_ty = tcx.normalize_associated_type_in_env(&field.ty(tcx, substs),
+                                                                       self.param_env);
+                    let offset = layout.field_offset(tcx, i).bytes();
+                    fields.push((field.name, self.read_const(ptr.offset(offset), field_ty)?));
+                }
+                ConstVal::Aggregate(ConstAggregate::Struct(tcx.alloc_name_const_slice(&fields)))
+            }
+            ty::TyAdt(adt, _) if adt.is_enum() => {
+                let index = self.read_variant_index(ptr, ty)?;
+                let variant = &adt.variants[index];
+                if !variant.fields.is_empty() {
+                    return Err(ErrKind::UnimplementedConstVal("enum variants with fields"));
+                }
+                ConstVal::Variant(variant.did)
+            }
+            ty::TyRef(_, ty::TypeAndMut { ty: pointee, .. }) => {
+                let pointer_size = self.memory.pointer_size();
+                let data = self.memory.read_primval(ptr, pointer_size, false)?.to_ptr()?;
+                match pointee.sty {
+                    ty::TyStr => {
+                        let len_ptr = ptr.offset(pointer_size);
+                        let len = self.memory.read_primval(len_ptr, pointer_size, false)?;
+                        let bytes = self.memory.read_bytes(data, len.to_u64()?)?;
+                        match ::std::str::from_utf8(bytes) {
+                            Ok(s) => ConstVal::Str(Symbol::intern(s).as_str()),
+                            Err(_) => {
+                                return Err(ErrKind::UnimplementedConstVal("non-UTF-8 `str`"));
+                            }
+                        }
+                    }
+                    ty::TySlice(element_ty) if element_ty == tcx.types.u8 => {
+                        let len_ptr = ptr.offset(pointer_size);
+                        let len = self.memory.read_primval(len_ptr, pointer_size, false)?;
+                        let bytes = self.memory.read_bytes(data, len.to_u64()?)?;
+                        ConstVal::ByteStr(ByteArray { data: tcx.alloc_byte_array(bytes) })
+                    }
+                    ty::TyArray(element_ty, _) if element_ty == tcx.types.u8 => {
+                        let len = self.size_of(pointee)?;
+                        let bytes = self.memory.read_bytes(data, len)?;
+                        ConstVal::ByteStr(ByteArray { data: tcx.alloc_byte_array(bytes) })
+                    }
+                    _ => return Err(ErrKind::UnimplementedConstVal("constant references")),
+                }
+            }
+            ty::TyRawPtr(_) | ty::TyFnPtr(_) => {
+                return Err(ErrKind::UnimplementedConstVal("constant pointers"));
+            }
+            _ => return Err(ErrKind::UnimplementedConstVal("constants of this type")),
+        };
+        Ok(tcx.mk_const(ty::Const { val, ty }))
+    }
+}
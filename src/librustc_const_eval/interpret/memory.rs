@@ -0,0 +1,415 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The interpreter's model of memory.
+//!
+//! Every local, constant, static and promoted value lives in an
+//! `Allocation`: a byte buffer plus a map of *relocations*, recording which
+//! bytes hold a pointer into another allocation, and a mask of the bytes
+//! that have been initialized. This lets constants create and follow
+//! pointers without ever needing a concrete address.
+
+use rustc::hir::def_id::DefId;
+use rustc::middle::const_val::ErrKind;
+use rustc::ty::layout::{Endian, TargetDataLayout};
+use rustc::ty::subst::Substs;
+use rustc::util::nodemap::FxHashMap;
+
+use std::collections::BTreeMap;
+
+use super::InterpResult;
+use super::value::{PrimVal, sign_extend, truncate};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AllocId(u64);
+
+/// A pointer into an allocation. Only meaningful while the allocation it
+/// points to is alive.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Pointer {
+    pub alloc_id: AllocId,
+    pub offset: u64,
+}
+
+impl Pointer {
+    pub fn offset(self, bytes: u64) -> Pointer {
+        Pointer { alloc_id: self.alloc_id, offset: self.offset.wrapping_add(bytes) }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Allocation {
+    pub bytes: Vec<u8>,
+    /// Maps offsets of pointer-sized values in `bytes` to the allocation
+    /// they point into. The pointer's offset is stored in `bytes` itself.
+    pub relocations: BTreeMap<u64, AllocId>,
+    pub undef_mask: UndefMask,
+    pub align: u64,
+    /// Allocations for constants and immutable statics are frozen once
+    /// initialized; writing to them is an error.
+    pub mutable: bool,
+}
+
+/// One bit per byte of an allocation, set when the byte has been written.
+#[derive(Clone, Debug)]
+pub struct UndefMask {
+    blocks: Vec<u64>,
+    len: u64,
+}
+
+impl UndefMask {
+    fn new(len: u64) -> UndefMask {
+        UndefMask {
+            blocks: vec![0; ((len + 63) / 64) as usize],
+            len,
+        }
+    }
+
+    pub fn get(&self, i: u64) -> bool {
+        assert!(i < self.len);
+        self.blocks[(i / 64) as usize] & (1 << (i % 64)) != 0
+    }
+
+    fn set(&mut self, i: u64, defined: bool) {
+        assert!(i < self.len);
+        let block = &mut self.blocks[(i / 64) as usize];
+        if defined {
+            *block |= 1 << (i % 64);
+        } else {
+            *block &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn is_range_defined(&self, start: u64, end: u64) -> bool {
+        (start..end).all(|i| self.get(i))
+    }
+
+    fn set_range(&mut self, start: u64, end: u64, defined: bool) {
+        for i in start..end {
+            self.set(i, defined);
+        }
+    }
+}
+
+pub struct Memory<'tcx> {
+    alloc_map: FxHashMap<AllocId, Allocation>,
+
+    /// Function pointers are allocations without any bytes; these
+    /// record which function each of them stands for.
+    functions: FxHashMap<AllocId, (DefId, &'tcx Substs<'tcx>)>,
+    function_alloc_cache: FxHashMap<(DefId, &'tcx Substs<'tcx>), AllocId>,
+
+    next_id: u64,
+    endian: Endian,
+    pointer_size: u64,
+}
+
+impl<'tcx> Memory<'tcx> {
+    pub fn new(data_layout: &TargetDataLayout) -> Self {
+        Memory {
+            alloc_map: FxHashMap(),
+            functions: FxHashMap(),
+            function_alloc_cache: FxHashMap(),
+            next_id: 0,
+            endian: data_layout.endian,
+            pointer_size: data_layout.pointer_size.bytes(),
+        }
+    }
+
+    pub fn pointer_size(&self) -> u64 {
+        self.pointer_size
+    }
+
+    fn next_id(&mut self) -> AllocId {
+        let id = AllocId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+
+    pub fn allocate(&mut self, size: u64, align: u64) -> Pointer {
+        let alloc = Allocation {
+            bytes: vec![0; size as usize],
+            relocations: BTreeMap::new(),
+            undef_mask: UndefMask::new(size),
+            align,
+            mutable: true,
+        };
+        let id = self.next_id();
+        self.alloc_map.insert(id, alloc);
+        Pointer { alloc_id: id, offset: 0 }
+    }
+
+    /// Allocates immutable memory holding `bytes`, e.g. for string literals.
+    pub fn allocate_bytes(&mut self, bytes: &[u8]) -> Pointer {
+        let ptr = self.allocate(bytes.len() as u64, 1);
+        {
+            let alloc = self.alloc_map.get_mut(&ptr.alloc_id).unwrap();
+            alloc.bytes.copy_from_slice(bytes);
+            alloc.undef_mask.set_range(0, bytes.len() as u64, true);
+            alloc.mutable = false;
+        }
+        ptr
+    }
+
+    pub fn create_fn_alloc(&mut self, def_id: DefId, substs: &'tcx Substs<'tcx>) -> Pointer {
+        if let Some(&id) = self.function_alloc_cache.get(&(def_id, substs)) {
+            return Pointer { alloc_id: id, offset: 0 };
+        }
+        let id = self.next_id();
+        self.functions.insert(id, (def_id, substs));
+        self.function_alloc_cache.insert((def_id, substs), id);
+        Pointer { alloc_id: id, offset: 0 }
+    }
+
+    pub fn deallocate(&mut self, ptr: Pointer) -> InterpResult<'tcx, ()> {
+        if ptr.offset != 0 {
+            return Err(ErrKind::InvalidPointerMath);
+        }
+        match self.alloc_map.remove(&ptr.alloc_id) {
+            Some(_) => Ok(()),
+            None => Err(ErrKind::DanglingPointerDeref),
+        }
+    }
+
+    /// Makes an allocation immutable, after its initializer has run.
+    pub fn freeze(&mut self, id: AllocId) -> InterpResult<'tcx, ()> {
+        self.get_mut(id)?.mutable = false;
+        Ok(())
+    }
+
+    pub fn get(&self, id: AllocId) -> InterpResult<'tcx, &Allocation> {
+        match self.alloc_map.get(&id) {
+            Some(alloc) => Ok(alloc),
+            None if self.functions.contains_key(&id) => Err(ErrKind::DerefFunctionPointer),
+            None => Err(ErrKind::DanglingPointerDeref),
+        }
+    }
+
+    fn get_mut(&mut self, id: AllocId) -> InterpResult<'tcx, &mut Allocation> {
+        if self.functions.contains_key(&id) {
+            return Err(ErrKind::DerefFunctionPointer);
+        }
+        let alloc = match self.alloc_map.get_mut(&id) {
+            Some(alloc) => alloc,
+            None => return Err(ErrKind::DanglingPointerDeref),
+        };
+        if !alloc.mutable {
+            return Err(ErrKind::ModifiedConstantMemory);
+        }
+        Ok(alloc)
+    }
+
+    pub fn get_fn(&self, ptr: Pointer) -> InterpResult<'tcx, (DefId, &'tcx Substs<'tcx>)> {
+        if ptr.offset != 0 {
+            return Err(ErrKind::InvalidPointerMath);
+        }
+        match self.functions.get(&ptr.alloc_id) {
+            Some(&function) => Ok(function),
+            None => Err(ErrKind::DanglingPointerDeref),
+        }
+    }
+
+    fn check_bounds(alloc: &Allocation, ptr: Pointer, size: u64) -> InterpResult<'tcx, ()> {
+        let allocation_size = alloc.bytes.len() as u64;
+        match ptr.offset.checked_add(size) {
+            Some(end) if end <= allocation_size => Ok(()),
+            _ => Err(ErrKind::PointerOutOfBounds {
+                offset: ptr.offset,
+                size,
+                allocation_size,
+            }),
+        }
+    }
+
+    /// Reads `size` initialized bytes that are not part of a pointer.
+    pub fn read_bytes(&self, ptr: Pointer, size: u64) -> InterpResult<'tcx, &[u8]> {
+        if size == 0 {
+            return Ok(&[]);
+        }
+        let alloc = self.get(ptr.alloc_id)?;
+        Self::check_bounds(alloc, ptr, size)?;
+        let (start, end) = (ptr.offset, ptr.offset + size);
+        if self.relocations(alloc, start, end).next().is_some() {
+            return Err(ErrKind::ReadPointerAsBytes);
+        }
+        if !alloc.undef_mask.is_range_defined(start, end) {
+            return Err(ErrKind::ReadUndefBytes);
+        }
+        Ok(&alloc.bytes[start as usize..end as usize])
+    }
+
+    pub fn write_bytes(&mut self, ptr: Pointer, src: &[u8]) -> InterpResult<'tcx, ()> {
+        let size = src.len() as u64;
+        if size == 0 {
+            return Ok(());
+        }
+        let pointer_size = self.pointer_size();
+        let alloc = self.get_mut(ptr.alloc_id)?;
+        Self::check_bounds(alloc, ptr, size)?;
+        let (start, end) = (ptr.offset, ptr.offset + size);
+        Self::clear_relocations(alloc, pointer_size, start, end);
+        alloc.bytes[start as usize..end as usize].copy_from_slice(src);
+        alloc.undef_mask.set_range(start, end, true);
+        Ok(())
+    }
+
+    /// Copies `size` bytes, including relocations and undefined bytes.
+    /// The source and destination may overlap.
+    pub fn copy(&mut self, src: Pointer, dest: Pointer, size: u64) -> InterpResult<'tcx, ()> {
+        if size == 0 {
+            return Ok(());
+        }
+        let (bytes, relocations, defined) = {
+            let alloc = self.get(src.alloc_id)?;
+            Self::check_bounds(alloc, src, size)?;
+            let (start, end) = (src.offset, src.offset + size);
+            let bytes = alloc.bytes[start as usize..end as usize].to_vec();
+            let relocations: Vec<_> = self.relocations(alloc, start, end)
+                .map(|(&offset, &id)| (offset - start, id))
+                .collect();
+            let defined: Vec<_> = (start..end).map(|i| alloc.undef_mask.get(i)).collect();
+            (bytes, relocations, defined)
+        };
+
+        let pointer_size = self.pointer_size();
+        let alloc = self.get_mut(dest.alloc_id)?;
+        Self::check_bounds(alloc, dest, size)?;
+        let (start, end) = (dest.offset, dest.offset + size);
+        Self::clear_relocations(alloc, pointer_size, start, end);
+        alloc.bytes[start as usize..end as usize].copy_from_slice(&bytes);
+        for (i, defined) in defined.into_iter().enumerate() {
+            alloc.undef_mask.set(start + i as u64, defined);
+        }
+        for (offset, id) in relocations {
+            alloc.relocations.insert(start + offset, id);
+        }
+        Ok(())
+    }
+
+    /// Reads an integer or pointer of `size` bytes. Reading a pointer
+    /// requires reading exactly the bytes it was written to.
+    pub fn read_primval(&self, ptr: Pointer, size: u64, signed: bool)
+                        -> InterpResult<'tcx, PrimVal> {
+        if size == 0 {
+            return Ok(PrimVal::Bytes(0));
+        }
+        let alloc = self.get(ptr.alloc_id)?;
+        Self::check_bounds(alloc, ptr, size)?;
+        let (start, end) = (ptr.offset, ptr.offset + size);
+        if !alloc.undef_mask.is_range_defined(start, end) {
+            return Ok(PrimVal::Undef);
+        }
+
+        let bytes = &alloc.bytes[start as usize..end as usize];
+        let bits = read_target_uint(self.endian, bytes);
+        let relocations: Vec<_> = self.relocations(alloc, start, end).collect();
+        match relocations.len() {
+            0 if signed => Ok(PrimVal::Bytes(sign_extend(bits, size))),
+            0 => Ok(PrimVal::Bytes(bits)),
+            1 if *relocations[0].0 == start && size == self.pointer_size() => {
+                Ok(PrimVal::Ptr(Pointer { alloc_id: *relocations[0].1, offset: bits as u64 }))
+            }
+            _ => Err(ErrKind::ReadPointerAsBytes),
+        }
+    }
+
+    pub fn write_primval(&mut self, ptr: Pointer, val: PrimVal, size: u64)
+                         -> InterpResult<'tcx, ()> {
+        if size == 0 {
+            return Ok(());
+        }
+        let endian = self.endian;
+        let pointer_size = self.pointer_size();
+        let alloc = self.get_mut(ptr.alloc_id)?;
+        Self::check_bounds(alloc, ptr, size)?;
+        let (start, end) = (ptr.offset, ptr.offset + size);
+        Self::clear_relocations(alloc, pointer_size, start, end);
+        let bits = match val {
+            PrimVal::Bytes(bits) => truncate(bits, size),
+            PrimVal::Ptr(p) => {
+                assert_eq!(size, pointer_size);
+                alloc.relocations.insert(start, p.alloc_id);
+                p.offset as u128
+            }
+            PrimVal::Undef => {
+                alloc.undef_mask.set_range(start, end, false);
+                return Ok(());
+            }
+        };
+        write_target_uint(endian, &mut alloc.bytes[start as usize..end as usize], bits);
+        alloc.undef_mask.set_range(start, end, true);
+        Ok(())
+    }
+
+    pub fn mark_undef(&mut self, ptr: Pointer, size: u64) -> InterpResult<'tcx, ()> {
+        self.write_primval(ptr, PrimVal::Undef, size)
+    }
+
+    /// Relocations that overlap `start..end`, including pointers that
+    /// start before `start` but extend into the range.
+    fn relocations<'b>(&self, alloc: &'b Allocation, start: u64, end: u64)
+                       -> ::std::collections::btree_map::Range<'b, u64, AllocId> {
+        let first = start.saturating_sub(self.pointer_size() - 1);
+        alloc.relocations.range(first..end)
+    }
+
+    /// Removes the relocations overlapping `start..end`. Bytes of a removed
+    /// pointer that lie outside the range become undefined, as the rest of
+    /// the pointer is meaningless without it.
+    fn clear_relocations(alloc: &mut Allocation, pointer_size: u64, start: u64, end: u64) {
+        let first = start.saturating_sub(pointer_size - 1);
+        let overlapping: Vec<u64> = alloc.relocations.range(first..end)
+            .map(|(&offset, _)| offset)
+            .collect();
+        for offset in overlapping {
+            alloc.relocations.remove(&offset);
+            if offset < start {
+                alloc.undef_mask.set_range(offset, start, false);
+            }
+            if offset + pointer_size > end {
+                alloc.undef_mask.set_range(end, offset + pointer_size, false);
+            }
+        }
+    }
+}
+
+fn read_target_uint(endian: Endian, bytes: &[u8]) -> u128 {
+    let mut value = 0u128;
+    match endian {
+        Endian::Little => {
+            for &b in bytes.iter().rev() {
+                value = (value << 8) | b as u128;
+            }
+        }
+        Endian::Big => {
+            for &b in bytes {
+                value = (value << 8) | b as u128;
+            }
+        }
+    }
+    value
+}
+
+fn write_target_uint(endian: Endian, bytes: &mut [u8], mut value: u128) {
+    match endian {
+        Endian::Little => {
+            for b in bytes.iter_mut() {
+                *b = value as u8;
+                value >>= 8;
+            }
+        }
+        Endian::Big => {
+            for b in bytes.iter_mut().rev() {
+                *b = value as u8;
+                value >>= 8;
+            }
+        }
+    }
+}
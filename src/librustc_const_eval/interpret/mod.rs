@@ -0,0 +1,35 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An interpreter for MIR, used to evaluate constants.
+//!
+//! Unlike the HIR evaluator in `eval`, this works on the MIR of a constant
+//! as it is before optimizations (the `mir_for_ctfe` query), so `const fn`s
+//! may use `let` bindings, loops and `match`. Values live in a model of
+//! memory (see `memory`), where references and raw pointers can be created
+//! and followed, and other constants are evaluated in the same memory.
+//!
+//! Only the value of the constant being evaluated is turned back into a
+//! `ConstVal`, once the whole body has run. That can't represent pointers
+//! other than references to `str` and byte strings, nor enum variants with
+//! fields, so constants with such values are reported as unimplemented,
+//! even though other constants may use them.
+
+mod eval_context;
+mod memory;
+mod operator;
+mod step;
+mod value;
+
+pub use self::eval_context::eval_body;
+
+use rustc::middle::const_val::ErrKind;
+
+pub type InterpResult<'tcx, T> = Result<T, ErrKind<'tcx>>;
@@ -0,0 +1,342 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Arithmetic, comparisons and casts.
+//!
+//! Integer and float arithmetic reuses `ConstInt` and `ConstFloat`, so
+//! overflow and division by zero are detected exactly as in the HIR
+//! evaluator.
+
+use rustc::middle::const_val::{ConstVal, ErrKind};
+use rustc::mir::{self, BinOp, CastKind, UnOp};
+use rustc::ty::{self, Ty};
+use rustc::ty::layout::Layout;
+use rustc::ty::util::IntTypeExt;
+use rustc_const_math::{ConstFloat, ConstInt, ConstMathErr, Op};
+use syntax::ast;
+
+use std::cmp::Ordering;
+
+use eval::cast_const;
+
+use super::InterpResult;
+use super::eval_context::EvalContext;
+use super::memory::Pointer;
+use super::value::PrimVal;
+
+/// The `ConstMathErr` operation corresponding to an arithmetic `BinOp`.
+pub fn math_op(op: BinOp) -> Op {
+    match op {
+        BinOp::Add => Op::Add,
+        BinOp::Sub => Op::Sub,
+        BinOp::Mul => Op::Mul,
+        BinOp::Div => Op::Div,
+        BinOp::Rem => Op::Rem,
+        BinOp::Shl => Op::Shl,
+        BinOp::Shr => Op::Shr,
+        BinOp::BitAnd => Op::BitAnd,
+        BinOp::BitOr => Op::BitOr,
+        BinOp::BitXor => Op::BitXor,
+        _ => bug!("`{:?}` cannot overflow", op),
+    }
+}
+
+fn compare<T: PartialOrd>(op: BinOp, l: T, r: T) -> PrimVal {
+    PrimVal::from_bool(match op {
+        BinOp::Eq => l == r,
+        BinOp::Ne => l != r,
+        BinOp::Lt => l < r,
+        BinOp::Le => l <= r,
+        BinOp::Gt => l > r,
+        BinOp::Ge => l >= r,
+        _ => bug!("`{:?}` is not a comparison", op),
+    })
+}
+
+fn is_comparison(op: BinOp) -> bool {
+    match op {
+        BinOp::Eq | BinOp::Ne | BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => true,
+        _ => false,
+    }
+}
+
+impl<'a, 'tcx> EvalContext<'a, 'tcx> {
+    fn eval_operand_primval(&mut self, operand: &mir::Operand<'tcx>)
+                            -> InterpResult<'tcx, (PrimVal, Ty<'tcx>)> {
+        let ty = self.operand_ty(operand);
+        let ptr = self.eval_operand(operand)?;
+        let size = self.size_of(ty)?;
+        let signed = match ty.sty {
+            ty::TyInt(_) => true,
+            _ => false,
+        };
+        Ok((self.memory.read_primval(ptr, size, signed)?, ty))
+    }
+
+    fn to_const_int(&self, bits: u128, ty: Ty<'tcx>) -> ConstInt {
+        let target = &self.tcx.sess.target;
+        match ty.sty {
+            ty::TyInt(ity) => ConstInt::new_signed_truncating(bits as i128, ity, target.isize_ty),
+            ty::TyUint(uty) => ConstInt::new_unsigned_truncating(bits, uty, target.usize_ty),
+            ty::TyBool => ConstInt::U8(bits as u8),
+            ty::TyChar => ConstInt::U32(bits as u32),
+            // Pointers that were cast from integers.
+            ty::TyRawPtr(_) | ty::TyRef(..) | ty::TyFnPtr(_) => {
+                ConstInt::new_unsigned_truncating(bits, ast::UintTy::Us, target.usize_ty)
+            }
+            _ => bug!("integer operation on `{}`", ty),
+        }
+    }
+
+    /// Evaluates `op`, returning the result and whether it overflowed.
+    pub fn binary_op(&mut self,
+                     op: BinOp,
+                     left: &mir::Operand<'tcx>,
+                     right: &mir::Operand<'tcx>)
+                     -> InterpResult<'tcx, (PrimVal, bool)> {
+        let (l, left_ty) = self.eval_operand_primval(left)?;
+        let (r, right_ty) = self.eval_operand_primval(right)?;
+        if let PrimVal::Ptr(_) = l {
+            return self.ptr_op(op, l, r, left_ty).map(|value| (value, false));
+        }
+        if let PrimVal::Ptr(_) = r {
+            return self.ptr_op(op, l, r, left_ty).map(|value| (value, false));
+        }
+        let (l, r) = (l.to_bytes()?, r.to_bytes()?);
+
+        if let ty::TyFloat(fty) = left_ty.sty {
+            if is_comparison(op) {
+                // `ConstFloat::try_cmp` doesn't handle NaN, so compare on the
+                // host, which is exact for IEEE floats.
+                let result = match fty {
+                    ast::FloatTy::F32 => compare(op, f32::from_bits(l as u32),
+                                                 f32::from_bits(r as u32)),
+                    ast::FloatTy::F64 => compare(op, f64::from_bits(l as u64),
+                                                 f64::from_bits(r as u64)),
+                };
+                return Ok((result, false));
+            }
+            let l = ConstFloat { ty: fty, bits: l };
+            let r = ConstFloat { ty: fty, bits: r };
+            let result = match op {
+                BinOp::Add => l + r,
+                BinOp::Sub => l - r,
+                BinOp::Mul => l * r,
+                BinOp::Div => l / r,
+                BinOp::Rem => l % r,
+                _ => bug!("invalid float operation `{:?}`", op),
+            };
+            return Ok((PrimVal::Bytes(result.map_err(ErrKind::Math)?.bits), false));
+        }
+
+        if op == BinOp::Offset {
+            // An integer cast to a pointer; it never points anywhere, so just
+            // do the arithmetic.
+            let pointee_size = self.pointee_size(left_ty)?;
+            let offset = (r as i128).wrapping_mul(pointee_size as i128);
+            return Ok((PrimVal::Bytes((l as i128).wrapping_add(offset) as u128), false));
+        }
+
+        let l = self.to_const_int(l, left_ty);
+        let r = self.to_const_int(r, right_ty);
+        if is_comparison(op) {
+            let ordering = l.try_cmp(r).map_err(ErrKind::Math)?;
+            return Ok((compare(op, ordering, Ordering::Equal), false));
+        }
+        let result = match op {
+            BinOp::Add => l + r,
+            BinOp::Sub => l - r,
+            BinOp::Mul => l * r,
+            BinOp::Div => l / r,
+            BinOp::Rem => l % r,
+            BinOp::Shl => l << r,
+            BinOp::Shr => l >> r,
+            BinOp::BitAnd => l & r,
+            BinOp::BitOr => l | r,
+            BinOp::BitXor => l ^ r,
+            _ => bug!("invalid integer operation `{:?}`", op),
+        };
+        match result {
+            Ok(value) => Ok((PrimVal::Bytes(value.to_u128_unchecked()), false)),
+            // The value is never used; an `Assert` or the caller reports
+            // the overflow.
+            Err(ConstMathErr::Overflow(_)) => Ok((PrimVal::Undef, true)),
+            Err(err) => Err(ErrKind::Math(err)),
+        }
+    }
+
+    /// Operations involving a pointer into an allocation. Such pointers
+    /// have no address yet, so only comparisons between pointers into the
+    /// same allocation and offsetting them are meaningful.
+    fn ptr_op(&mut self, op: BinOp, l: PrimVal, r: PrimVal, left_ty: Ty<'tcx>)
+              -> InterpResult<'tcx, PrimVal> {
+        match (op, l, r) {
+            (BinOp::Offset, PrimVal::Ptr(ptr), PrimVal::Bytes(n)) => {
+                let pointee_size = self.pointee_size(left_ty)?;
+                let offset = (n as i128).wrapping_mul(pointee_size as i128);
+                Ok(PrimVal::Ptr(ptr.offset(offset as u64)))
+            }
+            (_, PrimVal::Ptr(a), PrimVal::Ptr(b)) if is_comparison(op) => {
+                if a.alloc_id == b.alloc_id {
+                    Ok(compare(op, a.offset, b.offset))
+                } else if op == BinOp::Eq || op == BinOp::Ne {
+                    Ok(PrimVal::from_bool(op == BinOp::Ne))
+                } else {
+                    Err(ErrKind::InvalidPointerMath)
+                }
+            }
+            // Pointers into allocations are never null.
+            (BinOp::Eq, PrimVal::Ptr(_), PrimVal::Bytes(0)) |
+            (BinOp::Eq, PrimVal::Bytes(0), PrimVal::Ptr(_)) => Ok(PrimVal::from_bool(false)),
+            (BinOp::Ne, PrimVal::Ptr(_), PrimVal::Bytes(0)) |
+            (BinOp::Ne, PrimVal::Bytes(0), PrimVal::Ptr(_)) => Ok(PrimVal::from_bool(true)),
+            (_, PrimVal::Undef, _) | (_, _, PrimVal::Undef) => Err(ErrKind::ReadUndefBytes),
+            _ => Err(ErrKind::InvalidPointerMath),
+        }
+    }
+
+    fn pointee_size(&self, ty: Ty<'tcx>) -> InterpResult<'tcx, u64> {
+        match ty.builtin_deref(true, ty::NoPreference) {
+            Some(tam) => self.size_of(tam.ty),
+            None => bug!("offsetting non-pointer type `{}`", ty),
+        }
+    }
+
+    pub fn unary_op(&mut self, op: UnOp, operand: &mir::Operand<'tcx>)
+                    -> InterpResult<'tcx, PrimVal> {
+        let (value, ty) = self.eval_operand_primval(operand)?;
+        let bits = value.to_bytes()?;
+        match (op, &ty.sty) {
+            (UnOp::Not, &ty::TyBool) => Ok(PrimVal::from_bool(!value.to_bool()?)),
+            (UnOp::Neg, &ty::TyFloat(fty)) => {
+                Ok(PrimVal::Bytes((-ConstFloat { ty: fty, bits }).bits))
+            }
+            _ => {
+                let value = self.to_const_int(bits, ty);
+                let result = match op {
+                    UnOp::Not => !value,
+                    UnOp::Neg => -value,
+                };
+                Ok(PrimVal::Bytes(result.map_err(ErrKind::Math)?.to_u128_unchecked()))
+            }
+        }
+    }
+
+    pub fn cast(&mut self,
+                kind: CastKind,
+                operand: &mir::Operand<'tcx>,
+                dest_ty: Ty<'tcx>,
+                dest: Pointer)
+                -> InterpResult<'tcx, ()> {
+        let src_ty = self.operand_ty(operand);
+        let pointer_size = self.memory.pointer_size();
+        match kind {
+            CastKind::Misc => self.misc_cast(operand, src_ty, dest_ty, dest),
+
+            CastKind::ReifyFnPointer => match src_ty.sty {
+                ty::TyFnDef(def_id, substs) => {
+                    let f = self.memory.create_fn_alloc(def_id, substs);
+                    self.memory.write_primval(dest, PrimVal::Ptr(f), pointer_size)
+                }
+                _ => bug!("reifying non-function type `{}`", src_ty),
+            },
+
+            // Only the type changes.
+            CastKind::UnsafeFnPointer => {
+                let src = self.eval_operand(operand)?;
+                self.memory.copy(src, dest, pointer_size)
+            }
+
+            CastKind::ClosureFnPointer => Err(ErrKind::UnimplementedConstVal("closures")),
+
+            CastKind::Unsize => {
+                let src_pointee = src_ty.builtin_deref(true, ty::NoPreference).map(|tam| tam.ty);
+                let dest_pointee = dest_ty.builtin_deref(true, ty::NoPreference).map(|tam| tam.ty);
+                match (src_pointee.map(|ty| &ty.sty), dest_pointee.map(|ty| &ty.sty)) {
+                    (Some(&ty::TyArray(..)), Some(&ty::TySlice(_))) => {
+                        let len = match *self.layout_of(src_pointee.unwrap())?.layout {
+                            Layout::Array { count, .. } => count,
+                            _ => bug!("array type without an array layout"),
+                        };
+                        let src = self.eval_operand(operand)?;
+                        let ptr = self.memory.read_primval(src, pointer_size, false)?.to_ptr()?;
+                        self.write_fat_ptr(dest, ptr, len)
+                    }
+                    _ => Err(ErrKind::UnimplementedConstVal("unsizing casts")),
+                }
+            }
+        }
+    }
+
+    fn misc_cast(&mut self,
+                 operand: &mir::Operand<'tcx>,
+                 src_ty: Ty<'tcx>,
+                 dest_ty: Ty<'tcx>,
+                 dest: Pointer)
+                 -> InterpResult<'tcx, ()> {
+        let pointer_size = self.memory.pointer_size();
+        let dest_size = self.size_of(dest_ty)?;
+        let is_ptr = |ty: Ty<'tcx>| match ty.sty {
+            ty::TyRawPtr(_) | ty::TyRef(..) | ty::TyFnPtr(_) => true,
+            _ => false,
+        };
+
+        if is_ptr(src_ty) && is_ptr(dest_ty) {
+            // Casting a fat pointer to a thin one keeps the data pointer.
+            let src = self.eval_operand(operand)?;
+            return self.memory.copy(src, dest, dest_size);
+        }
+
+        let (src_ty, value) = match src_ty.sty {
+            ty::TyAdt(adt, _) if adt.is_enum() => {
+                let src = self.eval_operand(operand)?;
+                let discr = self.read_discriminant(src, src_ty)?;
+                (adt.repr.discr_type().to_ty(self.tcx), PrimVal::Bytes(discr))
+            }
+            _ => {
+                let (value, _) = self.eval_operand_primval(operand)?;
+                (src_ty, value)
+            }
+        };
+        if is_ptr(src_ty) {
+            // Pointers into allocations have no address to cast to an integer.
+            let bits = value.to_bytes()?;
+            let value = ConstVal::Integral(self.to_const_int(bits, src_ty));
+            return self.write_cast(value, dest_ty, dest, dest_size);
+        }
+        let bits = value.to_bytes()?;
+        if is_ptr(dest_ty) {
+            return self.memory.write_primval(dest, PrimVal::Bytes(bits), pointer_size);
+        }
+
+        let value = match src_ty.sty {
+            ty::TyBool => ConstVal::Bool(value.to_bool()?),
+            ty::TyChar => match ::std::char::from_u32(bits as u32) {
+                Some(c) => ConstVal::Char(c),
+                None => return Err(ErrKind::InvalidChar(bits)),
+            },
+            ty::TyFloat(fty) => ConstVal::Float(ConstFloat { ty: fty, bits }),
+            _ => ConstVal::Integral(self.to_const_int(bits, src_ty)),
+        };
+        self.write_cast(value, dest_ty, dest, dest_size)
+    }
+
+    fn write_cast(&mut self, value: ConstVal<'tcx>, dest_ty: Ty<'tcx>, dest: Pointer, size: u64)
+                  -> InterpResult<'tcx, ()> {
+        let bits = match cast_const(self.tcx, value, dest_ty)? {
+            ConstVal::Float(f) => f.bits,
+            value => match value.to_const_int() {
+                Some(i) => i.to_u128_unchecked(),
+                None => bug!("cast to `{}` produced {:?}", dest_ty, value),
+            },
+        };
+        self.memory.write_primval(dest, PrimVal::Bytes(bits), size)
+    }
+}
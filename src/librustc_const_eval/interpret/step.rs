@@ -0,0 +1,322 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Executing statements, rvalues and terminators.
+
+use rustc::hir::def::Def;
+use rustc::hir::def_id::DefId;
+use rustc::middle::const_val::ErrKind;
+use rustc::mir;
+use rustc::ty::{self, Ty};
+use rustc::ty::subst::Substs;
+use rustc_const_math::ConstMathErr;
+use syntax::abi::Abi;
+
+use super::InterpResult;
+use super::eval_context::{EvalContext, LvalueExtra};
+use super::memory::Pointer;
+use super::operator::math_op;
+use super::value::{PrimVal, truncate};
+
+impl<'a, 'tcx> EvalContext<'a, 'tcx> {
+    pub fn statement(&mut self, statement: &mir::Statement<'tcx>) -> InterpResult<'tcx, ()> {
+        use rustc::mir::StatementKind::*;
+
+        match statement.kind {
+            Assign(ref lvalue, ref rvalue) => {
+                let dest = self.eval_lvalue(lvalue)?.ptr;
+                let dest_ty = self.lvalue_ty(lvalue);
+                self.eval_rvalue_into(rvalue, dest, dest_ty)
+            }
+            SetDiscriminant { ref lvalue, variant_index } => {
+                let dest = self.eval_lvalue(lvalue)?.ptr;
+                let dest_ty = self.lvalue_ty(lvalue);
+                self.write_discriminant(dest, dest_ty, variant_index)
+            }
+            // Locals are allocated for the whole frame, and a constant may
+            // keep referring to its temporaries after their scope ends.
+            StorageLive(_) | StorageDead(_) => Ok(()),
+            InlineAsm { .. } => Err(ErrKind::UnimplementedConstVal("inline assembly")),
+            Validate(..) | EndRegion(_) | Nop => Ok(()),
+        }
+    }
+
+    fn eval_rvalue_into(&mut self, rvalue: &mir::Rvalue<'tcx>, dest: Pointer, dest_ty: Ty<'tcx>)
+                        -> InterpResult<'tcx, ()> {
+        if let mir::Rvalue::Use(ref operand) = *rvalue {
+            return self.copy_operand(operand, dest);
+        }
+        // The operands may overlap `dest`, as in `x = (x.1, x.0)`, so build
+        // the value on the side first.
+        let tmp = self.allocate_for(dest_ty)?;
+        self.eval_rvalue(rvalue, tmp, dest_ty)?;
+        let size = self.size_of(dest_ty)?;
+        self.memory.copy(tmp, dest, size)?;
+        self.memory.deallocate(tmp)
+    }
+
+    fn eval_rvalue(&mut self, rvalue: &mir::Rvalue<'tcx>, dest: Pointer, dest_ty: Ty<'tcx>)
+                   -> InterpResult<'tcx, ()> {
+        use rustc::mir::Rvalue::*;
+
+        let pointer_size = self.memory.pointer_size();
+        match *rvalue {
+            Use(ref operand) => self.copy_operand(operand, dest),
+
+            Repeat(ref operand, count) => {
+                let element_size = self.size_of(self.operand_ty(operand))?;
+                let src = self.eval_operand(operand)?;
+                for i in 0..count.as_u64() {
+                    self.memory.copy(src, dest.offset(i * element_size), element_size)?;
+                }
+                Ok(())
+            }
+
+            Ref(_, _, ref lvalue) => {
+                let lvalue = self.eval_lvalue(lvalue)?;
+                match lvalue.extra {
+                    LvalueExtra::Length(len) => {
+                        self.write_fat_ptr(dest, lvalue.ptr, len)
+                    }
+                    _ => self.memory.write_primval(dest, PrimVal::Ptr(lvalue.ptr), pointer_size),
+                }
+            }
+
+            Len(ref lvalue) => {
+                let ty = self.lvalue_ty(lvalue);
+                let lvalue = self.eval_lvalue(lvalue)?;
+                let (_, len) = self.element_size_and_len(lvalue, ty)?;
+                self.memory.write_primval(dest, PrimVal::Bytes(len as u128), pointer_size)
+            }
+
+            Cast(kind, ref operand, _) => self.cast(kind, operand, dest_ty, dest),
+
+            BinaryOp(op, ref left, ref right) => {
+                let (value, overflowed) = self.binary_op(op, left, right)?;
+                if overflowed {
+                    return Err(ErrKind::Math(ConstMathErr::Overflow(math_op(op))));
+                }
+                let size = self.size_of(dest_ty)?;
+                self.memory.write_primval(dest, value, size)
+            }
+
+            CheckedBinaryOp(op, ref left, ref right) => {
+                let (value, overflowed) = self.binary_op(op, left, right)?;
+                let layout = self.layout_of(dest_ty)?;
+                let (_, value_layout) = self.field(layout, 0)?;
+                let (flag_offset, _) = self.field(layout, 1)?;
+                let size = value_layout.size(self.tcx).bytes();
+                self.memory.write_primval(dest, value, size)?;
+                let flag = PrimVal::from_bool(overflowed);
+                self.memory.write_primval(dest.offset(flag_offset), flag, 1)
+            }
+
+            NullaryOp(mir::NullOp::SizeOf, ty) => {
+                let size = self.size_of(self.monomorphize(&ty))?;
+                self.memory.write_primval(dest, PrimVal::Bytes(size as u128), pointer_size)
+            }
+
+            NullaryOp(mir::NullOp::Box, _) => Err(ErrKind::UnimplementedConstVal("box")),
+
+            UnaryOp(op, ref operand) => {
+                let value = self.unary_op(op, operand)?;
+                let size = self.size_of(dest_ty)?;
+                self.memory.write_primval(dest, value, size)
+            }
+
+            Discriminant(ref lvalue) => {
+                let ty = self.lvalue_ty(lvalue);
+                let ptr = self.eval_lvalue(lvalue)?.ptr;
+                let discr = self.read_discriminant(ptr, ty)?;
+                let size = self.size_of(dest_ty)?;
+                self.memory.write_primval(dest, PrimVal::Bytes(discr), size)
+            }
+
+            Aggregate(ref kind, ref operands) => {
+                let mut layout = self.layout_of(dest_ty)?;
+                match **kind {
+                    mir::AggregateKind::Array(_) | mir::AggregateKind::Tuple |
+                    mir::AggregateKind::Closure(..) => {
+                        for (i, operand) in operands.iter().enumerate() {
+                            let offset = layout.field_offset(self.tcx, i).bytes();
+                            self.copy_operand(operand, dest.offset(offset))?;
+                        }
+                        Ok(())
+                    }
+                    mir::AggregateKind::Adt(adt, variant, _, active_field) => {
+                        if adt.is_enum() {
+                            layout = layout.for_variant(variant);
+                        }
+                        for (i, operand) in operands.iter().enumerate() {
+                            let field = active_field.unwrap_or(i);
+                            let offset = layout.field_offset(self.tcx, field).bytes();
+                            self.copy_operand(operand, dest.offset(offset))?;
+                        }
+                        if adt.is_enum() {
+                            self.write_discriminant(dest, dest_ty, variant)?;
+                        }
+                        Ok(())
+                    }
+                    mir::AggregateKind::Generator(..) => {
+                        Err(ErrKind::UnimplementedConstVal("generators"))
+                    }
+                }
+            }
+        }
+    }
+
+    fn copy_operand(&mut self, operand: &mir::Operand<'tcx>, dest: Pointer)
+                    -> InterpResult<'tcx, ()> {
+        let size = self.size_of(self.operand_ty(operand))?;
+        let src = self.eval_operand(operand)?;
+        self.memory.copy(src, dest, size)
+    }
+
+    /// Executes `terminator`, returning the block to continue with or
+    /// `None` if the current frame returned.
+    pub fn terminator(&mut self, terminator: &mir::Terminator<'tcx>)
+                      -> InterpResult<'tcx, Option<mir::BasicBlock>> {
+        use rustc::mir::TerminatorKind::*;
+
+        if self.steps_remaining == 0 {
+            return Err(ErrKind::StepLimitReached);
+        }
+        self.steps_remaining -= 1;
+
+        match terminator.kind {
+            Goto { target } => Ok(Some(target)),
+
+            SwitchInt { ref discr, switch_ty, ref values, ref targets } => {
+                let switch_ty = self.monomorphize(&switch_ty);
+                let size = self.size_of(switch_ty)?;
+                let ptr = self.eval_operand(discr)?;
+                let bits = self.memory.read_primval(ptr, size, false)?.to_bytes()?;
+                for (value, &target) in values.iter().zip(targets) {
+                    if truncate(value.to_u128_unchecked(), size) == bits {
+                        return Ok(Some(target));
+                    }
+                }
+                Ok(Some(*targets.last().unwrap()))
+            }
+
+            Return => Ok(None),
+
+            // Only types without drop glue can be dropped in constants.
+            Drop { target, .. } => Ok(Some(target)),
+
+            DropAndReplace { ref location, ref value, target, .. } => {
+                let dest = self.eval_lvalue(location)?.ptr;
+                self.copy_operand(value, dest)?;
+                Ok(Some(target))
+            }
+
+            Call { ref func, ref args, ref destination, .. } => {
+                self.call(func, args, destination).map(Some)
+            }
+
+            Assert { ref cond, expected, ref msg, target, .. } => {
+                let ptr = self.eval_operand(cond)?;
+                if self.memory.read_primval(ptr, 1, false)?.to_bool()? == expected {
+                    return Ok(Some(target));
+                }
+                match *msg {
+                    mir::AssertMessage::BoundsCheck { ref len, ref index } => {
+                        let pointer_size = self.memory.pointer_size();
+                        let len_ptr = self.eval_operand(len)?;
+                        let len = self.memory.read_primval(len_ptr, pointer_size, false)?;
+                        let index_ptr = self.eval_operand(index)?;
+                        let index = self.memory.read_primval(index_ptr, pointer_size, false)?;
+                        Err(ErrKind::IndexOutOfBounds {
+                            len: len.to_u64()?,
+                            index: index.to_u64()?,
+                        })
+                    }
+                    mir::AssertMessage::Math(ref err) => Err(ErrKind::Math(err.clone())),
+                    mir::AssertMessage::GeneratorResumedAfterReturn |
+                    mir::AssertMessage::GeneratorResumedAfterPanic => {
+                        Err(ErrKind::UnimplementedConstVal("generators"))
+                    }
+                }
+            }
+
+            Unreachable | Resume => Err(ErrKind::UnreachableCode),
+
+            Yield { .. } | GeneratorDrop => Err(ErrKind::UnimplementedConstVal("generators")),
+        }
+    }
+
+    fn call(&mut self,
+            func: &mir::Operand<'tcx>,
+            args: &[mir::Operand<'tcx>],
+            destination: &Option<(mir::Lvalue<'tcx>, mir::BasicBlock)>)
+            -> InterpResult<'tcx, mir::BasicBlock> {
+        let func_ty = self.operand_ty(func);
+        let (def_id, substs) = match func_ty.sty {
+            ty::TyFnDef(def_id, substs) => (def_id, substs),
+            ty::TyFnPtr(_) => {
+                let pointer_size = self.memory.pointer_size();
+                let ptr = self.eval_operand(func)?;
+                let fn_ptr = self.memory.read_primval(ptr, pointer_size, false)?.to_ptr()?;
+                self.memory.get_fn(fn_ptr)?
+            }
+            _ => bug!("calling non-function type `{}`", func_ty),
+        };
+        let (dest, target) = match *destination {
+            Some((ref lvalue, target)) => (self.eval_lvalue(lvalue)?.ptr, Some(target)),
+            None => (self.memory.allocate(0, 1), None),
+        };
+
+        match self.tcx.fn_sig(def_id).abi() {
+            Abi::RustIntrinsic | Abi::PlatformIntrinsic => {
+                self.call_intrinsic(def_id, substs, dest)?;
+                return target.ok_or(ErrKind::UnreachableCode);
+            }
+            _ => {}
+        }
+
+        // Calls to anything else were already reported by `qualify_consts`.
+        let is_ctor = match self.tcx.describe_def(def_id) {
+            Some(Def::StructCtor(..)) | Some(Def::VariantCtor(..)) => true,
+            _ => false,
+        };
+        if !is_ctor && !self.tcx.is_const_fn(def_id) {
+            return Err(ErrKind::TypeckError);
+        }
+        if !self.tcx.is_mir_available(def_id) {
+            return Err(ErrKind::UnimplementedConstVal("calling a `const fn` without MIR"));
+        }
+
+        let mut arg_ptrs = Vec::with_capacity(args.len());
+        for arg in args {
+            arg_ptrs.push(self.eval_operand(arg)?);
+        }
+        let mir = self.tcx.mir_for_ctfe(def_id);
+        self.run_frame(mir, substs, false, dest, &arg_ptrs).map_err(|e| match e.kind {
+            ErrKind::TypeckError => ErrKind::TypeckError,
+            _ => ErrKind::ErroneousConstFnCall(def_id, box e),
+        })?;
+        target.ok_or(ErrKind::UnreachableCode)
+    }
+
+    fn call_intrinsic(&mut self,
+                      def_id: DefId,
+                      substs: &'tcx Substs<'tcx>,
+                      dest: Pointer)
+                      -> InterpResult<'tcx, ()> {
+        let pointer_size = self.memory.pointer_size();
+        let value = match &*self.tcx.item_name(def_id) {
+            "size_of" => self.size_of(substs.type_at(0))?,
+            "min_align_of" => self.layout_of(substs.type_at(0))?.align(self.tcx).abi(),
+            // Calls to other intrinsics were already reported by `qualify_consts`.
+            _ => return Err(ErrKind::TypeckError),
+        };
+        self.memory.write_primval(dest, PrimVal::Bytes(value as u128), pointer_size)
+    }
+}
@@ -0,0 +1,87 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Scalar values as seen by the interpreter.
+
+use rustc::middle::const_val::ErrKind;
+
+use super::InterpResult;
+use super::memory::Pointer;
+
+/// A value small enough to be read from or written to memory in one go:
+/// an integer, float, `bool` or `char` (as raw bits), or a pointer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PrimVal {
+    /// The raw bits of the value, zero-extended to 128 bits.
+    Bytes(u128),
+
+    /// A pointer into an allocation. Pointers have no integer value
+    /// at compile-time, so they are kept apart from `Bytes`.
+    Ptr(Pointer),
+
+    /// Memory that was never written to.
+    Undef,
+}
+
+impl PrimVal {
+    pub fn from_bool(b: bool) -> PrimVal {
+        PrimVal::Bytes(b as u128)
+    }
+
+    pub fn to_bytes<'tcx>(self) -> InterpResult<'tcx, u128> {
+        match self {
+            PrimVal::Bytes(b) => Ok(b),
+            PrimVal::Ptr(_) => Err(ErrKind::ReadPointerAsBytes),
+            PrimVal::Undef => Err(ErrKind::ReadUndefBytes),
+        }
+    }
+
+    pub fn to_ptr<'tcx>(self) -> InterpResult<'tcx, Pointer> {
+        match self {
+            PrimVal::Ptr(p) => Ok(p),
+            // Integers cast to pointers don't point anywhere.
+            PrimVal::Bytes(_) => Err(ErrKind::DanglingPointerDeref),
+            PrimVal::Undef => Err(ErrKind::ReadUndefBytes),
+        }
+    }
+
+    pub fn to_u64<'tcx>(self) -> InterpResult<'tcx, u64> {
+        let b = self.to_bytes()?;
+        assert_eq!(b as u64 as u128, b);
+        Ok(b as u64)
+    }
+
+    pub fn to_bool<'tcx>(self) -> InterpResult<'tcx, bool> {
+        match self.to_bytes()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ErrKind::InvalidBool),
+        }
+    }
+}
+
+/// Truncates `value` to its lowest `size` bytes.
+pub fn truncate(value: u128, size: u64) -> u128 {
+    if size >= 16 {
+        value
+    } else {
+        value & ((1 << (size * 8)) - 1)
+    }
+}
+
+/// Sign-extends the lowest `size` bytes of `value` to 128 bits.
+pub fn sign_extend(value: u128, size: u64) -> u128 {
+    if size == 0 || size >= 16 {
+        value
+    } else {
+        let shift = 128 - size * 8;
+        (((value << shift) as i128) >> shift) as u128
+    }
+}
@@ -43,6 +43,7 @@ extern crate syntax_pos;
 mod diagnostics;
 
 mod eval;
+mod interpret;
 mod _match;
 pub mod check_match;
 pub mod pattern;
@@ -87,6 +87,7 @@ use std::env;
 use std::ffi::OsString;
 use std::io::{self, Read, Write};
 use std::iter::repeat;
+use std::panic;
 use std::path::PathBuf;
 use std::process::{self, Command, Stdio};
 use std::rc::Rc;
@@ -138,7 +139,9 @@ pub fn run<F>(run_compiler: F) -> isize
                 }
                 None => {
                     let emitter =
-                        errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto, None);
+                        errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto,
+                                                               None,
+                                                               false);
                     let handler = errors::Handler::with_emitter(true, false, Box::new(emitter));
                     handler.emit(&MultiSpan::new(),
                                  "aborting due to previous error(s)",
@@ -247,14 +250,40 @@ pub fn run_compiler<'a>(args: &[String],
 
     let plugins = sess.opts.debugging_opts.extra_plugins.clone();
     let control = callbacks.build_controller(&sess, &matches);
-    (driver::compile_input(&sess,
-                           &cstore,
-                           &input,
-                           &odir,
-                           &ofile,
-                           Some(plugins),
-                           &control),
-     Some(sess))
+    if !sess.opts.apply_suggestions {
+        let result = driver::compile_input(&sess,
+                                           &cstore,
+                                           &input,
+                                           &odir,
+                                           &ofile,
+                                           Some(plugins),
+                                           &control);
+        return (result, Some(sess));
+    }
+
+    // Fatal errors unwind out of the compiler, and the suggestions collected
+    // until then are just as applicable as with any other error.
+    let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+        driver::compile_input(&sess,
+                              &cstore,
+                              &input,
+                              &odir,
+                              &ofile,
+                              Some(plugins),
+                              &control)
+    }));
+    match result {
+        Ok(result) => {
+            sess.apply_suggestions();
+            (result, Some(sess))
+        }
+        Err(value) => {
+            if value.is::<errors::FatalError>() {
+                sess.apply_suggestions();
+            }
+            panic::resume_unwind(value)
+        }
+    }
 }
 
 // Extract output directory and file from matches.
@@ -1208,7 +1237,9 @@ pub fn monitor<F: FnOnce() + Send + 'static>(f: F) {
         // Thread panicked without emitting a fatal diagnostic
         if !value.is::<errors::FatalError>() {
             let emitter =
-                Box::new(errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto, None));
+                Box::new(errors::emitter::EmitterWriter::stderr(errors::ColorConfig::Auto,
+                                                                None,
+                                                                false));
             let handler = errors::Handler::with_emitter(true, false, emitter);
 
             // a .span_bug or .bug call has already printed what
@@ -0,0 +1,180 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Rewriting source files with the machine-applicable suggestions made by
+//! the compiler, as requested by `--apply-suggestions`.
+
+use {Applicability, CodeMapper, CodeSuggestion, DiagnosticBuilder};
+use emitter::Emitter;
+use syntax_pos::{FileMap, FileName, NO_EXPANSION};
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::rc::Rc;
+
+/// An emitter which records the machine-applicable suggestions of every
+/// diagnostic it sees before handing the diagnostic on to another emitter.
+pub struct SuggestionCollector {
+    inner: Box<Emitter>,
+    suggestions: Rc<RefCell<Vec<CodeSuggestion>>>,
+}
+
+impl SuggestionCollector {
+    pub fn new(inner: Box<Emitter>,
+               suggestions: Rc<RefCell<Vec<CodeSuggestion>>>)
+               -> SuggestionCollector {
+        SuggestionCollector {
+            inner,
+            suggestions,
+        }
+    }
+}
+
+impl Emitter for SuggestionCollector {
+    fn emit(&mut self, db: &DiagnosticBuilder) {
+        self.suggestions.borrow_mut().extend(db.suggestions.iter().filter(|sugg| {
+            sugg.applicability == Applicability::MachineApplicable
+        }).cloned());
+        self.inner.emit(db);
+    }
+}
+
+/// Replacement of the bytes `lo..hi` of a file.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Edit {
+    lo: usize,
+    hi: usize,
+    replacement: String,
+}
+
+impl Edit {
+    fn conflicts_with(&self, other: &Edit) -> bool {
+        // Two insertions at the same position conflict too, as there's no
+        // telling which one should go first.
+        self.lo == other.lo || (self.lo < other.hi && other.lo < self.hi)
+    }
+}
+
+/// Applies `suggestions` to the files they point into, rewriting those
+/// files in place. Returns, for each file touched, either the number of
+/// suggestions applied to it or why it couldn't be rewritten.
+///
+/// Suggestions with more than one alternative, or which point into macro
+/// expansions or span several files, are never applied. Neither is any
+/// suggestion overlapping one that was already accepted.
+pub fn apply_suggestions(cm: &CodeMapper,
+                         suggestions: &[CodeSuggestion])
+                         -> Vec<(FileName, Result<usize, String>)> {
+    let mut files: BTreeMap<FileName, (Rc<FileMap>, Vec<Vec<Edit>>)> = BTreeMap::new();
+
+    'suggestions: for sugg in suggestions {
+        if sugg.substitution_parts.iter().any(|part| part.substitutions.len() != 1) {
+            continue
+        }
+
+        let mut filemap: Option<Rc<FileMap>> = None;
+        let mut edits = Vec::with_capacity(sugg.substitution_parts.len());
+        for part in &sugg.substitution_parts {
+            if part.span.ctxt() != NO_EXPANSION {
+                continue 'suggestions
+            }
+            let lo = cm.lookup_char_pos(part.span.lo());
+            let hi = cm.lookup_char_pos(part.span.hi());
+            if !lo.file.is_real_file() || lo.file.name != hi.file.name {
+                continue 'suggestions
+            }
+            if filemap.as_ref().map_or(false, |fm| fm.name != lo.file.name) {
+                continue 'suggestions
+            }
+
+            let start_pos = lo.file.start_pos.0;
+            edits.push(Edit {
+                lo: (part.span.lo().0 - start_pos) as usize,
+                hi: (part.span.hi().0 - start_pos) as usize,
+                replacement: part.substitutions[0].clone(),
+            });
+            filemap = Some(lo.file);
+        }
+
+        if let Some(filemap) = filemap {
+            edits.sort();
+            files.entry(filemap.name.clone())
+                 .or_insert_with(|| (filemap, Vec::new()))
+                 .1
+                 .push(edits);
+        }
+    }
+
+    files.into_iter().map(|(name, (filemap, suggestions))| {
+        (name, apply_to_file(&filemap, suggestions))
+    }).collect()
+}
+
+fn apply_to_file(filemap: &FileMap, suggestions: Vec<Vec<Edit>>) -> Result<usize, String> {
+    if filemap.name_was_remapped {
+        return Err(format!("its path was remapped"))
+    }
+
+    let mut src = String::new();
+    File::open(&filemap.name)
+        .and_then(|mut f| f.read_to_string(&mut src))
+        .map_err(|e| e.to_string())?;
+
+    // The compiler never sees the byte order mark, so neither do our spans.
+    let bom = src.starts_with("\u{feff}");
+    if bom {
+        src.drain(..3);
+    }
+    if filemap.src.as_ref().map_or(true, |fm_src| **fm_src != src) {
+        return Err(format!("it changed since it was compiled"))
+    }
+
+    let mut accepted: Vec<Edit> = Vec::new();
+    let mut applied = 0;
+    for edits in suggestions {
+        // The same suggestion may well have been made more than once.
+        if edits.iter().all(|edit| accepted.contains(edit)) {
+            continue
+        }
+        let conflicting = edits.windows(2).any(|w| w[0].conflicts_with(&w[1])) ||
+                          edits.iter().any(|edit| {
+                              accepted.iter().any(|other| edit.conflicts_with(other))
+                          });
+        if conflicting {
+            continue
+        }
+        accepted.extend(edits);
+        applied += 1;
+    }
+
+    if applied == 0 {
+        return Ok(0)
+    }
+
+    accepted.sort();
+    let mut out = String::with_capacity(src.len());
+    if bom {
+        out.push_str("\u{feff}");
+    }
+    let mut pos = 0;
+    for edit in &accepted {
+        out.push_str(&src[pos..edit.lo]);
+        out.push_str(&edit.replacement);
+        pos = edit.hi;
+    }
+    out.push_str(&src[pos..]);
+
+    File::create(&filemap.name)
+        .and_then(|mut f| f.write_all(out.as_bytes()))
+        .map_err(|e| e.to_string())?;
+    Ok(applied)
+}
@@ -9,6 +9,7 @@
 // except according to those terms.
 
 use CodeSuggestion;
+use Applicability;
 use Substitution;
 use Level;
 use RenderSpan;
@@ -210,6 +211,18 @@ impl Diagnostic {
     ///
     /// See `diagnostic::CodeSuggestion` for more information.
     pub fn span_suggestion_short(&mut self, sp: Span, msg: &str, suggestion: String) -> &mut Self {
+        self.span_suggestion_short_with_applicability(sp, msg, suggestion,
+                                                      Applicability::Unspecified)
+    }
+
+    /// Like `span_suggestion_short`, but also records how confident we are that
+    /// the suggestion is correct.
+    pub fn span_suggestion_short_with_applicability(&mut self,
+                                                    sp: Span,
+                                                    msg: &str,
+                                                    suggestion: String,
+                                                    applicability: Applicability)
+                                                    -> &mut Self {
         self.suggestions.push(CodeSuggestion {
             substitution_parts: vec![Substitution {
                 span: sp,
@@ -217,6 +230,7 @@ impl Diagnostic {
             }],
             msg: msg.to_owned(),
             show_code_when_inline: false,
+            applicability,
         });
         self
     }
@@ -237,6 +251,18 @@ impl Diagnostic {
     ///
     /// See `diagnostic::CodeSuggestion` for more information.
     pub fn span_suggestion(&mut self, sp: Span, msg: &str, suggestion: String) -> &mut Self {
+        self.span_suggestion_with_applicability(sp, msg, suggestion, Applicability::Unspecified)
+    }
+
+    /// Like `span_suggestion`, but also records how confident we are that the
+    /// suggestion is correct. Only `Applicability::MachineApplicable`
+    /// suggestions are applied by `--apply-suggestions`.
+    pub fn span_suggestion_with_applicability(&mut self,
+                                              sp: Span,
+                                              msg: &str,
+                                              suggestion: String,
+                                              applicability: Applicability)
+                                              -> &mut Self {
         self.suggestions.push(CodeSuggestion {
             substitution_parts: vec![Substitution {
                 span: sp,
@@ -244,11 +270,23 @@ impl Diagnostic {
             }],
             msg: msg.to_owned(),
             show_code_when_inline: true,
+            applicability,
         });
         self
     }
 
     pub fn span_suggestions(&mut self, sp: Span, msg: &str, suggestions: Vec<String>) -> &mut Self {
+        self.span_suggestions_with_applicability(sp, msg, suggestions, Applicability::Unspecified)
+    }
+
+    /// Like `span_suggestions`, but also records how confident we are that the
+    /// suggestions are correct.
+    pub fn span_suggestions_with_applicability(&mut self,
+                                               sp: Span,
+                                               msg: &str,
+                                               suggestions: Vec<String>,
+                                               applicability: Applicability)
+                                               -> &mut Self {
         self.suggestions.push(CodeSuggestion {
             substitution_parts: vec![Substitution {
                 span: sp,
@@ -256,6 +294,7 @@ impl Diagnostic {
             }],
             msg: msg.to_owned(),
             show_code_when_inline: true,
+            applicability,
         });
         self
     }
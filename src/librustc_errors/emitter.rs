@@ -107,6 +107,9 @@ impl ColorConfig {
 pub struct EmitterWriter {
     dst: Destination,
     cm: Option<Rc<CodeMapper>>,
+    /// Print each diagnostic as a single `file:line:col: level[code]: message`
+    /// line, leaving out the source snippets and all sub-diagnostics.
+    short_message: bool,
 }
 
 struct FileWithAnnotatedLines {
@@ -116,25 +119,34 @@ struct FileWithAnnotatedLines {
 }
 
 impl EmitterWriter {
-    pub fn stderr(color_config: ColorConfig, code_map: Option<Rc<CodeMapper>>) -> EmitterWriter {
+    pub fn stderr(color_config: ColorConfig,
+                  code_map: Option<Rc<CodeMapper>>,
+                  short_message: bool)
+                  -> EmitterWriter {
         if color_config.use_color() {
             let dst = Destination::from_stderr();
             EmitterWriter {
                 dst,
                 cm: code_map,
+                short_message,
             }
         } else {
             EmitterWriter {
                 dst: Raw(Box::new(io::stderr())),
                 cm: code_map,
+                short_message,
             }
         }
     }
 
-    pub fn new(dst: Box<Write + Send>, code_map: Option<Rc<CodeMapper>>) -> EmitterWriter {
+    pub fn new(dst: Box<Write + Send>,
+               code_map: Option<Rc<CodeMapper>>,
+               short_message: bool)
+               -> EmitterWriter {
         EmitterWriter {
             dst: Raw(dst),
             cm: code_map,
+            short_message,
         }
     }
 
@@ -924,6 +936,16 @@ impl EmitterWriter {
             emit_to_destination(&buffer.render(), level, &mut self.dst)?;
             return Ok(());
         };
+
+        if self.short_message {
+            let loc = &primary_lo;
+            buffer.prepend(0,
+                           &format!("{}:{}:{}: ", loc.file.name, loc.line, loc.col.0 + 1),
+                           Style::LineAndColumn);
+            emit_to_destination(&buffer.render(), level, &mut self.dst)?;
+            return Ok(());
+        }
+
         if let Ok(pos) =
             annotated_files.binary_search_by(|x| x.file.name.cmp(&primary_lo.file.name)) {
             annotated_files.swap(0, pos);
@@ -1155,6 +1177,7 @@ impl EmitterWriter {
         let max_line_num_len = max_line_num.to_string().len();
 
         match self.emit_message_default(span, message, code, level, max_line_num_len, false) {
+            Ok(()) if self.short_message => {}
             Ok(()) => {
                 if !children.is_empty() {
                     let mut buffer = StyledBuffer::new();
@@ -1201,15 +1224,14 @@ impl EmitterWriter {
             }
             Err(e) => panic!("failed to emit error: {}", e),
         }
-        match write!(&mut self.dst, "\n") {
-            Err(e) => panic!("failed to emit error: {}", e),
-            _ => {
-                match self.dst.flush() {
-                    Err(e) => panic!("failed to emit error: {}", e),
-                    _ => (),
-                }
+        if !self.short_message {
+            if let Err(e) = write!(&mut self.dst, "\n") {
+                panic!("failed to emit error: {}", e)
             }
         }
+        if let Err(e) = self.dst.flush() {
+            panic!("failed to emit error: {}", e)
+        }
     }
 }
 
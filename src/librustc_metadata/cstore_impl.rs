@@ -134,6 +134,13 @@ provide! { <'tcx> tcx, def_id, other, cdata,
 
         mir
     }
+    mir_for_ctfe => {
+        let mir = cdata.maybe_get_mir_for_ctfe(tcx, def_id.index).unwrap_or_else(|| {
+            bug!("get_mir_for_ctfe: missing MIR for `{:?}`", def_id)
+        });
+
+        tcx.alloc_mir(mir)
+    }
     generator_sig => { cdata.generator_sig(def_id.index, tcx) }
     mir_const_qualif => {
         (cdata.mir_const_qualif(def_id.index), Rc::new(IdxSetBuf::new_empty(0)))
@@ -836,6 +836,16 @@ impl<'a, 'tcx> CrateMetadata {
         }
     }
 
+    pub fn maybe_get_mir_for_ctfe(&self,
+                                  tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                  id: DefIndex)
+                                  -> Option<Mir<'tcx>> {
+        match self.is_proc_macro(id) {
+            true => None,
+            false => self.entry(id).mir_for_ctfe.map(|mir| mir.decode((self, tcx))),
+        }
+    }
+
     pub fn mir_const_qualif(&self, id: DefIndex) -> u8 {
         match self.entry(id).kind {
             EntryKind::Const(qualif) |
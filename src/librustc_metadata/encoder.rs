@@ -539,6 +539,7 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
 
             ast: None,
             mir: self.encode_optimized_mir(def_id),
+            mir_for_ctfe: self.encode_mir_for_ctfe(def_id),
         }
     }
 
@@ -578,7 +579,8 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
             predicates: None,
 
             ast: None,
-            mir: None
+            mir: None,
+            mir_for_ctfe: None,
         }
     }
 
@@ -620,6 +622,7 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
 
             ast: None,
             mir: None,
+            mir_for_ctfe: None,
         }
     }
 
@@ -671,6 +674,7 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
 
             ast: None,
             mir: self.encode_optimized_mir(def_id),
+            mir_for_ctfe: self.encode_mir_for_ctfe(def_id),
         }
     }
 
@@ -771,6 +775,11 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
                 None
             },
             mir: self.encode_optimized_mir(def_id),
+            mir_for_ctfe: if trait_item.kind == ty::AssociatedKind::Const {
+                self.encode_mir_for_ctfe(def_id)
+            } else {
+                None
+            },
         }
     }
 
@@ -813,8 +822,8 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
             ty::AssociatedKind::Type => EntryKind::AssociatedType(container)
         };
 
-        let (ast, mir) = if let hir::ImplItemKind::Const(_, body) = ast_item.node {
-            (Some(body), true)
+        let (ast, mir, mir_for_ctfe) = if let hir::ImplItemKind::Const(_, body) = ast_item.node {
+            (Some(body), true, true)
         } else if let hir::ImplItemKind::Method(ref sig, body) = ast_item.node {
             let generics = self.tcx.generics_of(def_id);
             let types = generics.parent_types as usize + generics.types.len();
@@ -822,9 +831,9 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
             let is_const_fn = sig.constness == hir::Constness::Const;
             let ast = if is_const_fn { Some(body) } else { None };
             let always_encode_mir = self.tcx.sess.opts.debugging_opts.always_encode_mir;
-            (ast, needs_inline || is_const_fn || always_encode_mir)
+            (ast, needs_inline || is_const_fn || always_encode_mir, is_const_fn)
         } else {
-            (None, false)
+            (None, false, false)
         };
 
         Entry {
@@ -848,6 +857,7 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
 
             ast: ast.map(|body| self.encode_body(body)),
             mir: if mir { self.encode_optimized_mir(def_id) } else { None },
+            mir_for_ctfe: if mir_for_ctfe { self.encode_mir_for_ctfe(def_id) } else { None },
         }
     }
 
@@ -878,6 +888,16 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
         }
     }
 
+    fn encode_mir_for_ctfe(&mut self, def_id: DefId) -> Option<Lazy<mir::Mir<'tcx>>> {
+        debug!("EntryBuilder::encode_mir_for_ctfe({:?})", def_id);
+        if self.tcx.mir_keys(LOCAL_CRATE).contains(&def_id) {
+            let mir = self.tcx.mir_for_ctfe(def_id);
+            Some(self.lazy(&mir))
+        } else {
+            None
+        }
+    }
+
     // Encodes the inherent implementations of a structure, enumeration, or trait.
     fn encode_inherent_implementations(&mut self, def_id: DefId) -> LazySeq<DefIndex> {
         debug!("IsolatedEncoder::encode_inherent_implementations({:?})", def_id);
@@ -1128,6 +1148,14 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
                 }
                 _ => None,
             },
+            mir_for_ctfe: match item.node {
+                hir::ItemStatic(..) if self.tcx.sess.opts.debugging_opts.always_encode_mir => {
+                    self.encode_mir_for_ctfe(def_id)
+                }
+                hir::ItemConst(..) |
+                hir::ItemFn(_, _, hir::Constness::Const, ..) => self.encode_mir_for_ctfe(def_id),
+                _ => None,
+            },
         }
     }
 
@@ -1154,6 +1182,7 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
             predicates: None,
             ast: None,
             mir: None,
+            mir_for_ctfe: None,
         }
     }
 
@@ -1183,6 +1212,7 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
 
             ast: None,
             mir: None,
+            mir_for_ctfe: None,
         }
     }
 
@@ -1206,6 +1236,7 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
 
             ast: None,
             mir: None,
+            mir_for_ctfe: None,
         }
     }
 
@@ -1245,6 +1276,7 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
 
             ast: None,
             mir: self.encode_optimized_mir(def_id),
+            mir_for_ctfe: None,
         }
     }
 
@@ -1271,6 +1303,7 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
 
             ast: Some(self.encode_body(body)),
             mir: self.encode_optimized_mir(def_id),
+            mir_for_ctfe: self.encode_mir_for_ctfe(def_id),
         }
     }
 
@@ -1441,6 +1474,7 @@ impl<'a, 'b: 'a, 'tcx: 'b> IsolatedEncoder<'a, 'b, 'tcx> {
 
             ast: None,
             mir: None,
+            mir_for_ctfe: None,
         }
     }
 }
@@ -264,6 +264,7 @@ pub struct Entry<'tcx> {
 
     pub ast: Option<Lazy<astencode::Ast<'tcx>>>,
     pub mir: Option<Lazy<mir::Mir<'tcx>>>,
+    pub mir_for_ctfe: Option<Lazy<mir::Mir<'tcx>>>,
 }
 
 impl_stable_hash_for!(struct Entry<'tcx> {
@@ -280,7 +281,8 @@ impl_stable_hash_for!(struct Entry<'tcx> {
     generics,
     predicates,
     ast,
-    mir
+    mir,
+    mir_for_ctfe
 });
 
 #[derive(Copy, Clone, RustcEncodable, RustcDecodable)]
@@ -28,8 +28,10 @@ use super::drop_flag_effects_for_location;
 use super::on_lookup_result_bits;
 
 mod storage_liveness;
+mod reaching_defs;
 
 pub use self::storage_liveness::*;
+pub use self::reaching_defs::*;
 
 #[allow(dead_code)]
 pub(super) mod borrows;
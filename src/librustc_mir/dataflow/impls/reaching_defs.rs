@@ -0,0 +1,246 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reaching definitions: which assignments to a local may still be the
+//! value of that local at a given point of the control flow.
+
+use rustc::mir::{self, Local, Location, Lvalue, Mir, ProjectionElem};
+use rustc::mir::visit::Visitor;
+use rustc::util::nodemap::FxHashMap;
+
+use rustc_data_structures::bitslice::BitwiseOperator;
+use rustc_data_structures::indexed_set::IdxSet;
+use rustc_data_structures::indexed_vec::IndexVec;
+
+use dataflow::{BitDenotation, BlockSets, DataflowOperator};
+pub use dataflow::indexes::DefinitionIndex;
+
+/// `ReachingDefinitions` maps each dataflow bit to a `Definition`: a
+/// write to (part of) a local, or the value of an argument on entry to
+/// the function. Writes through a `Deref` do not define the local they
+/// start from, and are not tracked.
+///
+/// Every definition of a local kills all other definitions of the same
+/// local, as do `StorageLive` and `StorageDead`. A local is therefore
+/// known to hold the value of a single definition `d` at some point if
+/// `d` is the only definition of it in the flow state there, provided
+/// the local is never borrowed.
+pub struct ReachingDefinitions<'a, 'tcx: 'a> {
+    mir: &'a Mir<'tcx>,
+    definitions: IndexVec<DefinitionIndex, Definition>,
+    location_map: FxHashMap<Location, Vec<DefinitionIndex>>,
+    local_map: IndexVec<Local, Vec<DefinitionIndex>>,
+}
+
+#[derive(Debug)]
+pub struct Definition {
+    pub local: Local,
+    /// The statement or terminator performing the write, or `None` for
+    /// the value of an argument on entry to the function.
+    pub location: Option<Location>,
+}
+
+impl<'a, 'tcx> ReachingDefinitions<'a, 'tcx> {
+    pub fn new(mir: &'a Mir<'tcx>) -> Self {
+        let mut visitor = GatherDefinitions {
+            definitions: IndexVec::new(),
+            location_map: FxHashMap(),
+            local_map: IndexVec::from_elem(vec![], &mir.local_decls),
+        };
+        for arg in mir.args_iter() {
+            visitor.add(arg, None);
+        }
+        visitor.visit_mir(mir);
+        return ReachingDefinitions {
+            mir,
+            definitions: visitor.definitions,
+            location_map: visitor.location_map,
+            local_map: visitor.local_map,
+        };
+
+        struct GatherDefinitions {
+            definitions: IndexVec<DefinitionIndex, Definition>,
+            location_map: FxHashMap<Location, Vec<DefinitionIndex>>,
+            local_map: IndexVec<Local, Vec<DefinitionIndex>>,
+        }
+        impl GatherDefinitions {
+            fn add(&mut self, local: Local, location: Option<Location>) {
+                let idx = self.definitions.push(Definition { local, location });
+                self.local_map[local].push(idx);
+                if let Some(location) = location {
+                    self.location_map.entry(location).or_insert(vec![]).push(idx);
+                }
+            }
+
+            fn add_lvalue(&mut self, lvalue: &Lvalue, location: Location) {
+                if let Some(local) = defined_local(lvalue) {
+                    self.add(local, Some(location));
+                }
+            }
+        }
+        impl<'tcx> Visitor<'tcx> for GatherDefinitions {
+            fn visit_statement(&mut self,
+                               _block: mir::BasicBlock,
+                               statement: &mir::Statement<'tcx>,
+                               location: Location) {
+                match statement.kind {
+                    mir::StatementKind::Assign(ref lvalue, _) |
+                    mir::StatementKind::SetDiscriminant { ref lvalue, .. } => {
+                        self.add_lvalue(lvalue, location);
+                    }
+                    mir::StatementKind::InlineAsm { ref outputs, .. } => {
+                        for output in outputs {
+                            self.add_lvalue(output, location);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            fn visit_terminator(&mut self,
+                                _block: mir::BasicBlock,
+                                terminator: &mir::Terminator<'tcx>,
+                                location: Location) {
+                match terminator.kind {
+                    mir::TerminatorKind::Call {
+                        destination: Some((ref lvalue, _)), ..
+                    } |
+                    mir::TerminatorKind::DropAndReplace { location: ref lvalue, .. } => {
+                        self.add_lvalue(lvalue, location);
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    pub fn definitions(&self) -> &IndexVec<DefinitionIndex, Definition> {
+        &self.definitions
+    }
+
+    /// All definitions of `local`, in the order they appear in the MIR.
+    pub fn definitions_of(&self, local: Local) -> &[DefinitionIndex] {
+        &self.local_map[local]
+    }
+
+    /// Applies the effect of the statement at `location` to `state`, so
+    /// that a block can be walked forward from its entry set.
+    pub fn apply_statement_effect(&self,
+                                  state: &mut IdxSet<DefinitionIndex>,
+                                  location: Location) {
+        self.each_effect(location, |idx, live| {
+            if live { state.add(&idx); } else { state.remove(&idx); }
+        });
+    }
+
+    /// Calls `f(idx, true)` for each definition generated by the statement
+    /// or terminator at `location`, and `f(idx, false)` for each one killed.
+    fn each_effect<F>(&self, location: Location, mut f: F)
+        where F: FnMut(DefinitionIndex, bool)
+    {
+        let block = &self.mir[location.block];
+        if location.statement_index < block.statements.len() {
+            match block.statements[location.statement_index].kind {
+                mir::StatementKind::StorageLive(local) |
+                mir::StatementKind::StorageDead(local) => {
+                    for &idx in &self.local_map[local] {
+                        f(idx, false);
+                    }
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(defs) = self.location_map.get(&location) {
+            for &def in defs {
+                for &idx in &self.local_map[self.definitions[def].local] {
+                    f(idx, false);
+                }
+                f(def, true);
+            }
+        }
+    }
+}
+
+/// The local whose value is (partially) overwritten by a write to `lvalue`.
+fn defined_local(lvalue: &Lvalue) -> Option<Local> {
+    match *lvalue {
+        Lvalue::Local(local) => Some(local),
+        Lvalue::Static(_) => None,
+        Lvalue::Projection(ref proj) => match proj.elem {
+            ProjectionElem::Deref => None,
+            _ => defined_local(&proj.base),
+        },
+    }
+}
+
+impl<'a, 'tcx> BitDenotation for ReachingDefinitions<'a, 'tcx> {
+    type Idx = DefinitionIndex;
+    fn name() -> &'static str { "reaching_definitions" }
+    fn bits_per_block(&self) -> usize {
+        self.definitions.len()
+    }
+
+    fn start_block_effect(&self, sets: &mut BlockSets<DefinitionIndex>) {
+        for arg in self.mir.args_iter() {
+            for &idx in &self.local_map[arg] {
+                if self.definitions[idx].location.is_none() {
+                    sets.on_entry.add(&idx);
+                }
+            }
+        }
+    }
+
+    fn statement_effect(&self,
+                        sets: &mut BlockSets<DefinitionIndex>,
+                        location: Location) {
+        self.each_effect(location, |idx, live| {
+            if live { sets.gen(&idx); } else { sets.kill(&idx); }
+        });
+    }
+
+    fn terminator_effect(&self,
+                         sets: &mut BlockSets<DefinitionIndex>,
+                         location: Location) {
+        // The destination of a call is only written on the return edge,
+        // in `propagate_call_return`.
+        if let mir::TerminatorKind::Call { .. } = self.mir[location.block].terminator().kind {
+            return;
+        }
+        self.statement_effect(sets, location);
+    }
+
+    fn propagate_call_return(&self,
+                             in_out: &mut IdxSet<DefinitionIndex>,
+                             call_bb: mir::BasicBlock,
+                             _dest_bb: mir::BasicBlock,
+                             _dest_lval: &mir::Lvalue) {
+        let location = Location {
+            block: call_bb,
+            statement_index: self.mir[call_bb].statements.len(),
+        };
+        self.apply_statement_effect(in_out, location);
+    }
+}
+
+impl<'a, 'tcx> BitwiseOperator for ReachingDefinitions<'a, 'tcx> {
+    #[inline]
+    fn join(&self, pred1: usize, pred2: usize) -> usize {
+        pred1 | pred2 // a definition reaches a join if it reaches any predecessor
+    }
+}
+
+impl<'a, 'tcx> DataflowOperator for ReachingDefinitions<'a, 'tcx> {
+    #[inline]
+    fn bottom_value() -> bool {
+        false // bottom = no definitions reach
+    }
+}
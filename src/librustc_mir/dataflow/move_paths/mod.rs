@@ -61,6 +61,9 @@ pub(crate) mod indexes {
 
     /// Index into Borrows.locations
     new_index!(BorrowIndex, "bw");
+
+    /// Index into ReachingDefinitions.definitions
+    new_index!(DefinitionIndex, "df");
 }
 
 pub use self::indexes::MovePathIndex;
@@ -0,0 +1,691 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Propagates constants through the locals of a function.
+//!
+//! The value of a local at a use is known when a single definition of it
+//! reaches the use (see `dataflow::ReachingDefinitions`) and that
+//! definition assigns a value that can itself be folded. Uses of such
+//! locals are replaced by constants, as are arithmetic rvalues, and
+//! `SwitchInt` and `Assert` terminators whose condition is known become
+//! `Goto`s, after which the blocks that are no longer reachable are
+//! removed.
+//!
+//! Assertions known to fail, such as arithmetic overflow or an index out
+//! of bounds, will panic every time they are reached and are reported
+//! with the `const_err` lint. The lint is reported at every optimization
+//! level, but like the other MIR optimizations, the MIR is only rewritten
+//! when the MIR optimization level is > 1.
+//!
+//! The MIR of a `#[rustc_inherit_overflow_checks]` function is translated
+//! with the overflow checks setting of the crate it ends up in, so its
+//! overflow assertions are never folded.
+
+use rustc::hir::def_id::DefId;
+use rustc::lint::builtin::CONST_ERR;
+use rustc::middle::const_val::{ConstEvalErr, ConstVal, ErrKind};
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource};
+use rustc::mir::traversal;
+use rustc::mir::visit::{MutVisitor, Visitor};
+use rustc::traits::Reveal;
+use rustc::ty::{self, TyCtxt};
+use rustc::ty::subst::Substs;
+use rustc::util::nodemap::FxHashMap;
+use rustc_const_math::{ConstFloat, ConstInt, ConstMathErr, Op};
+use rustc_data_structures::indexed_set::{IdxSet, IdxSetBuf};
+use rustc_data_structures::indexed_vec::Idx;
+use syntax::ast;
+use syntax::attr;
+use syntax::attr::IntType;
+use syntax_pos::Span;
+
+use dataflow::{self, DataflowResults, DefinitionIndex, ReachingDefinitions};
+use transform::simplify;
+use util::borrowed_locals::borrowed_locals;
+
+use std::cmp::Ordering;
+
+pub struct ConstProp;
+
+impl MirPass for ConstProp {
+    fn run_pass<'a, 'tcx>(&self,
+                          tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          source: MirSource,
+                          mir: &mut Mir<'tcx>) {
+        let node_id = match source {
+            MirSource::Fn(node_id) => node_id,
+            // Constants and statics are evaluated, and their errors
+            // reported, by `const_eval`.
+            _ => return,
+        };
+        // First, find the known values. This is done in a pre-pass to keep the MIR read-only
+        // while the dataflow results, which borrow it, are in use.
+        let optimizations = {
+            let dead_unwinds = IdxSetBuf::new_empty(mir.basic_blocks().len());
+            let analysis = ReachingDefinitions::new(mir);
+            let results = dataflow::do_dataflow(tcx, mir, node_id, &[], &dead_unwinds, analysis,
+                                                |bd, p| &bd.definitions()[p]);
+            let mut propagator = ConstPropagator::new(tcx, node_id, mir, &results);
+            propagator.propagate();
+            propagator.optimizations
+        };
+
+        // Lints are reported above at any optimization level, but the MIR is only rewritten
+        // when the MIR optimization level is > 1, like copy propagation.
+        if tcx.sess.opts.debugging_opts.mir_opt_level <= 1 {
+            return;
+        }
+
+        let removes_blocks = !optimizations.gotos.is_empty();
+        MutVisitor::visit_mir(&mut ConstPropVisitor { optimizations }, mir);
+        if removes_blocks {
+            simplify::remove_dead_blocks(mir);
+        }
+    }
+}
+
+/// What is known about the value of a local.
+#[derive(Clone, Debug)]
+enum Value<'tcx> {
+    /// A `bool`, `char`, integer or float.
+    Scalar(ConstVal<'tcx>),
+    /// The result of a `CheckedBinaryOp`, and whether it overflowed.
+    Checked(ConstVal<'tcx>, bool),
+    /// An enum built from the variant with the given index.
+    Variant(usize),
+}
+
+struct ConstPropagator<'b, 'a, 'tcx: 'a + 'b> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'b Mir<'tcx>,
+    results: &'b DataflowResults<ReachingDefinitions<'b, 'tcx>>,
+    /// Locals that are never borrowed, so that only their definitions
+    /// can change their value.
+    trackable: IdxSetBuf<Local>,
+    /// The folded value of each definition evaluated so far. A definition
+    /// is recorded as `None` while it is being evaluated, so that one
+    /// depending on itself through a loop is not known.
+    values: FxHashMap<DefinitionIndex, Option<Value<'tcx>>>,
+    /// Whether overflow checks are enabled, mirroring trans: without them,
+    /// `CheckedBinaryOp` never reports an overflow, and negation is not
+    /// checked.
+    check_overflow: bool,
+    /// Whether the function is `#[rustc_inherit_overflow_checks]`, in which
+    /// case `check_overflow` depends on the crate the MIR is translated in.
+    inherits_overflow_checks: bool,
+    optimizations: OptimizationList<'tcx>,
+}
+
+#[derive(Default)]
+struct OptimizationList<'tcx> {
+    /// Uses of lvalues which can be replaced by a constant, per location.
+    operands: FxHashMap<Location, Vec<(Lvalue<'tcx>, Constant<'tcx>)>>,
+    /// Rvalues which fold to a constant.
+    rvalues: FxHashMap<Location, Constant<'tcx>>,
+    /// Blocks whose terminator always continues to the given block.
+    gotos: FxHashMap<BasicBlock, BasicBlock>,
+}
+
+impl<'b, 'a, 'tcx> ConstPropagator<'b, 'a, 'tcx> {
+    fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+           node_id: ast::NodeId,
+           mir: &'b Mir<'tcx>,
+           results: &'b DataflowResults<ReachingDefinitions<'b, 'tcx>>)
+           -> Self {
+        let mut trackable = IdxSetBuf::new_filled(mir.local_decls.len());
+        trackable.subtract(&borrowed_locals(mir));
+        let inherits_overflow_checks =
+            attr::contains_name(tcx.hir.attrs(node_id), "rustc_inherit_overflow_checks");
+        ConstPropagator {
+            tcx,
+            mir,
+            results,
+            trackable,
+            values: FxHashMap(),
+            check_overflow: tcx.sess.overflow_checks(),
+            inherits_overflow_checks,
+            optimizations: OptimizationList::default(),
+        }
+    }
+
+    /// Walks the reachable blocks in reverse postorder, skipping the
+    /// successors of terminators that are known not to be taken.
+    fn propagate(&mut self) {
+        let mir = self.mir;
+        let results = self.results;
+        let analysis = results.operator();
+        let mut reachable = IdxSetBuf::new_empty(mir.basic_blocks().len());
+        reachable.add(&START_BLOCK);
+
+        for (bb, data) in traversal::reverse_postorder(mir) {
+            if !reachable.contains(&bb) {
+                continue;
+            }
+
+            let mut state = results.sets().on_entry_set_for(bb.index()).to_owned();
+            for (index, statement) in data.statements.iter().enumerate() {
+                let location = Location { block: bb, statement_index: index };
+                self.propagate_statement(statement, location, &state);
+                analysis.apply_statement_effect(&mut state, location);
+            }
+
+            let location = Location { block: bb, statement_index: data.statements.len() };
+            for target in self.propagate_terminator(data.terminator(), location, &state) {
+                reachable.add(&target);
+            }
+        }
+    }
+
+    fn propagate_statement(&mut self,
+                           statement: &Statement<'tcx>,
+                           location: Location,
+                           state: &IdxSet<DefinitionIndex>) {
+        UseCollector { propagator: self, state }
+            .visit_statement(location.block, statement, location);
+
+        if let StatementKind::Assign(_, ref rvalue) = statement.kind {
+            match *rvalue {
+                Rvalue::BinaryOp(..) |
+                Rvalue::UnaryOp(..) |
+                Rvalue::Discriminant(..) |
+                Rvalue::Len(..) => {
+                    if let Some(Value::Scalar(val)) = self.eval_rvalue(rvalue, state) {
+                        let ty = rvalue.ty(self.mir, self.tcx);
+                        let constant = self.constant(val, ty, statement.source_info.span);
+                        self.optimizations.rvalues.insert(location, constant);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns the successors of `terminator` that may be taken.
+    fn propagate_terminator(&mut self,
+                            terminator: &Terminator<'tcx>,
+                            location: Location,
+                            state: &IdxSet<DefinitionIndex>)
+                            -> Vec<BasicBlock> {
+        UseCollector { propagator: self, state }
+            .visit_terminator(location.block, terminator, location);
+
+        match terminator.kind {
+            TerminatorKind::SwitchInt { ref discr, ref values, ref targets, .. } => {
+                let discr = match self.eval_operand(discr, state) {
+                    Some(Value::Scalar(val)) => val.to_const_int(),
+                    _ => None,
+                };
+                if let Some(discr) = discr {
+                    let (otherwise, targets) = targets.split_last().unwrap();
+                    let target = values.iter().zip(targets)
+                        .find(|&(value, _)| *value == discr)
+                        .map_or(*otherwise, |(_, &target)| target);
+                    self.optimizations.gotos.insert(location.block, target);
+                    return vec![target];
+                }
+            }
+            TerminatorKind::Assert { ref cond, expected, ref msg, target, .. } => {
+                if let Some(Value::Scalar(ConstVal::Bool(cond))) = self.eval_operand(cond, state) {
+                    if cond == expected {
+                        self.optimizations.gotos.insert(location.block, target);
+                        return vec![target];
+                    }
+                    match *msg {
+                        AssertMessage::Math(ConstMathErr::Overflow(_))
+                            if self.inherits_overflow_checks => {
+                            // Whether this panics depends on the crate the MIR is
+                            // translated in.
+                            return terminator.successors().into_owned();
+                        }
+                        AssertMessage::Math(ConstMathErr::Overflow(Op::Neg))
+                            if !self.check_overflow => {
+                            self.optimizations.gotos.insert(location.block, target);
+                            return vec![target];
+                        }
+                        _ => {}
+                    }
+                    self.report_panic(msg, terminator.source_info, state);
+                    return vec![];
+                }
+            }
+            _ => {}
+        }
+
+        terminator.successors().into_owned()
+    }
+
+    /// Reports an assertion that fails every time it is reached.
+    fn report_panic(&mut self,
+                    msg: &AssertMessage<'tcx>,
+                    source_info: SourceInfo,
+                    state: &IdxSet<DefinitionIndex>) {
+        let kind = match *msg {
+            AssertMessage::BoundsCheck { ref len, ref index } => {
+                let len = self.eval_u64(len, state);
+                let index = self.eval_u64(index, state);
+                match (len, index) {
+                    (Some(len), Some(index)) => ErrKind::IndexOutOfBounds { len, index },
+                    _ => return,
+                }
+            }
+            AssertMessage::Math(ref err) => ErrKind::Math(err.clone()),
+            AssertMessage::GeneratorResumedAfterReturn |
+            AssertMessage::GeneratorResumedAfterPanic => return,
+        };
+
+        let lint_root = match self.mir.visibility_scope_info {
+            ClearOnDecode::Set(ref info) => info[source_info.scope].lint_root,
+            ClearOnDecode::Clear => return,
+        };
+        let span = source_info.span;
+        let err = ConstEvalErr { span, kind };
+        let mut diag = self.tcx.struct_span_lint_node(CONST_ERR,
+                                                      lint_root,
+                                                      span,
+                                                      "this expression will panic at run-time");
+        err.note(self.tcx, span, "expression", &mut diag);
+        diag.emit();
+    }
+
+    /// Records a replacement for `operand` if its value is known.
+    fn propagate_operand(&mut self,
+                         operand: &Operand<'tcx>,
+                         location: Location,
+                         state: &IdxSet<DefinitionIndex>) {
+        if let Operand::Consume(ref lvalue) = *operand {
+            if let Some(Value::Scalar(val)) = self.eval_lvalue(lvalue, state) {
+                let ty = lvalue.ty(self.mir, self.tcx).to_ty(self.tcx);
+                let span = self.span_of(location);
+                let constant = self.constant(val, ty, span);
+                self.optimizations.operands.entry(location).or_insert(vec![])
+                    .push((lvalue.clone(), constant));
+            }
+        }
+    }
+
+    fn span_of(&self, location: Location) -> Span {
+        let block = &self.mir[location.block];
+        match block.statements.get(location.statement_index) {
+            Some(statement) => statement.source_info.span,
+            None => block.terminator().source_info.span,
+        }
+    }
+
+    fn constant(&self, val: ConstVal<'tcx>, ty: ty::Ty<'tcx>, span: Span) -> Constant<'tcx> {
+        Constant {
+            span,
+            ty,
+            literal: Literal::Value {
+                value: self.tcx.mk_const(ty::Const { val, ty })
+            },
+        }
+    }
+
+    /// The value of `local` in the flow state `state`.
+    fn local_value(&mut self,
+                   local: Local,
+                   state: &IdxSet<DefinitionIndex>)
+                   -> Option<Value<'tcx>> {
+        if !self.trackable.contains(&local) {
+            return None;
+        }
+        let results = self.results;
+        let mut reaching = results.operator().definitions_of(local).iter()
+            .filter(|&idx| state.contains(idx));
+        match (reaching.next(), reaching.next()) {
+            (Some(&idx), None) => self.definition_value(idx),
+            _ => None,
+        }
+    }
+
+    fn definition_value(&mut self, idx: DefinitionIndex) -> Option<Value<'tcx>> {
+        if let Some(value) = self.values.get(&idx) {
+            return value.clone();
+        }
+        self.values.insert(idx, None);
+
+        let (mir, results) = (self.mir, self.results);
+        let definition = &results.operator().definitions()[idx];
+        let value = match definition.location {
+            Some(location) => {
+                match mir[location.block].statements.get(location.statement_index) {
+                    Some(&Statement {
+                        kind: StatementKind::Assign(Lvalue::Local(local), ref rvalue), ..
+                    }) if local == definition.local => {
+                        let state = dataflow::state_for_location(location,
+                                                                 results.operator(),
+                                                                 results);
+                        self.eval_rvalue(rvalue, &state)
+                    }
+                    // Partial writes, and the results of calls.
+                    _ => None,
+                }
+            }
+            // Arguments.
+            None => None,
+        };
+
+        self.values.insert(idx, value.clone());
+        value
+    }
+
+    fn eval_lvalue(&mut self,
+                   lvalue: &Lvalue<'tcx>,
+                   state: &IdxSet<DefinitionIndex>)
+                   -> Option<Value<'tcx>> {
+        match *lvalue {
+            Lvalue::Local(local) => self.local_value(local, state),
+            Lvalue::Projection(box Projection {
+                base: Lvalue::Local(local),
+                elem: ProjectionElem::Field(field, _),
+            }) => {
+                match self.local_value(local, state) {
+                    Some(Value::Checked(val, overflowed)) => match field.index() {
+                        0 => Some(Value::Scalar(val)),
+                        1 => Some(Value::Scalar(ConstVal::Bool(overflowed))),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn eval_operand(&mut self,
+                    operand: &Operand<'tcx>,
+                    state: &IdxSet<DefinitionIndex>)
+                    -> Option<Value<'tcx>> {
+        match *operand {
+            Operand::Consume(ref lvalue) => self.eval_lvalue(lvalue, state),
+            Operand::Constant(box Constant { literal: Literal::Value { value }, .. }) => {
+                let val = match value.val {
+                    ConstVal::Unevaluated(def_id, substs) => self.eval_const(def_id, substs),
+                    val => Some(val),
+                };
+                val.and_then(scalar).map(Value::Scalar)
+            }
+            Operand::Constant(_) => None,
+        }
+    }
+
+    fn eval_const(&self, def_id: DefId, substs: &'tcx Substs<'tcx>) -> Option<ConstVal<'tcx>> {
+        if substs.needs_subst() {
+            return None;
+        }
+        // Errors are reported by trans, when it evaluates the constant.
+        let param_env = ty::ParamEnv::empty(Reveal::All);
+        self.tcx.const_eval(param_env.and((def_id, substs))).ok().map(|c| c.val)
+    }
+
+    fn eval_u64(&mut self,
+                operand: &Operand<'tcx>,
+                state: &IdxSet<DefinitionIndex>)
+                -> Option<u64> {
+        match self.eval_operand(operand, state) {
+            Some(Value::Scalar(val)) => val.to_const_int().and_then(|i| i.to_u64()),
+            _ => None,
+        }
+    }
+
+    fn eval_scalar(&mut self,
+                   operand: &Operand<'tcx>,
+                   state: &IdxSet<DefinitionIndex>)
+                   -> Option<ConstVal<'tcx>> {
+        match self.eval_operand(operand, state) {
+            Some(Value::Scalar(val)) => Some(val),
+            _ => None,
+        }
+    }
+
+    fn eval_rvalue(&mut self,
+                   rvalue: &Rvalue<'tcx>,
+                   state: &IdxSet<DefinitionIndex>)
+                   -> Option<Value<'tcx>> {
+        match *rvalue {
+            Rvalue::Use(ref operand) => self.eval_operand(operand, state),
+            Rvalue::BinaryOp(op, ref left, ref right) => {
+                let left = self.eval_scalar(left, state);
+                let right = self.eval_scalar(right, state);
+                match (left, right) {
+                    (Some(left), Some(right)) => match binary_op(op, left, right) {
+                        Some(Ok(val)) => Some(Value::Scalar(val)),
+                        _ => None,
+                    },
+                    _ => None,
+                }
+            }
+            Rvalue::CheckedBinaryOp(op, ref left, ref right) => {
+                let left = self.eval_scalar(left, state);
+                let right = self.eval_scalar(right, state);
+                let (left, right) = match (left, right) {
+                    (Some(left), Some(right)) => (left, right),
+                    _ => return None,
+                };
+                match binary_op(op, left, right) {
+                    Some(Ok(val)) => Some(Value::Checked(val, false)),
+                    Some(Err(ConstMathErr::Overflow(_))) if !self.inherits_overflow_checks => {
+                        self.wrapping_op(op, left, right)
+                            .map(|val| Value::Checked(val, self.check_overflow))
+                    }
+                    _ => None,
+                }
+            }
+            Rvalue::UnaryOp(op, ref operand) => {
+                let val = match (op, self.eval_scalar(operand, state)) {
+                    (UnOp::Not, Some(ConstVal::Bool(b))) => Ok(ConstVal::Bool(!b)),
+                    (UnOp::Not, Some(ConstVal::Integral(i))) => (!i).map(ConstVal::Integral),
+                    (UnOp::Neg, Some(ConstVal::Integral(i))) => (-i).map(ConstVal::Integral),
+                    (UnOp::Neg, Some(ConstVal::Float(f))) => Ok(ConstVal::Float(-f)),
+                    _ => return None,
+                };
+                val.ok().map(Value::Scalar)
+            }
+            Rvalue::Discriminant(Lvalue::Local(local)) => {
+                let variant = match self.local_value(local, state) {
+                    Some(Value::Variant(variant)) => variant,
+                    _ => return None,
+                };
+                match self.mir.local_decls[local].ty.sty {
+                    ty::TyAdt(adt, _) if adt.is_enum() => {
+                        let discr = adt.discriminant_for_variant(self.tcx, variant);
+                        Some(Value::Scalar(ConstVal::Integral(discr)))
+                    }
+                    _ => None,
+                }
+            }
+            Rvalue::Len(ref lvalue) => {
+                match lvalue.ty(self.mir, self.tcx).to_ty(self.tcx).sty {
+                    ty::TyArray(_, len) => scalar(len.val).map(Value::Scalar),
+                    _ => None,
+                }
+            }
+            Rvalue::Aggregate(box AggregateKind::Adt(adt, variant, ..), _) if adt.is_enum() => {
+                Some(Value::Variant(variant))
+            }
+            _ => None,
+        }
+    }
+
+    /// The result of an integer operation which overflowed, wrapped
+    /// around as it is at run-time.
+    fn wrapping_op(&self, op: BinOp, left: ConstVal, right: ConstVal) -> Option<ConstVal<'tcx>> {
+        let (left, right) = match (left, right) {
+            (ConstVal::Integral(left), ConstVal::Integral(right)) => (left, right),
+            _ => return None,
+        };
+        let (l, r) = (left.to_u128_unchecked(), right.to_u128_unchecked());
+        let bits = match op {
+            BinOp::Add => l.wrapping_add(r),
+            BinOp::Sub => l.wrapping_sub(r),
+            BinOp::Mul => l.wrapping_mul(r),
+            _ => return None,
+        };
+        let target = &self.tcx.sess.target;
+        let val = match left.int_type() {
+            IntType::SignedInt(ty) => {
+                ConstInt::new_signed_truncating(bits as i128, ty, target.isize_ty)
+            }
+            IntType::UnsignedInt(ty) => {
+                ConstInt::new_unsigned_truncating(bits, ty, target.usize_ty)
+            }
+        };
+        Some(ConstVal::Integral(val))
+    }
+}
+
+fn scalar(val: ConstVal) -> Option<ConstVal> {
+    match val {
+        ConstVal::Integral(_) |
+        ConstVal::Float(_) |
+        ConstVal::Bool(_) |
+        ConstVal::Char(_) => Some(val),
+        _ => None,
+    }
+}
+
+/// Folds `left op right`, returning `None` if the operation is not
+/// supported, or `Some(Err(..))` if it fails.
+fn binary_op<'tcx>(op: BinOp, left: ConstVal<'tcx>, right: ConstVal<'tcx>)
+                   -> Option<Result<ConstVal<'tcx>, ConstMathErr>> {
+    if let (ConstVal::Float(left), ConstVal::Float(right)) = (left, right) {
+        return float_binary_op(op, left, right);
+    }
+
+    let is_bool = match left { ConstVal::Bool(_) => true, _ => false };
+    let (left, right) = match (left.to_const_int(), right.to_const_int()) {
+        (Some(left), Some(right)) => (left, right),
+        _ => return None,
+    };
+    let result = match op {
+        BinOp::Add => left + right,
+        BinOp::Sub => left - right,
+        BinOp::Mul => left * right,
+        BinOp::Div => left / right,
+        BinOp::Rem => left % right,
+        BinOp::BitXor => left ^ right,
+        BinOp::BitAnd => left & right,
+        BinOp::BitOr => left | right,
+        BinOp::Shl => left << right,
+        BinOp::Shr => left >> right,
+        BinOp::Eq | BinOp::Lt | BinOp::Le | BinOp::Ne | BinOp::Ge | BinOp::Gt => {
+            return Some(left.try_cmp(right).map(|ord| ConstVal::Bool(compare(op, ord))));
+        }
+        BinOp::Offset => return None,
+    };
+    Some(result.map(|i| {
+        if is_bool {
+            ConstVal::Bool(i.to_u128_unchecked() != 0)
+        } else {
+            ConstVal::Integral(i)
+        }
+    }))
+}
+
+fn float_binary_op<'tcx>(op: BinOp, left: ConstFloat, right: ConstFloat)
+                         -> Option<Result<ConstVal<'tcx>, ConstMathErr>> {
+    // Compare on the host, as `ConstFloat::try_cmp` does not order NaNs as
+    // IEEE 754 does.
+    let (l, r) = (host_float(left), host_float(right));
+    let result = match op {
+        BinOp::Add => left + right,
+        BinOp::Sub => left - right,
+        BinOp::Mul => left * right,
+        BinOp::Div => left / right,
+        BinOp::Rem => left % right,
+        BinOp::Eq => return Some(Ok(ConstVal::Bool(l == r))),
+        BinOp::Ne => return Some(Ok(ConstVal::Bool(l != r))),
+        BinOp::Lt => return Some(Ok(ConstVal::Bool(l < r))),
+        BinOp::Le => return Some(Ok(ConstVal::Bool(l <= r))),
+        BinOp::Gt => return Some(Ok(ConstVal::Bool(l > r))),
+        BinOp::Ge => return Some(Ok(ConstVal::Bool(l >= r))),
+        _ => return None,
+    };
+    Some(result.map(ConstVal::Float))
+}
+
+fn host_float(f: ConstFloat) -> f64 {
+    match f.ty {
+        ast::FloatTy::F32 => f32::from_bits(f.bits as u32) as f64,
+        ast::FloatTy::F64 => f64::from_bits(f.bits as u64),
+    }
+}
+
+fn compare(op: BinOp, ord: Ordering) -> bool {
+    match op {
+        BinOp::Eq => ord == Ordering::Equal,
+        BinOp::Ne => ord != Ordering::Equal,
+        BinOp::Lt => ord == Ordering::Less,
+        BinOp::Le => ord != Ordering::Greater,
+        BinOp::Gt => ord == Ordering::Greater,
+        BinOp::Ge => ord != Ordering::Less,
+        _ => bug!("not a comparison: {:?}", op),
+    }
+}
+
+/// Records the known uses in the statement or terminator it visits.
+struct UseCollector<'c, 'b: 'c, 'a: 'c, 'tcx: 'a + 'b> {
+    propagator: &'c mut ConstPropagator<'b, 'a, 'tcx>,
+    state: &'c IdxSet<DefinitionIndex>,
+}
+
+impl<'c, 'b, 'a, 'tcx> Visitor<'tcx> for UseCollector<'c, 'b, 'a, 'tcx> {
+    fn visit_operand(&mut self, operand: &Operand<'tcx>, location: Location) {
+        self.propagator.propagate_operand(operand, location, self.state);
+    }
+}
+
+struct ConstPropVisitor<'tcx> {
+    optimizations: OptimizationList<'tcx>,
+}
+
+impl<'tcx> MutVisitor<'tcx> for ConstPropVisitor<'tcx> {
+    fn visit_rvalue(&mut self, rvalue: &mut Rvalue<'tcx>, location: Location) {
+        if let Some(constant) = self.optimizations.rvalues.remove(&location) {
+            debug!("Replacing {:?} with {:?}", rvalue, constant);
+            *rvalue = Rvalue::Use(Operand::Constant(box constant));
+            return;
+        }
+
+        self.super_rvalue(rvalue, location)
+    }
+
+    fn visit_operand(&mut self, operand: &mut Operand<'tcx>, location: Location) {
+        let constant = match *operand {
+            Operand::Consume(ref lvalue) => {
+                self.optimizations.operands.get(&location).and_then(|uses| {
+                    uses.iter().find(|&&(ref used, _)| used == lvalue)
+                        .map(|&(_, ref constant)| constant.clone())
+                })
+            }
+            Operand::Constant(_) => None,
+        };
+        if let Some(constant) = constant {
+            debug!("Replacing {:?} with {:?}", operand, constant);
+            *operand = Operand::Constant(box constant);
+        }
+
+        self.super_operand(operand, location)
+    }
+
+    fn visit_terminator_kind(&mut self,
+                             block: BasicBlock,
+                             kind: &mut TerminatorKind<'tcx>,
+                             location: Location) {
+        if let Some(target) = self.optimizations.gotos.remove(&block) {
+            debug!("Replacing terminator of {:?} with a goto to {:?}", block, target);
+            *kind = TerminatorKind::Goto { target };
+            return;
+        }
+
+        self.super_terminator_kind(block, kind, location)
+    }
+}
@@ -0,0 +1,410 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Destination propagation.
+//!
+//! Moving a large value from one local into another is a memcpy that LLVM often fails to remove,
+//! and chains like `let x = make(); let y = x; return y;` produce one memcpy per step. This pass
+//! looks for statements of the form:
+//!
+//!     DEST = SRC
+//!
+//! where `DEST` and `SRC` are locals of the same type that are never borrowed, and gives both
+//! locals the same storage when their live ranges don't overlap. The assignment then becomes
+//! `DEST = DEST` and is removed. `DEST` may be the return place, in which case the value is built
+//! in place in the return slot.
+//!
+//! Two locals *interfere* if one of them is written while the other is live, using the liveness
+//! computed by `util::liveness`. As usual for coalescing, `DEST = SRC` itself does not make them
+//! interfere, since both hold the same value afterwards. Because `util::liveness` does not see
+//! the return place being used by `return`, a local written anywhere after the return place has
+//! been written is considered to interfere with it.
+//!
+//! Merged locals lose their `StorageLive` and `StorageDead` statements, since the storage of the
+//! merged local has to cover both of the original live ranges.
+//!
+//! Unlike copy propagation, this doesn't use `util::def_use::DefUseAnalysis` to only consider
+//! locals with a single definition and a single use. Interference is what decides whether two
+//! locals can be merged, and it also allows locals that are assigned in several places, such as
+//! a variable updated in a loop or assigned in each arm of a `match`. Borrowed locals are left
+//! out, using `util::borrowed_locals`, because liveness can't follow uses through a reference.
+
+use rustc::mir::{Local, LocalKind, Location, Lvalue, Mir, Mutability, Operand, ProjectionElem};
+use rustc::mir::{Rvalue, Statement, StatementKind, TerminatorKind, RETURN_POINTER};
+use rustc::mir::transform::{MirPass, MirSource};
+use rustc::mir::visit::{LvalueContext, MutVisitor, Visitor};
+use rustc::ty::TyCtxt;
+use rustc_data_structures::bitvec::{BitMatrix, BitVector};
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use util::borrowed_locals::borrowed_locals;
+use util::liveness::liveness_of_locals;
+
+pub struct DestinationPropagation;
+
+impl MirPass for DestinationPropagation {
+    fn run_pass<'a, 'tcx>(&self,
+                          tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          source: MirSource,
+                          mir: &mut Mir<'tcx>) {
+        match source {
+            MirSource::Const(_) | MirSource::Static(..) | MirSource::Promoted(..) => {
+                // Like copy propagation, leave constants alone, as trans might not be able to
+                // evaluate the optimized MIR.
+                return
+            }
+            MirSource::Fn(_) | MirSource::GeneratorDrop(_) => (),
+        }
+
+        // Like copy propagation, this only runs when the MIR optimization level is > 1, as it
+        // loses the debug info of merged variables.
+        if tcx.sess.opts.debugging_opts.mir_opt_level <= 1 {
+            return;
+        }
+
+        // The generator transform relies on the storage statements we would remove.
+        if mir.yield_ty.is_some() {
+            return;
+        }
+
+        let candidates = find_candidates(mir);
+        if candidates.is_empty() {
+            return;
+        }
+
+        let mut conflicts = Conflicts::new(mir, &candidates);
+        conflicts.compute(mir);
+
+        let mut merged_into: IndexVec<Local, Local> = mir.local_decls.indices().collect();
+        let mut changed = false;
+        for candidate in &candidates {
+            let dest = representative(&merged_into, candidate.dest);
+            let src = representative(&merged_into, candidate.src);
+            if dest == src || conflicts.interfere(dest, src) {
+                continue;
+            }
+
+            // Keep the return place if it is part of the merged set, and the destination
+            // otherwise.
+            let (keep, remove) = if src == RETURN_POINTER { (src, dest) } else { (dest, src) };
+            debug!("DestinationPropagation: merging {:?} into {:?} for {:?}",
+                   remove, keep, candidate.location);
+            merged_into[remove] = keep;
+            conflicts.merge(remove, keep);
+            changed = true;
+        }
+
+        if !changed {
+            return;
+        }
+
+        let mut merged = BitVector::new(mir.local_decls.len());
+        for local in mir.local_decls.indices() {
+            let rep = representative(&merged_into, local);
+            if rep != local {
+                merged.insert(local.index());
+                merged.insert(rep.index());
+            }
+        }
+
+        LocalRenamer { merged_into: &merged_into }.visit_mir(mir);
+
+        for data in mir.basic_blocks_mut().iter_mut() {
+            for statement in &mut data.statements {
+                let remove = match statement.kind {
+                    StatementKind::StorageLive(local) |
+                    StatementKind::StorageDead(local) => merged.contains(local.index()),
+                    StatementKind::Assign(Lvalue::Local(dest),
+                                          Rvalue::Use(Operand::Consume(Lvalue::Local(src)))) => {
+                        dest == src
+                    }
+                    _ => false,
+                };
+                if remove {
+                    statement.make_nop();
+                }
+            }
+        }
+    }
+}
+
+fn representative(merged_into: &IndexVec<Local, Local>, mut local: Local) -> Local {
+    while merged_into[local] != local {
+        local = merged_into[local];
+    }
+    local
+}
+
+/// A `dest = src` statement whose locals may be able to share storage.
+struct Candidate {
+    dest: Local,
+    src: Local,
+    location: Location,
+}
+
+fn find_candidates<'tcx>(mir: &Mir<'tcx>) -> Vec<Candidate> {
+    let borrowed = borrowed_locals(mir);
+
+    let mut candidates = vec![];
+    for (block, data) in mir.basic_blocks().iter_enumerated() {
+        for (statement_index, statement) in data.statements.iter().enumerate() {
+            let (dest, src) = match statement.kind {
+                StatementKind::Assign(Lvalue::Local(dest),
+                                      Rvalue::Use(Operand::Consume(Lvalue::Local(src)))) => {
+                    (dest, src)
+                }
+                _ => continue,
+            };
+
+            if dest == src || borrowed.contains(&dest) || borrowed.contains(&src) {
+                continue;
+            }
+            if mir.local_decls[dest].ty != mir.local_decls[src].ty {
+                continue;
+            }
+            match mir.local_kind(dest) {
+                LocalKind::ReturnPointer | LocalKind::Var | LocalKind::Temp => {}
+                LocalKind::Arg => continue,
+            }
+            match mir.local_kind(src) {
+                LocalKind::Var | LocalKind::Temp => {}
+                LocalKind::ReturnPointer | LocalKind::Arg => continue,
+            }
+
+            candidates.push(Candidate {
+                dest,
+                src,
+                location: Location { block, statement_index },
+            });
+        }
+    }
+    candidates
+}
+
+/// The interference between the locals that appear in candidates.
+struct Conflicts {
+    /// Index of each candidate local in `matrix`.
+    index: IndexVec<Local, Option<usize>>,
+    /// Symmetric interference matrix.
+    matrix: BitMatrix,
+}
+
+impl Conflicts {
+    fn new<'tcx>(mir: &Mir<'tcx>, candidates: &[Candidate]) -> Conflicts {
+        let mut index = IndexVec::from_elem_n(None, mir.local_decls.len());
+        let mut count = 0;
+        for candidate in candidates {
+            for &local in &[candidate.dest, candidate.src] {
+                if index[local].is_none() {
+                    index[local] = Some(count);
+                    count += 1;
+                }
+            }
+        }
+        Conflicts {
+            index,
+            matrix: BitMatrix::new(count, count),
+        }
+    }
+
+    fn add(&mut self, a: Local, b: Local) {
+        if a == b {
+            return;
+        }
+        if let (Some(a), Some(b)) = (self.index[a], self.index[b]) {
+            self.matrix.add(a, b);
+            self.matrix.add(b, a);
+        }
+    }
+
+    fn interfere(&self, a: Local, b: Local) -> bool {
+        match (self.index[a], self.index[b]) {
+            (Some(a), Some(b)) => self.matrix.contains(a, b),
+            _ => false,
+        }
+    }
+
+    /// Makes `keep` interfere with everything `remove` interferes with.
+    fn merge(&mut self, remove: Local, keep: Local) {
+        let (remove, keep) = (self.index[remove].unwrap(), self.index[keep].unwrap());
+        self.matrix.merge(remove, keep);
+        let others: Vec<usize> = self.matrix.iter(remove).collect();
+        for other in others {
+            self.matrix.add(other, keep);
+        }
+    }
+
+    fn compute<'tcx>(&mut self, mir: &Mir<'tcx>) {
+        let after_return_write = self.blocks_after_return_write(mir);
+        let liveness = liveness_of_locals(mir);
+
+        for (block, data) in mir.basic_blocks().iter_enumerated() {
+            // The statement index of the first write to the return place in this block.
+            let first_return_write = (0..data.statements.len() + 1).find(|&i| {
+                let location = Location { block, statement_index: i };
+                Effects::of_location(mir, location).clobbers.contains(&RETURN_POINTER)
+            });
+            let is_after_return_write = |statement_index| {
+                after_return_write.contains(block.index()) ||
+                    first_return_write.map_or(false, |first| first < statement_index)
+            };
+
+            let mut live = liveness.outs[block].clone();
+            for statement_index in (0..data.statements.len() + 1).rev() {
+                let location = Location { block, statement_index };
+                let effects = Effects::of_location(mir, location);
+
+                let moved_from = match data.statements.get(statement_index) {
+                    Some(&Statement {
+                        kind: StatementKind::Assign(
+                            Lvalue::Local(_), Rvalue::Use(Operand::Consume(Lvalue::Local(src)))),
+                        ..
+                    }) => Some(src),
+                    _ => None,
+                };
+
+                for &def in &effects.clobbers {
+                    for other in live.iter() {
+                        if Some(other) != moved_from {
+                            self.add(def, other);
+                        }
+                    }
+                    for &other in effects.uses.iter().chain(&effects.clobbers) {
+                        if Some(other) != moved_from {
+                            self.add(def, other);
+                        }
+                    }
+                    if is_after_return_write(statement_index) {
+                        self.add(def, RETURN_POINTER);
+                    }
+                }
+
+                for def in &effects.kills {
+                    live.remove(def);
+                }
+                for used in &effects.uses {
+                    live.add(used);
+                }
+            }
+        }
+    }
+
+    /// Returns the blocks that may be entered after the return place has been written.
+    fn blocks_after_return_write<'tcx>(&self, mir: &Mir<'tcx>) -> BitVector {
+        let mut after = BitVector::new(mir.basic_blocks().len());
+        if self.index[RETURN_POINTER].is_none() {
+            return after;
+        }
+
+        let mut work_list = vec![];
+        for (block, data) in mir.basic_blocks().iter_enumerated() {
+            let writes_return = (0..data.statements.len() + 1).any(|i| {
+                let location = Location { block, statement_index: i };
+                Effects::of_location(mir, location).clobbers.contains(&RETURN_POINTER)
+            });
+            if writes_return {
+                work_list.extend(data.terminator().successors().iter().cloned());
+            }
+        }
+        while let Some(block) = work_list.pop() {
+            if after.insert(block.index()) {
+                work_list.extend(mir[block].terminator().successors().iter().cloned());
+            }
+        }
+        after
+    }
+}
+
+/// How a single statement or terminator accesses locals.
+struct Effects {
+    /// Locals that are no longer live before this location, following `util::liveness`.
+    kills: Vec<Local>,
+    /// Locals that are written, fully or in part.
+    clobbers: Vec<Local>,
+    /// Locals that are read.
+    uses: Vec<Local>,
+}
+
+impl Effects {
+    fn of_location<'tcx>(mir: &Mir<'tcx>, location: Location) -> Effects {
+        let mut effects = Effects { kills: vec![], clobbers: vec![], uses: vec![] };
+        let data = &mir[location.block];
+        match data.statements.get(location.statement_index) {
+            Some(statement) => effects.visit_statement(location.block, statement, location),
+            None => {
+                let terminator = data.terminator();
+                if let TerminatorKind::DropAndReplace { ref location, .. } = terminator.kind {
+                    // This writes the new value into `location`, even though it is only
+                    // visited as a drop.
+                    if let Some(local) = written_local(location) {
+                        effects.clobbers.push(local);
+                    }
+                }
+                effects.visit_terminator(location.block, terminator, location);
+            }
+        }
+        effects
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for Effects {
+    fn visit_local(&mut self,
+                   &local: &Local,
+                   context: LvalueContext<'tcx>,
+                   _: Location) {
+        match context {
+            LvalueContext::Store |
+            LvalueContext::Call => {
+                self.kills.push(local);
+                self.clobbers.push(local);
+            }
+            LvalueContext::StorageLive |
+            LvalueContext::StorageDead => {
+                self.kills.push(local);
+            }
+            LvalueContext::Projection(Mutability::Mut) => {
+                self.clobbers.push(local);
+                self.uses.push(local);
+            }
+            LvalueContext::Projection(Mutability::Not) |
+            LvalueContext::Borrow { .. } |
+            LvalueContext::Inspect |
+            LvalueContext::Consume |
+            LvalueContext::Validate |
+            LvalueContext::Drop => {
+                self.uses.push(local);
+            }
+        }
+    }
+}
+
+/// Returns the local whose storage is written through `lvalue`, if any.
+fn written_local(lvalue: &Lvalue) -> Option<Local> {
+    match *lvalue {
+        Lvalue::Local(local) => Some(local),
+        Lvalue::Static(..) => None,
+        Lvalue::Projection(ref proj) => match proj.elem {
+            ProjectionElem::Deref => None,
+            _ => written_local(&proj.base),
+        },
+    }
+}
+
+struct LocalRenamer<'a> {
+    merged_into: &'a IndexVec<Local, Local>,
+}
+
+impl<'a, 'tcx> MutVisitor<'tcx> for LocalRenamer<'a> {
+    fn visit_local(&mut self,
+                   local: &mut Local,
+                   _: LvalueContext<'tcx>,
+                   _: Location) {
+        *local = representative(self.merged_into, *local);
+    }
+}
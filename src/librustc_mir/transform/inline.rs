@@ -18,6 +18,7 @@ use rustc_data_structures::indexed_vec::{Idx, IndexVec};
 use rustc::mir::*;
 use rustc::mir::transform::{MirPass, MirSource};
 use rustc::mir::visit::*;
+use rustc::session::config::OptLevel;
 use rustc::ty::{self, Ty, TyCtxt};
 use rustc::ty::subst::{Subst,Substs};
 
@@ -43,6 +44,9 @@ struct CallSite<'tcx> {
     substs: &'tcx Substs<'tcx>,
     bb: BasicBlock,
     location: SourceInfo,
+    // Index into the inlining history of the inlined call this callsite
+    // was copied from, if any.
+    inlined_from: Option<usize>,
 }
 
 impl MirPass for Inline {
@@ -50,7 +54,22 @@ impl MirPass for Inline {
                           tcx: TyCtxt<'a, 'tcx, 'tcx>,
                           source: MirSource,
                           mir: &mut Mir<'tcx>) {
-        if tcx.sess.opts.debugging_opts.mir_opt_level >= 2 {
+        // The inliner runs by default when optimizing for speed, and can be
+        // forced on at any opt-level with `-Z mir-opt-level=2`. `-Z inline-mir`
+        // overrides both, which is used to measure its effect on compile times
+        // with `src/etc/bench-mir-inline.py`.
+        let enabled = match tcx.sess.opts.debugging_opts.inline_mir {
+            Some(enabled) => enabled,
+            None => match tcx.sess.opts.debugging_opts.mir_opt_level {
+                0 => false,
+                1 => match tcx.sess.opts.optimize {
+                    OptLevel::Default | OptLevel::Aggressive => true,
+                    _ => false,
+                },
+                _ => true,
+            },
+        };
+        if enabled {
             Inliner { tcx, source }.run_pass(mir);
         }
     }
@@ -74,8 +93,16 @@ impl<'a, 'tcx> Inliner<'a, 'tcx> {
         // in depth. It is unclear if this is the best heuristic,
         // really, but that's true of all the heuristics in this
         // file. =)
+        //
+        // Callees with MIR that has already been optimized may still
+        // contain calls that could not be inlined because of a cycle, so
+        // we also keep a history of the calls inlined so far. A callsite
+        // is never inlined into itself or into a call it was copied from,
+        // which keeps mutually recursive functions from being unrolled
+        // forever.
 
         let mut callsites = VecDeque::new();
+        let mut history: Vec<(DefId, Option<usize>)> = Vec::new();
 
         // Only do inlining into fn bodies.
         if let MirSource::Fn(_) = self.source {
@@ -88,12 +115,16 @@ impl<'a, 'tcx> Inliner<'a, 'tcx> {
                 if let TerminatorKind::Call {
                     func: Operand::Constant(ref f), .. } = terminator.kind {
                     if let ty::TyFnDef(callee_def_id, substs) = f.ty.sty {
+                        // A trait method call names the method of the trait,
+                        // whose MIR is the default body rather than the impl
+                        // that is actually called (#40473), so don't inline it.
                         if self.tcx.trait_of_item(callee_def_id).is_none() {
                             callsites.push_back(CallSite {
                                 callee: callee_def_id,
                                 substs,
                                 bb,
-                                location: terminator.source_info
+                                location: terminator.source_info,
+                                inlined_from: None,
                             });
                         }
                     }
@@ -111,6 +142,11 @@ impl<'a, 'tcx> Inliner<'a, 'tcx> {
                     continue;
                 }
 
+                if self.is_recursive(callsite, &history) {
+                    debug!("Not inlining recursive call to {:?}", callsite.callee);
+                    continue;
+                }
+
                 let callee_mir = match ty::queries::optimized_mir::try_get(self.tcx,
                                                                            callsite.location.span,
                                                                            callsite.callee) {
@@ -132,6 +168,9 @@ impl<'a, 'tcx> Inliner<'a, 'tcx> {
                     continue;
                 }
 
+                history.push((callsite.callee, callsite.inlined_from));
+                let inlined_from = Some(history.len() - 1);
+
                 // Add callsites from inlined function
                 for (bb, bb_data) in caller_mir.basic_blocks().iter_enumerated().skip(start) {
                     // Only consider direct calls to functions
@@ -139,13 +178,17 @@ impl<'a, 'tcx> Inliner<'a, 'tcx> {
                     if let TerminatorKind::Call {
                         func: Operand::Constant(ref f), .. } = terminator.kind {
                         if let ty::TyFnDef(callee_def_id, substs) = f.ty.sty {
-                            // Don't inline the same function multiple times.
-                            if callsite.callee != callee_def_id {
+                            // Don't inline trait method calls either, as above.
+                            // They are common in inlined generic code, where a
+                            // call through a `T: Trait` bound becomes a call to
+                            // the trait's method for the concrete type.
+                            if self.tcx.trait_of_item(callee_def_id).is_none() {
                                 callsites.push_back(CallSite {
                                     callee: callee_def_id,
                                     substs,
                                     bb,
-                                    location: terminator.source_info
+                                    location: terminator.source_info,
+                                    inlined_from,
                                 });
                             }
                         }
@@ -169,6 +212,29 @@ impl<'a, 'tcx> Inliner<'a, 'tcx> {
         }
     }
 
+    /// Returns true if inlining `callsite` would inline a function into
+    /// itself, either directly or through the calls it was copied from.
+    fn is_recursive(&self,
+                    callsite: CallSite<'tcx>,
+                    history: &[(DefId, Option<usize>)])
+                    -> bool
+    {
+        let caller = self.tcx.hir.local_def_id(self.source.item_id());
+        if callsite.callee == caller {
+            return true;
+        }
+
+        let mut inlined_from = callsite.inlined_from;
+        while let Some(idx) = inlined_from {
+            let (callee, parent) = history[idx];
+            if callee == callsite.callee {
+                return true;
+            }
+            inlined_from = parent;
+        }
+        false
+    }
+
     fn should_inline(&self,
                      callsite: CallSite<'tcx>,
                      callee_mir: &Mir<'tcx>)
@@ -188,6 +254,14 @@ impl<'a, 'tcx> Inliner<'a, 'tcx> {
         }
 
         let attrs = tcx.get_attrs(callsite.callee);
+
+        // Functions with their own target features or a naked body have to
+        // stay separate functions for trans to handle them correctly.
+        if attr::contains_name(&attrs[..], "target_feature") ||
+            attr::contains_name(&attrs[..], "naked") {
+            return false;
+        }
+
         let hint = attr::find_inline_attr(None, &attrs[..]);
 
         let hinted = match hint {
@@ -202,8 +276,10 @@ impl<'a, 'tcx> Inliner<'a, 'tcx> {
 
         // Only inline local functions if they would be eligible for cross-crate
         // inlining. This is to ensure that the final crate doesn't have MIR that
-        // reference unexported symbols
-        if callsite.callee.is_local() {
+        // reference unexported symbols. With `-Z always-encode-mir` the MIR of
+        // every function is exported, and so is everything it references.
+        let always_encode_mir = tcx.sess.opts.debugging_opts.always_encode_mir;
+        if callsite.callee.is_local() && !always_encode_mir {
             if callsite.substs.types().count() == 0 && !hinted {
                 return false;
             }
@@ -9,9 +9,10 @@
 // except according to those terms.
 
 use build;
+use rustc::hir::def::Def;
 use rustc::hir::def_id::{CrateNum, DefId, LOCAL_CRATE};
 use rustc::mir::Mir;
-use rustc::mir::transform::{MirPassIndex, MirSuite, MirSource,
+use rustc::mir::transform::{MirPass, MirPassIndex, MirSuite, MirSource,
                             MIR_CONST, MIR_VALIDATED, MIR_OPTIMIZED};
 use rustc::ty::{self, TyCtxt};
 use rustc::ty::maps::Providers;
@@ -41,6 +42,9 @@ pub mod dump_mir;
 pub mod deaggregator;
 pub mod instcombine;
 pub mod copy_prop;
+pub mod dest_prop;
+pub mod sroa;
+pub mod const_prop;
 pub mod generator;
 pub mod inline;
 pub mod nll;
@@ -53,6 +57,7 @@ pub(crate) fn provide(providers: &mut Providers) {
         mir_const,
         mir_validated,
         optimized_mir,
+        mir_for_ctfe,
         is_mir_available,
         ..*providers
     };
@@ -131,12 +136,47 @@ fn optimized_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> &'tcx
     ty::queries::mir_borrowck::force(tcx, DUMMY_SP, def_id);
     ty::queries::borrowck::force(tcx, DUMMY_SP, def_id);
 
-    let mut mir = tcx.mir_validated(def_id).steal();
+    // Constant evaluation interprets the unoptimized MIR, and may be needed
+    // by the optimizations themselves, so keep a copy of it before stealing.
     let source = MirSource::from_local_def_id(tcx, def_id);
+    if is_const_evaluable(tcx, source, def_id) {
+        ty::queries::mir_for_ctfe::force(tcx, DUMMY_SP, def_id);
+    }
+
+    let mut mir = tcx.mir_validated(def_id).steal();
     transform::run_suite(tcx, source, MIR_OPTIMIZED, &mut mir);
     tcx.alloc_mir(mir)
 }
 
+fn mir_for_ctfe<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> &'tcx Mir<'tcx> {
+    let source = MirSource::from_local_def_id(tcx, def_id);
+    let mut mir = tcx.mir_validated(def_id).borrow().clone();
+    // Like trans, the interpreter has no use for regions, and the MIR may be
+    // written to the crate metadata, so erase them as `optimized_mir` does.
+    erase_regions::EraseRegions.run_pass(tcx, source, &mut mir);
+    for (index, promoted_mir) in mir.promoted.iter_enumerated_mut() {
+        let promoted_source = MirSource::Promoted(source.item_id(), index);
+        erase_regions::EraseRegions.run_pass(tcx, promoted_source, promoted_mir);
+    }
+    tcx.alloc_mir(mir)
+}
+
+/// Whether the item may be evaluated at compile time: constants and statics,
+/// `const fn`s, and the tuple struct and variant constructors they can call.
+fn is_const_evaluable<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                source: MirSource,
+                                def_id: DefId)
+                                -> bool {
+    match source {
+        MirSource::Const(_) | MirSource::Static(..) => true,
+        MirSource::Fn(_) => match tcx.describe_def(def_id) {
+            Some(Def::StructCtor(..)) | Some(Def::VariantCtor(..)) => true,
+            _ => tcx.is_const_fn(def_id),
+        },
+        MirSource::Promoted(..) | MirSource::GeneratorDrop(_) => false,
+    }
+}
+
 fn run_suite<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
                        source: MirSource,
                        suite: MirSuite,
@@ -113,6 +113,9 @@ impl fmt::Display for Mode {
 
 struct Qualifier<'a, 'gcx: 'a+'tcx, 'tcx: 'a> {
     mode: Mode,
+    /// Whether this `const fn` may use `let` bindings and control flow,
+    /// with `#![feature(const_fn_control_flow)]`.
+    control_flow: bool,
     span: Span,
     def_id: DefId,
     mir: &'a Mir<'tcx>,
@@ -139,6 +142,8 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
         rpo.reset();
         Qualifier {
             mode,
+            control_flow: mode == Mode::ConstFn &&
+                          tcx.sess.features.borrow().const_fn_control_flow,
             span: mir.span,
             def_id,
             mir,
@@ -227,11 +232,14 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
     fn assign(&mut self, dest: &Lvalue<'tcx>, location: Location) {
         let qualif = self.qualif;
         let span = self.span;
+        let control_flow = self.control_flow;
         let store = |slot: &mut Option<Qualif>| {
-            if slot.is_some() {
-                span_bug!(span, "multiple assignments to {:?}", dest);
-            }
-            *slot = Some(qualif);
+            *slot = match *slot {
+                // With control flow, each branch may assign the same local.
+                Some(previous) if control_flow => Some(previous | qualif),
+                Some(_) => span_bug!(span, "multiple assignments to {:?}", dest),
+                None => Some(qualif),
+            };
         };
 
         // Only handle promotable temps in non-const functions.
@@ -281,15 +289,16 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
             _ => {
                 // Catch more errors in the destination.
                 self.visit_lvalue(dest, LvalueContext::Store, location);
-                self.statement_like();
+                if !self.control_flow {
+                    self.statement_like();
+                }
             }
         }
     }
 
-    /// Qualify a whole const, static initializer or const fn.
-    fn qualify_const(&mut self) -> (Qualif, Rc<IdxSetBuf<Local>>) {
-        debug!("qualifying {} {:?}", self.mode, self.def_id);
-
+    /// Walk the body as a single path from the start block to the return,
+    /// erroring on any branch or loop.
+    fn qualify_linear_cfg(&mut self) {
         let mir = self.mir;
 
         let mut seen_blocks = BitVector::new(mir.basic_blocks().len());
@@ -372,6 +381,23 @@ impl<'a, 'tcx> Qualifier<'a, 'tcx, 'tcx> {
                 }
             }
         }
+    }
+
+    /// Qualify a whole const, static initializer or const fn.
+    fn qualify_const(&mut self) -> (Qualif, Rc<IdxSetBuf<Local>>) {
+        debug!("qualifying {} {:?}", self.mode, self.def_id);
+
+        let mir = self.mir;
+
+        if self.control_flow {
+            // The body is run by the MIR interpreter, which can handle any
+            // CFG, so only the statements themselves need checking.
+            while let Some((bb, data)) = self.rpo.next() {
+                self.visit_basic_block_data(bb, data);
+            }
+        } else {
+            self.qualify_linear_cfg();
+        }
 
         self.qualif = self.return_qualif.unwrap_or(Qualif::NOT_CONST);
 
@@ -0,0 +1,326 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Scalar replacement of aggregates.
+//!
+//! This pass splits struct and tuple locals which are only ever accessed through their fields
+//! into one local per field, so that
+//!
+//!     StorageLive(_1);
+//!     _1 = (_2, _3);
+//!     _4 = (_1.0: u32);
+//!     StorageDead(_1);
+//!
+//! becomes
+//!
+//!     StorageLive(_5);
+//!     StorageLive(_6);
+//!     _5 = _2;
+//!     _6 = _3;
+//!     _4 = _5;
+//!     StorageDead(_5);
+//!     StorageDead(_6);
+//!
+//! Apart from storage statements and assignments of a whole aggregate, any use of the local as a
+//! whole (moving it, borrowing it, dropping it, passing it to a call...) prevents it from being
+//! split. So does borrowing one of its fields, since unsafe code may use that borrow to reach
+//! the other fields, e.g. of a `#[repr(C)]` struct. Only fields that are actually used get a
+//! local.
+//!
+//! Struct aggregates are usually already split into field assignments by the `Deaggregator`,
+//! which this pass runs after. Like the `Deaggregator`, it only runs at `-Z mir-opt-level=3`.
+//! It also runs after `ConstProp`, which doesn't track values stored in fields, so constants
+//! that only become visible once a local is split are neither propagated nor checked by the
+//! `const_err` lint.
+
+use rustc::mir::*;
+use rustc::mir::transform::{MirPass, MirSource};
+use rustc::mir::visit::{LvalueContext, MutVisitor, Visitor};
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc_data_structures::indexed_set::IdxSetBuf;
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use std::mem;
+
+pub struct ScalarReplacementOfAggregates;
+
+impl MirPass for ScalarReplacementOfAggregates {
+    fn run_pass<'a, 'tcx>(&self,
+                          tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          source: MirSource,
+                          mir: &mut Mir<'tcx>) {
+        // We only run when mir_opt_level > 2, like the Deaggregator.
+        if tcx.sess.opts.debugging_opts.mir_opt_level <= 2 {
+            return;
+        }
+
+        let def_id = match source {
+            MirSource::Fn(node_id) => tcx.hir.local_def_id(node_id),
+            _ => return,
+        };
+
+        // Locals which are saved across yields are accessed as a whole by the generator
+        // transform.
+        if mir.yield_ty.is_some() {
+            return;
+        }
+
+        let param_env = tcx.param_env(def_id);
+        let mut candidates = IdxSetBuf::new_empty(mir.local_decls.len());
+        for local in mir.vars_and_temps_iter() {
+            if can_split(tcx, param_env, mir.local_decls[local].ty) {
+                candidates.add(&local);
+            }
+        }
+
+        let (candidates, fields) = {
+            let mut finder = FieldFinder {
+                tcx,
+                mir,
+                candidates,
+                fields: IndexVec::from_elem_n(vec![], mir.local_decls.len()),
+            };
+            finder.visit_mir(mir);
+            (finder.candidates, finder.fields)
+        };
+
+        // Create a local for each field that is used.
+        let local_count = mir.local_decls.len();
+        let mut replacements = IndexVec::from_elem_n(vec![], local_count);
+        for local in (0..local_count).map(Local::new) {
+            if !candidates.contains(&local) {
+                continue;
+            }
+            let source_info = mir.local_decls[local].source_info;
+            let lexical_scope = mir.local_decls[local].lexical_scope;
+            replacements[local] = fields[local].iter().map(|ty| {
+                ty.map(|ty| {
+                    let decl = LocalDecl {
+                        source_info,
+                        lexical_scope,
+                        ..LocalDecl::new_temp(ty, source_info.span)
+                    };
+                    mir.local_decls.push(decl)
+                })
+            }).collect();
+            debug!("ScalarReplacementOfAggregates: replacing {:?} with {:?}",
+                   local, replacements[local]);
+        }
+
+        let mut replacer = FieldReplacer { replacements };
+        for data in mir.basic_blocks_mut() {
+            let statements = mem::replace(&mut data.statements, vec![]);
+            for statement in statements {
+                replacer.split_statement(statement, &mut data.statements);
+            }
+        }
+        replacer.visit_mir(mir);
+    }
+}
+
+/// Returns true if locals of type `ty` may be split into their fields.
+fn can_split<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                       param_env: ty::ParamEnv<'tcx>,
+                       ty: Ty<'tcx>)
+                       -> bool {
+    // Drops have to see the whole value.
+    if ty.needs_drop(tcx, param_env) {
+        return false;
+    }
+    match ty.sty {
+        ty::TyTuple(tys, _) => !tys.is_empty(),
+        ty::TyAdt(adt_def, _) => {
+            adt_def.is_struct() && !adt_def.repr.packed() && !adt_def.repr.simd() &&
+                !adt_def.struct_variant().fields.is_empty()
+        }
+        _ => false,
+    }
+}
+
+/// Returns the number of fields of a splittable type.
+fn field_count<'tcx>(ty: Ty<'tcx>) -> usize {
+    match ty.sty {
+        ty::TyTuple(tys, _) => tys.len(),
+        ty::TyAdt(adt_def, _) => adt_def.struct_variant().fields.len(),
+        _ => bug!("cannot split a local of type {:?}", ty),
+    }
+}
+
+/// Returns the operands of an assignment that builds the whole of `local` from its fields.
+fn aggregate_operands<'s, 'tcx>(statement: &'s Statement<'tcx>, local: Local)
+                                -> Option<&'s [Operand<'tcx>]> {
+    match statement.kind {
+        StatementKind::Assign(Lvalue::Local(dest), Rvalue::Aggregate(ref kind, ref operands))
+            if dest == local => {
+            match **kind {
+                AggregateKind::Tuple |
+                AggregateKind::Adt(_, 0, _, None) => Some(&operands[..]),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn root_local(lvalue: &Lvalue) -> Option<Local> {
+    match *lvalue {
+        Lvalue::Local(local) => Some(local),
+        Lvalue::Static(..) => None,
+        Lvalue::Projection(ref proj) => root_local(&proj.base),
+    }
+}
+
+/// Finds the candidates that are only accessed through their fields, and the types of the
+/// fields that are used.
+struct FieldFinder<'m, 'a: 'm, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'m Mir<'tcx>,
+    candidates: IdxSetBuf<Local>,
+    fields: IndexVec<Local, Vec<Option<Ty<'tcx>>>>,
+}
+
+impl<'m, 'a, 'tcx> FieldFinder<'m, 'a, 'tcx> {
+    fn use_field(&mut self, local: Local, field: Field, ty: Ty<'tcx>) {
+        if !self.candidates.contains(&local) {
+            return;
+        }
+        if self.fields[local].is_empty() {
+            let count = field_count(self.mir.local_decls[local].ty);
+            self.fields[local] = vec![None; count];
+        }
+        self.fields[local][field.index()] = Some(ty);
+    }
+}
+
+impl<'m, 'a, 'tcx> Visitor<'tcx> for FieldFinder<'m, 'a, 'tcx> {
+    fn visit_statement(&mut self,
+                       block: BasicBlock,
+                       statement: &Statement<'tcx>,
+                       location: Location) {
+        if let StatementKind::Assign(Lvalue::Local(dest), _) = statement.kind {
+            if let Some(operands) = aggregate_operands(statement, dest) {
+                for (i, operand) in operands.iter().enumerate() {
+                    // The fields are assigned one at a time once split, so they must not be
+                    // read while building the aggregate.
+                    if let Operand::Consume(ref lvalue) = *operand {
+                        if root_local(lvalue) == Some(dest) {
+                            self.candidates.remove(&dest);
+                        }
+                    }
+                    let ty = operand.ty(self.mir, self.tcx);
+                    self.use_field(dest, Field::new(i), ty);
+                    self.visit_operand(operand, location);
+                }
+                return;
+            }
+        }
+        self.super_statement(block, statement, location);
+    }
+
+    fn visit_lvalue(&mut self,
+                    lvalue: &Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        if let Lvalue::Projection(ref proj) = *lvalue {
+            if let (&Lvalue::Local(local), &ProjectionElem::Field(field, ty)) =
+                (&proj.base, &proj.elem) {
+                if let LvalueContext::Borrow { .. } = context {
+                    self.candidates.remove(&local);
+                }
+                self.use_field(local, field, ty);
+                return;
+            }
+        }
+        self.super_lvalue(lvalue, context, location);
+    }
+
+    fn visit_local(&mut self,
+                   &local: &Local,
+                   context: LvalueContext<'tcx>,
+                   _: Location) {
+        match context {
+            LvalueContext::StorageLive |
+            LvalueContext::StorageDead => {}
+            _ => {
+                self.candidates.remove(&local);
+            }
+        }
+    }
+}
+
+struct FieldReplacer {
+    /// The local replacing each field of a split local, if that field is used.
+    replacements: IndexVec<Local, Vec<Option<Local>>>,
+}
+
+impl FieldReplacer {
+    /// Pushes the statements replacing `statement` onto `statements`.
+    fn split_statement<'tcx>(&self,
+                             statement: Statement<'tcx>,
+                             statements: &mut Vec<Statement<'tcx>>) {
+        let source_info = statement.source_info;
+        match statement.kind {
+            StatementKind::StorageLive(local) if !self.replacements[local].is_empty() => {
+                for &field_local in self.replacements[local].iter().flat_map(|l| l) {
+                    statements.push(Statement {
+                        source_info,
+                        kind: StatementKind::StorageLive(field_local),
+                    });
+                }
+                return;
+            }
+            StatementKind::StorageDead(local) if !self.replacements[local].is_empty() => {
+                for &field_local in self.replacements[local].iter().flat_map(|l| l) {
+                    statements.push(Statement {
+                        source_info,
+                        kind: StatementKind::StorageDead(field_local),
+                    });
+                }
+                return;
+            }
+            StatementKind::Assign(Lvalue::Local(dest), _)
+                if !self.replacements[dest].is_empty() => {
+                if let Some(operands) = aggregate_operands(&statement, dest) {
+                    for (i, operand) in operands.iter().enumerate() {
+                        let field_local = self.replacements[dest][i].unwrap();
+                        statements.push(Statement {
+                            source_info,
+                            kind: StatementKind::Assign(Lvalue::Local(field_local),
+                                                        Rvalue::Use(operand.clone())),
+                        });
+                    }
+                    return;
+                }
+            }
+            _ => {}
+        }
+        statements.push(statement);
+    }
+}
+
+impl<'tcx> MutVisitor<'tcx> for FieldReplacer {
+    fn visit_lvalue(&mut self,
+                    lvalue: &mut Lvalue<'tcx>,
+                    context: LvalueContext<'tcx>,
+                    location: Location) {
+        let replacement = match *lvalue {
+            Lvalue::Projection(ref proj) => match (&proj.base, &proj.elem) {
+                (&Lvalue::Local(local), &ProjectionElem::Field(field, _)) => {
+                    self.replacements[local].get(field.index()).and_then(|&l| l)
+                }
+                _ => None,
+            },
+            _ => None,
+        };
+        match replacement {
+            Some(local) => *lvalue = Lvalue::Local(local),
+            None => self.super_lvalue(lvalue, context, location),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Finds the locals whose address is taken.
+
+use rustc::mir::{Local, Lvalue, Location, Mir, ProjectionElem, Rvalue};
+use rustc::mir::visit::Visitor;
+use rustc_data_structures::indexed_set::IdxSetBuf;
+
+/// Returns the locals that are borrowed anywhere in `mir`, either as a whole or through one of
+/// their fields or elements. Borrowing something behind a reference stored in a local doesn't
+/// count as borrowing the local.
+///
+/// Optimizations can't follow what happens to a borrowed local through the reference, so they
+/// generally have to leave these locals alone.
+pub fn borrowed_locals<'tcx>(mir: &Mir<'tcx>) -> IdxSetBuf<Local> {
+    let mut visitor = BorrowedLocalsVisitor {
+        borrowed: IdxSetBuf::new_empty(mir.local_decls.len()),
+    };
+    visitor.visit_mir(mir);
+    visitor.borrowed
+}
+
+struct BorrowedLocalsVisitor {
+    borrowed: IdxSetBuf<Local>,
+}
+
+impl<'tcx> Visitor<'tcx> for BorrowedLocalsVisitor {
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Ref(_, _, ref lvalue) = *rvalue {
+            if let Some(local) = borrowed_local(lvalue) {
+                self.borrowed.add(&local);
+            }
+        }
+        self.super_rvalue(rvalue, location)
+    }
+}
+
+fn borrowed_local(lvalue: &Lvalue) -> Option<Local> {
+    match *lvalue {
+        Lvalue::Local(local) => Some(local),
+        Lvalue::Static(..) => None,
+        Lvalue::Projection(ref proj) => match proj.elem {
+            ProjectionElem::Deref => None,
+            _ => borrowed_local(&proj.base),
+        },
+    }
+}
@@ -9,6 +9,7 @@
 // except according to those terms.
 
 pub mod borrowck_errors;
+pub mod borrowed_locals;
 pub mod elaborate_drops;
 pub mod def_use;
 pub mod patch;
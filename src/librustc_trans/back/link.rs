@@ -11,11 +11,13 @@
 use super::archive::{ArchiveBuilder, ArchiveConfig};
 use super::bytecode::{self, RLIB_BYTECODE_EXTENSION};
 use super::linker::Linker;
+use super::pnacl;
 use super::command::Command;
 use super::rpath::RPathConfig;
 use super::rpath;
 use metadata::METADATA_FILENAME;
 use rustc::session::config::{self, NoDebugInfo, OutputFilenames, OutputType, PrintRequest};
+use rustc::session::config::Lto;
 use rustc::session::filesearch;
 use rustc::session::search_paths::PathKind;
 use rustc::session::Session;
@@ -26,7 +28,7 @@ use rustc::util::common::time;
 use rustc::util::fs::fix_windows_verbatim_for_gcc;
 use rustc::hir::def_id::CrateNum;
 use rustc_back::tempdir::TempDir;
-use rustc_back::{PanicStrategy, RelroLevel};
+use rustc_back::{LinkerFlavor, LldFlavor, PanicStrategy, RelroLevel};
 use context::get_reloc_model;
 use llvm;
 
@@ -81,7 +83,7 @@ pub fn get_linker(sess: &Session) -> (String, Command, Vec<(OsString, OsString)>
         }
     };
 
-    if let Some(ref linker) = sess.opts.cg.linker {
+    let (pname, mut cmd, envs) = if let Some(ref linker) = sess.opts.cg.linker {
         (linker.clone(), cmd(linker), envs)
     } else if sess.target.target.options.is_like_msvc {
         let (cmd, envs) = msvc_link_exe_cmd(sess);
@@ -89,7 +91,17 @@ pub fn get_linker(sess: &Session) -> (String, Command, Vec<(OsString, OsString)>
     } else {
         let linker = &sess.target.target.options.linker;
         (linker.clone(), cmd(linker), envs)
+    };
+
+    // LLD is one binary with several personalities, and which one it picks
+    // normally depends on the name it was invoked under. We may be running
+    // `rust-lld` out of the sysroot (which is on the `PATH` set up above) or
+    // a user-provided `lld`, so always tell it explicitly what to be.
+    if let LinkerFlavor::Lld(f) = sess.linker_flavor() {
+        cmd.arg("-flavor").arg(f.desc());
     }
+
+    (pname, cmd, envs)
 }
 
 #[cfg(windows)]
@@ -286,6 +298,9 @@ fn link_binary_output(sess: &Session,
                                &out_filename,
                                tmpdir.path());
             }
+            _ if sess.target.target.options.is_like_pnacl => {
+                pnacl::link(sess, trans, &out_filename);
+            }
             _ => {
                 link_natively(sess, crate_type, &out_filename,
                               trans, outputs, tmpdir.path());
@@ -534,7 +549,7 @@ fn link_staticlib(sess: &Session,
         });
         ab.add_rlib(path,
                     &name.as_str(),
-                    sess.lto() && !ignored_for_lto(&trans.crate_info, cnum),
+                    sess.lto() != Lto::No && !ignored_for_lto(&trans.crate_info, cnum),
                     skip_object_files).unwrap();
 
         all_native_libs.extend(trans.crate_info.native_libraries[&cnum].iter().cloned());
@@ -899,7 +914,8 @@ fn link_args(cmd: &mut Linker,
     // If we're building a dynamic library then some platforms need to make sure
     // that all symbols are exported correctly from the dynamic library.
     if crate_type != config::CrateTypeExecutable ||
-       sess.target.target.options.is_like_emscripten {
+       sess.target.target.options.is_like_emscripten ||
+       sess.linker_flavor() == LinkerFlavor::Lld(LldFlavor::Wasm) {
         cmd.export_symbols(tmpdir, crate_type);
     }
 
@@ -1239,7 +1255,7 @@ fn add_upstream_rust_crates(cmd: &mut Linker,
             lib.kind == NativeLibraryKind::NativeStatic && !relevant_lib(sess, lib)
         });
 
-        if (!sess.lto() || ignored_for_lto(&trans.crate_info, cnum)) &&
+        if (sess.lto() == Lto::No || ignored_for_lto(&trans.crate_info, cnum)) &&
            crate_type != config::CrateTypeDylib &&
            !skip_native {
             cmd.link_rlib(&fix_windows_verbatim_for_gcc(cratepath));
@@ -1280,7 +1296,7 @@ fn add_upstream_rust_crates(cmd: &mut Linker,
                 // file, then we don't need the object file as it's part of the
                 // LTO module. Note that `#![no_builtins]` is excluded from LTO,
                 // though, so we let that object file slide.
-                let skip_because_lto = sess.lto() && is_rust_object &&
+                let skip_because_lto = sess.lto() != Lto::No && is_rust_object &&
                                         !trans.crate_info.is_no_builtins.contains(&cnum);
 
                 if skip_because_cfg_say_so || skip_because_lto {
@@ -1315,7 +1331,7 @@ fn add_upstream_rust_crates(cmd: &mut Linker,
     fn add_dynamic_crate(cmd: &mut Linker, sess: &Session, cratepath: &Path) {
         // If we're performing LTO, then it should have been previously required
         // that all upstream rust dependencies were available in an rlib format.
-        assert!(!sess.lto());
+        assert_eq!(sess.lto(), Lto::No);
 
         // Just need to tell the linker about where the library lives and
         // what its name is
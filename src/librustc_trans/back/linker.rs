@@ -23,7 +23,7 @@ use rustc::middle::dependency_format::Linkage;
 use rustc::session::Session;
 use rustc::session::config::{self, CrateType, OptLevel, DebugInfoLevel};
 use rustc::ty::TyCtxt;
-use rustc_back::LinkerFlavor;
+use rustc_back::{LinkerFlavor, LldFlavor};
 use serialize::{json, Encoder};
 
 /// For all the linkers we support, and information they might
@@ -68,6 +68,7 @@ impl LinkerInfo {
                     is_ld: false,
                 }) as Box<Linker>
             }
+            LinkerFlavor::Lld(LldFlavor::Ld) |
             LinkerFlavor::Ld => {
                 Box::new(GccLinker {
                     cmd,
@@ -77,6 +78,13 @@ impl LinkerInfo {
                     is_ld: true,
                 }) as Box<Linker>
             }
+            LinkerFlavor::Lld(LldFlavor::Wasm) => {
+                Box::new(WasmLd {
+                    cmd,
+                    sess,
+                    info: self,
+                }) as Box<Linker>
+            }
         }
     }
 }
@@ -744,6 +752,138 @@ impl<'a> Linker for EmLinker<'a> {
     }
 }
 
+pub struct WasmLd<'a> {
+    cmd: Command,
+    sess: &'a Session,
+    info: &'a LinkerInfo,
+}
+
+impl<'a> Linker for WasmLd<'a> {
+    fn link_dylib(&mut self, lib: &str) {
+        self.cmd.arg("-l").arg(lib);
+    }
+
+    fn link_staticlib(&mut self, lib: &str) {
+        self.cmd.arg("-l").arg(lib);
+    }
+
+    fn link_rlib(&mut self, lib: &Path) {
+        self.cmd.arg(lib);
+    }
+
+    fn include_path(&mut self, path: &Path) {
+        self.cmd.arg("-L").arg(path);
+    }
+
+    fn framework_path(&mut self, _path: &Path) {
+        bug!("frameworks are not supported on wasm")
+    }
+
+    fn output_filename(&mut self, path: &Path) {
+        self.cmd.arg("-o").arg(path);
+    }
+
+    fn add_object(&mut self, path: &Path) {
+        self.cmd.arg(path);
+    }
+
+    fn position_independent_executable(&mut self) {
+        // noop
+    }
+
+    fn partial_relro(&mut self) {
+        // noop
+    }
+
+    fn full_relro(&mut self) {
+        // noop
+    }
+
+    fn build_static_executable(&mut self) {
+        // noop
+    }
+
+    fn args(&mut self, args: &[String]) {
+        self.cmd.args(args);
+    }
+
+    fn link_rust_dylib(&mut self, lib: &str, _path: &Path) {
+        self.cmd.arg("-l").arg(lib);
+    }
+
+    fn link_framework(&mut self, _framework: &str) {
+        bug!("frameworks are not supported on wasm")
+    }
+
+    fn link_whole_staticlib(&mut self, lib: &str, _search_path: &[PathBuf]) {
+        self.cmd.arg("--whole-archive").arg("-l").arg(lib);
+        self.cmd.arg("--no-whole-archive");
+    }
+
+    fn link_whole_rlib(&mut self, lib: &Path) {
+        self.cmd.arg("--whole-archive").arg(lib).arg("--no-whole-archive");
+    }
+
+    fn gc_sections(&mut self, _keep_metadata: bool) {
+        // There's no dynamic loading of wasm modules, so there's no metadata
+        // section worth keeping around either.
+        self.cmd.arg("--gc-sections");
+    }
+
+    fn optimize(&mut self) {
+        self.cmd.arg(match self.sess.opts.optimize {
+            OptLevel::No => "-O0",
+            OptLevel::Less => "-O1",
+            OptLevel::Default => "-O2",
+            OptLevel::Aggressive => "-O3",
+            // Currently LLD doesn't support `Os` and `Oz`, so pass through `O2`
+            // instead.
+            OptLevel::Size => "-O2",
+            OptLevel::SizeMin => "-O2"
+        });
+    }
+
+    fn debuginfo(&mut self) {
+        // Debuginfo is carried in custom sections which LLD passes through.
+    }
+
+    fn no_default_libraries(&mut self) {
+        // There are no default libraries to link against on wasm.
+    }
+
+    fn build_dylib(&mut self, _out_filename: &Path) {
+        // A wasm "dylib" is just a module with exports, the same as an
+        // executable, so there's nothing to change here.
+    }
+
+    fn export_symbols(&mut self, _tmpdir: &Path, crate_type: CrateType) {
+        // LLD exports nothing from a wasm module unless told to, so spell
+        // out exactly the symbols that this crate type should expose.
+        for sym in self.info.exports[&crate_type].iter() {
+            self.cmd.arg("--export").arg(sym);
+        }
+    }
+
+    fn subsystem(&mut self, _subsystem: &str) {
+        // noop
+    }
+
+    fn finalize(&mut self) -> Command {
+        // There's no entry point for libraries and `main` is exported
+        // explicitly like any other symbol, so don't make LLD look for
+        // `_start`.
+        self.cmd.arg("--no-entry");
+
+        // Imports from the host (e.g. JS functions) show up as undefined
+        // symbols, so they shouldn't be an error at link time.
+        self.cmd.arg("--allow-undefined");
+
+        let mut cmd = Command::new("");
+        ::std::mem::swap(&mut cmd, &mut self.cmd);
+        cmd
+    }
+}
+
 fn exported_symbols(tcx: TyCtxt, crate_type: CrateType) -> Vec<String> {
     let mut symbols = Vec::new();
 
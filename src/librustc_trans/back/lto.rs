@@ -10,8 +10,9 @@
 
 use back::bytecode::{DecodedBytecode, RLIB_BYTECODE_EXTENSION};
 use back::write;
+use back::pnacl;
 use back::symbol_export;
-use rustc::session::config;
+use rustc::session::config::{self, Lto};
 use errors::{FatalError, Handler};
 use llvm;
 use llvm::archive_ro::ArchiveRO;
@@ -19,13 +20,29 @@ use llvm::{ModuleRef, TargetMachineRef, True, False};
 use rustc::middle::exported_symbols::SymbolExportLevel;
 use rustc::util::common::time;
 use rustc::hir::def_id::LOCAL_CRATE;
+use rustc_data_structures::fx::FxHashMap;
 use back::write::{ModuleConfig, with_llvm_pmb, CodegenContext};
-use {ModuleTranslation, ModuleKind};
+use {CompiledModule, ModuleTranslation, ModuleKind, ModuleSource, ModuleLlvm};
 
 use libc;
 
-use std::ffi::CString;
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::{CStr, CString};
+use std::fs::{self, File};
+use std::hash::Hasher;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::Path;
 use std::slice;
+use std::sync::Arc;
+
+/// The name of the file in the incremental compilation directory recording
+/// which modules each codegen unit imported from during the last ThinLTO run.
+const THIN_LTO_IMPORTS_FILE_NAME: &'static str = "thin-lto-imports.bin";
+
+/// The extension of the temporary file holding a codegen unit's bitcode as it
+/// was handed to ThinLTO, which incremental compilation saves alongside the
+/// unit's object file.
+pub const PRE_THIN_LTO_BC_EXT: &'static str = "pre-lto.bc";
 
 pub fn crate_type_allows_lto(crate_type: config::CrateType) -> bool {
     match crate_type {
@@ -45,14 +62,14 @@ pub enum LtoModuleTranslation {
         _serialized_bitcode: Vec<SerializedModule>,
     },
 
-    // Note the lack of other entries in this enum! Ideally one day this gap is
-    // intended to be filled with a "Thin" LTO variant.
+    Thin(ThinModule),
 }
 
 impl LtoModuleTranslation {
     pub fn name(&self) -> &str {
         match *self {
             LtoModuleTranslation::Fat { .. } => "everything",
+            LtoModuleTranslation::Thin(ref m) => m.name(),
         }
     }
 
@@ -71,9 +88,26 @@ impl LtoModuleTranslation {
                 let config = cgcx.config(trans.kind);
                 let llmod = trans.llvm().unwrap().llmod;
                 let tm = trans.llvm().unwrap().tm;
-                run_pass_manager(cgcx, tm, llmod, config);
+                if cgcx.emit_pnacl_bitcode {
+                    let diag_handler = cgcx.create_diag_handler();
+                    pnacl::simplify_abi(cgcx, &diag_handler, llmod, false)?;
+                    run_pass_manager(cgcx, tm, llmod, config, false);
+                    pnacl::simplify_abi(cgcx, &diag_handler, llmod, true)?;
+                    pnacl::verify(cgcx, &diag_handler, llmod)?;
+                    cgcx.save_temp_bitcode(&trans, "lto.pnacl");
+                } else {
+                    run_pass_manager(cgcx, tm, llmod, config, false);
+                }
                 Ok(trans)
             }
+            LtoModuleTranslation::Thin(ref mut thin) => thin.optimize(cgcx),
+        }
+    }
+
+    pub fn kind(&self) -> ModuleKind {
+        match *self {
+            LtoModuleTranslation::Fat { .. } => ModuleKind::Regular,
+            LtoModuleTranslation::Thin(ref m) => m.kind,
         }
     }
 
@@ -83,12 +117,16 @@ impl LtoModuleTranslation {
         match *self {
             // Only one module with fat LTO, so the cost doesn't matter.
             LtoModuleTranslation::Fat { .. } => 0,
+            LtoModuleTranslation::Thin(ref m) => m.cost(),
         }
     }
 }
 
+/// Performs LTO over all of the given modules, returning the modules which
+/// still need to be optimized and code generated, along with any module
+/// whose artifacts from a previous incremental session can be used as-is.
 pub fn run(cgcx: &CodegenContext, modules: Vec<ModuleTranslation>)
-    -> Result<Vec<LtoModuleTranslation>, FatalError>
+    -> Result<(Vec<LtoModuleTranslation>, Vec<CompiledModule>), FatalError>
 {
     let diag_handler = cgcx.create_diag_handler();
     if cgcx.opts.cg.prefer_dynamic {
@@ -162,7 +200,14 @@ pub fn run(cgcx: &CodegenContext, modules: Vec<ModuleTranslation>)
                                                          .map(|c| c.as_ptr())
                                                          .collect();
 
-    fat_lto(cgcx, &diag_handler, modules, upstream_modules, &arr)
+    match cgcx.lto {
+        Lto::Fat => {
+            let modules = fat_lto(cgcx, &diag_handler, modules, upstream_modules, &arr)?;
+            Ok((modules, Vec::new()))
+        }
+        Lto::Thin => thin_lto(cgcx, &diag_handler, modules, upstream_modules, &arr),
+        Lto::No => bug!("running LTO without it being requested"),
+    }
 }
 
 fn fat_lto(cgcx: &CodegenContext,
@@ -258,7 +303,8 @@ fn fat_lto(cgcx: &CodegenContext,
 fn run_pass_manager(cgcx: &CodegenContext,
                     tm: TargetMachineRef,
                     llmod: ModuleRef,
-                    config: &ModuleConfig) {
+                    config: &ModuleConfig,
+                    thin: bool) {
 
     // Now we have one massive module inside of llmod. Time to run the
     // LTO-specific optimization passes that LLVM provides.
@@ -274,9 +320,13 @@ fn run_pass_manager(cgcx: &CodegenContext,
         llvm::LLVMRustAddPass(pm, pass);
 
         with_llvm_pmb(llmod, config, &mut |b| {
-            llvm::LLVMPassManagerBuilderPopulateLTOPassManager(b, pm,
-                /* Internalize = */ False,
-                /* RunInliner = */ True);
+            if thin {
+                llvm::LLVMRustPassManagerBuilderPopulateThinLTOPassManager(b, pm);
+            } else {
+                llvm::LLVMPassManagerBuilderPopulateLTOPassManager(b, pm,
+                    /* Internalize = */ False,
+                    /* RunInliner = */ True);
+            }
         });
 
         let pass = llvm::LLVMRustFindAndCreatePass("verify\0".as_ptr() as *const _);
@@ -293,14 +343,18 @@ fn run_pass_manager(cgcx: &CodegenContext,
 
 pub enum SerializedModule {
     Local(ModuleBuffer),
+    Thin(ThinBuffer),
     FromRlib(Vec<u8>),
+    FromIncrCache(Vec<u8>),
 }
 
 impl SerializedModule {
     fn data(&self) -> &[u8] {
         match *self {
             SerializedModule::Local(ref m) => m.data(),
+            SerializedModule::Thin(ref m) => m.data(),
             SerializedModule::FromRlib(ref m) => m,
+            SerializedModule::FromIncrCache(ref m) => m,
         }
     }
 }
@@ -331,3 +385,518 @@ impl Drop for ModuleBuffer {
         unsafe { llvm::LLVMRustModuleBufferFree(self.0); }
     }
 }
+
+pub struct ThinBuffer(*mut llvm::ThinLTOBuffer);
+
+unsafe impl Send for ThinBuffer {}
+unsafe impl Sync for ThinBuffer {}
+
+impl ThinBuffer {
+    pub fn new(m: ModuleRef) -> ThinBuffer {
+        unsafe {
+            let buffer = llvm::LLVMRustThinLTOBufferCreate(m);
+            ThinBuffer(buffer)
+        }
+    }
+
+    pub fn data(&self) -> &[u8] {
+        unsafe {
+            let ptr = llvm::LLVMRustThinLTOBufferPtr(self.0) as *const _;
+            let len = llvm::LLVMRustThinLTOBufferLen(self.0);
+            slice::from_raw_parts(ptr, len)
+        }
+    }
+}
+
+impl Drop for ThinBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            llvm::LLVMRustThinLTOBufferFree(self.0);
+        }
+    }
+}
+
+/// Prepare "thin" LTO to get run on these modules.
+///
+/// The general structure of ThinLTO is quite different from the structure of
+/// "fat" LTO above. With "fat" LTO all LLVM modules in question are merged into
+/// one giant LLVM module, and then we run more optimization passes over this
+/// big module after internalizing most symbols. Thin LTO, on the other hand,
+/// avoid this large bottleneck through more targeted optimization.
+///
+/// At a high level Thin LTO looks like:
+///
+///     1. Prepare a "summary" of each LLVM module in question which describes
+///        the values inside, cost of the values, etc.
+///     2. Merge the summaries of all modules in question into one "index"
+///     3. Perform some global analysis on this index
+///     4. For each module, use the index and analysis calculated previously to
+///        perform local transformations on the module, for example inlining
+///        small functions from other modules.
+///     5. Run thin-specific optimization passes over each module, and then code
+///        generate everything at the end.
+///
+/// The summary for each module is intended to be quite cheap, and the global
+/// index is relatively quite cheap to create as well. As a result, the goal of
+/// ThinLTO is to reduce the bottleneck on LTO and enable LTO to be used in more
+/// situations. For example one cheap optimization is that we can parallelize
+/// all codegen modules, easily making use of all the cores on a machine.
+///
+/// With all that in mind, the function here is designed at specifically just
+/// calculating the *index* for ThinLTO. This index will then be shared amongst
+/// all of the `LtoModuleTranslation` units returned below and destroyed once
+/// they all go out of scope.
+///
+/// When compiling incrementally, codegen units which weren't retranslated
+/// participate through the bitcode they had last time around. If in addition
+/// they end up importing exactly what they imported last time and their
+/// definitions get the same linkage as last time, their previous post-LTO
+/// object file is still valid and is returned as is.
+fn thin_lto(cgcx: &CodegenContext,
+            diag_handler: &Handler,
+            modules: Vec<ModuleTranslation>,
+            upstream_modules: Vec<(SerializedModule, CString)>,
+            symbol_white_list: &[*const libc::c_char])
+    -> Result<(Vec<LtoModuleTranslation>, Vec<CompiledModule>), FatalError>
+{
+    unsafe {
+        info!("going for that thin, thin LTO");
+
+        let mut serialized_modules = Vec::new();
+        let mut module_names = Vec::new();
+        let mut local_modules = Vec::new();
+
+        // FIXME: right now, like with fat LTO, we serialize all in-memory
+        //        modules before working with them and ThinLTO. We really
+        //        shouldn't do this, however, and instead figure out how to
+        //        extract a summary from an in-memory module and then merge that
+        //        into the global index. It turns out that this loop is by far
+        //        the most expensive portion of this small bit of global
+        //        analysis!
+        for module in modules {
+            info!("local module: {}", module.llmod_id);
+            let pre_lto_bc = cgcx.output_filenames.temp_path_ext(PRE_THIN_LTO_BC_EXT,
+                                                                 Some(&module.name));
+            let serialized = match module.source {
+                ModuleSource::Translated(ref llvm) => {
+                    let buffer = ThinBuffer::new(llvm.llmod);
+                    // Keep the bitcode around so incremental compilation can
+                    // feed it back into ThinLTO without retranslating.
+                    if cgcx.incr_comp_session_dir.is_some() {
+                        if let Err(e) = File::create(&pre_lto_bc)
+                                            .and_then(|mut f| f.write_all(buffer.data())) {
+                            let msg = format!("failed to write {}: {}",
+                                              pre_lto_bc.display(), e);
+                            return Err(diag_handler.fatal(&msg))
+                        }
+                    }
+                    SerializedModule::Thin(buffer)
+                }
+                ModuleSource::Preexisting(_) => {
+                    let mut bc = Vec::new();
+                    if let Err(e) = File::open(&pre_lto_bc)
+                                        .and_then(|mut f| f.read_to_end(&mut bc)) {
+                        let msg = format!("failed to read {}: {}", pre_lto_bc.display(), e);
+                        return Err(diag_handler.fatal(&msg))
+                    }
+                    SerializedModule::FromIncrCache(bc)
+                }
+            };
+            serialized_modules.push(serialized);
+            module_names.push(CString::new(module.llmod_id.clone()).unwrap());
+            local_modules.push(module);
+        }
+
+        // All upstream modules take part in the analysis too, and are code
+        // generated along with ours since the linker won't see their objects.
+        for (module, name) in upstream_modules {
+            info!("foreign module {:?}", name);
+            let module = match module {
+                SerializedModule::FromRlib(bc) => {
+                    SerializedModule::Thin(thin_buffer_from_bitcode(diag_handler, &bc, &name)?)
+                }
+                module => module,
+            };
+            serialized_modules.push(module);
+            module_names.push(name);
+        }
+
+        let thin_modules = serialized_modules.iter().zip(&module_names).map(|(module, name)| {
+            llvm::ThinLTOModule {
+                identifier: name.as_ptr(),
+                data: module.data().as_ptr(),
+                len: module.data().len(),
+            }
+        }).collect::<Vec<_>>();
+
+        // Delegate to the C++ bindings to create some data here. Once this is a
+        // tried-and-true interface we may wish to try to upstream some of this
+        // to LLVM itself, right now we reimplement a lot of what they do
+        // upstream...
+        let data = llvm::LLVMRustCreateThinLTOData(
+            thin_modules.as_ptr(),
+            thin_modules.len() as u32,
+            symbol_white_list.as_ptr(),
+            symbol_white_list.len() as u32,
+        );
+        if data.is_null() {
+            let msg = format!("failed to prepare thin LTO context");
+            return Err(write::llvm_err(&diag_handler, msg))
+        }
+        let data = ThinData(data);
+        info!("thin LTO data created");
+
+        let local_names = local_modules.iter()
+                                       .map(|m| m.llmod_id.clone())
+                                       .collect::<Vec<_>>();
+        let imports = ThinLTOImports::from_thin_lto_data(data.0,
+                                                         &local_names,
+                                                         &module_names,
+                                                         &serialized_modules);
+        let previous_imports = cgcx.incr_comp_session_dir.as_ref().map(|dir| {
+            let path = dir.join(THIN_LTO_IMPORTS_FILE_NAME);
+            // Missing or unreadable import information just means that no
+            // post-LTO artifacts get reused.
+            let previous = ThinLTOImports::load_from_file(&path).unwrap_or_default();
+            if let Err(e) = imports.save_to_file(&path) {
+                let msg = format!("failed to save ThinLTO imports to {}: {}",
+                                  path.display(), e);
+                diag_handler.warn(&msg);
+            }
+            previous
+        });
+
+        let config = cgcx.config(ModuleKind::Regular);
+        let mut reused = Vec::new();
+        let mut names = Vec::new();
+        for module in local_modules {
+            let can_reuse = match (&module.source, &previous_imports) {
+                (&ModuleSource::Preexisting(_), &Some(ref previous)) => {
+                    previous.imports.get(&module.llmod_id) ==
+                        imports.imports.get(&module.llmod_id) &&
+                    previous.linkage.get(&module.llmod_id) ==
+                        imports.linkage.get(&module.llmod_id)
+                }
+                _ => false,
+            };
+            if can_reuse {
+                info!("reusing post-LTO artifacts of {}", module.name);
+                reused.push(module.into_compiled_module(config.emit_obj,
+                                                        config.emit_bc,
+                                                        &cgcx.output_filenames));
+            } else {
+                names.push((module.name, module.symbol_name_hash));
+            }
+        }
+        let local_count = names.len() + reused.len();
+        let reused_names = reused.iter()
+                                 .map(|m| m.llmod_id.clone())
+                                 .collect::<Vec<_>>();
+
+        // Throw everything in an `Arc` as we'll be passing it to many threads
+        // to run optimization passes over.
+        let shared = Arc::new(ThinShared {
+            data,
+            serialized_modules,
+            module_names,
+        });
+
+        let mut local_names = names.into_iter();
+        let mut opt_jobs = Vec::new();
+        for idx in 0..shared.module_names.len() {
+            let identifier = shared.module_names[idx].to_str().unwrap().to_string();
+            let (name, symbol_name_hash, kind) = if idx < local_count {
+                if reused_names.contains(&identifier) {
+                    continue
+                }
+                let (name, symbol_name_hash) = local_names.next().unwrap();
+                (name, symbol_name_hash, ModuleKind::Regular)
+            } else {
+                // Upstream modules have no codegen unit of their own, their
+                // identifier is as good a name as any.
+                (identifier.clone(), 0, ModuleKind::Upstream)
+            };
+            opt_jobs.push(LtoModuleTranslation::Thin(ThinModule {
+                shared: shared.clone(),
+                idx,
+                name,
+                symbol_name_hash,
+                kind,
+            }));
+        }
+
+        Ok((opt_jobs, reused))
+    }
+}
+
+/// Rlibs contain plain bitcode, without the summary ThinLTO needs, so parse it
+/// and serialize it again along with one.
+unsafe fn thin_buffer_from_bitcode(diag_handler: &Handler, bc: &[u8], name: &CStr)
+    -> Result<ThinBuffer, FatalError>
+{
+    let llcx = llvm::LLVMContextCreate();
+    let llmod = llvm::LLVMRustParseBitcodeForThinLTO(llcx, bc.as_ptr(), bc.len(), name.as_ptr());
+    if llmod.is_null() {
+        llvm::LLVMContextDispose(llcx);
+        let msg = format!("failed to parse bitcode of {:?}", name);
+        return Err(write::llvm_err(diag_handler, msg))
+    }
+    let buffer = ThinBuffer::new(llmod);
+    llvm::LLVMContextDispose(llcx);
+    Ok(buffer)
+}
+
+struct ThinShared {
+    data: ThinData,
+    serialized_modules: Vec<SerializedModule>,
+    module_names: Vec<CString>,
+}
+
+struct ThinData(*mut llvm::ThinLTOData);
+
+unsafe impl Send for ThinData {}
+unsafe impl Sync for ThinData {}
+
+impl Drop for ThinData {
+    fn drop(&mut self) {
+        unsafe {
+            llvm::LLVMRustFreeThinLTOData(self.0);
+        }
+    }
+}
+
+pub struct ThinModule {
+    shared: Arc<ThinShared>,
+    idx: usize,
+    name: String,
+    symbol_name_hash: u64,
+    kind: ModuleKind,
+}
+
+impl ThinModule {
+    fn identifier(&self) -> &str {
+        self.shared.module_names[self.idx].to_str().unwrap()
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn cost(&self) -> u64 {
+        // Yes, that's correct, we're using the size of the bytecode as an
+        // indicator for how costly this codegen unit is.
+        self.data().len() as u64
+    }
+
+    fn data(&self) -> &[u8] {
+        self.shared.serialized_modules[self.idx].data()
+    }
+
+    unsafe fn optimize(&mut self, cgcx: &CodegenContext)
+        -> Result<ModuleTranslation, FatalError>
+    {
+        let diag_handler = cgcx.create_diag_handler();
+        let tm = (cgcx.tm_factory)().map_err(|e| {
+            write::llvm_err(&diag_handler, e)
+        })?;
+
+        // Right now the implementation we've got only works over serialized
+        // modules, so we create a fresh new LLVM context and parse the module
+        // into that context. One day, however, we may do this for upstream
+        // crates but for locally translated modules we may be able to reuse
+        // that LLVM Context and Module.
+        let llcx = llvm::LLVMContextCreate();
+        let llmod = llvm::LLVMRustParseBitcodeForThinLTO(
+            llcx,
+            self.data().as_ptr(),
+            self.data().len(),
+            self.shared.module_names[self.idx].as_ptr(),
+        );
+        if llmod.is_null() {
+            let msg = format!("failed to parse bitcode for thin LTO module");
+            return Err(write::llvm_err(&diag_handler, msg));
+        }
+        let mtrans = ModuleTranslation {
+            source: ModuleSource::Translated(ModuleLlvm {
+                llmod,
+                llcx,
+                tm,
+            }),
+            llmod_id: self.identifier().to_string(),
+            name: self.name().to_string(),
+            kind: self.kind,
+            symbol_name_hash: self.symbol_name_hash,
+        };
+        cgcx.save_temp_bitcode(&mtrans, "thin-lto-input");
+
+        // Like with "fat" LTO, get some better optimizations if landing pads
+        // are disabled by removing all landing pads.
+        if cgcx.no_landing_pads {
+            llvm::LLVMRustMarkAllFunctionsNounwind(llmod);
+            cgcx.save_temp_bitcode(&mtrans, "thin-lto-after-nounwind");
+        }
+
+        // Up next comes the per-module local analyses that we do for Thin LTO.
+        // Each of these functions is basically copied from the LLVM
+        // implementation and then tailored to suit this implementation. Ideally
+        // each of these would be supported by upstream LLVM but that's perhaps
+        // a patch for another day!
+        //
+        // You can find some more comments about these functions in the LLVM
+        // bindings we've got (currently `PassWrapper.cpp`)
+        if !llvm::LLVMRustPrepareThinLTORename(self.shared.data.0, llmod) {
+            let msg = format!("failed to prepare thin LTO module");
+            return Err(write::llvm_err(&diag_handler, msg))
+        }
+        cgcx.save_temp_bitcode(&mtrans, "thin-lto-after-rename");
+        if !llvm::LLVMRustPrepareThinLTOResolveWeak(self.shared.data.0, llmod) {
+            let msg = format!("failed to prepare thin LTO module");
+            return Err(write::llvm_err(&diag_handler, msg))
+        }
+        cgcx.save_temp_bitcode(&mtrans, "thin-lto-after-resolve");
+        if !llvm::LLVMRustPrepareThinLTOInternalize(self.shared.data.0, llmod) {
+            let msg = format!("failed to prepare thin LTO module");
+            return Err(write::llvm_err(&diag_handler, msg))
+        }
+        cgcx.save_temp_bitcode(&mtrans, "thin-lto-after-internalize");
+        if !llvm::LLVMRustPrepareThinLTOImport(self.shared.data.0, llmod) {
+            let msg = format!("failed to prepare thin LTO module");
+            return Err(write::llvm_err(&diag_handler, msg))
+        }
+        cgcx.save_temp_bitcode(&mtrans, "thin-lto-after-import");
+
+        // Alright now that we've done everything related to the ThinLTO
+        // analysis it's time to run some optimizations! Here we use the same
+        // `run_pass_manager` as the "fat" LTO above except that we tell it to
+        // populate a thin-specific pass manager, which presumably LLVM treats a
+        // little differently.
+        info!("running thin lto passes over {}", mtrans.name);
+        let config = cgcx.config(mtrans.kind);
+        run_pass_manager(cgcx, tm, llmod, config, true);
+        cgcx.save_temp_bitcode(&mtrans, "thin-lto-after-pm");
+        Ok(mtrans)
+    }
+}
+
+/// The modules each local module imported from during a ThinLTO run, along
+/// with a hash of the bitcode of each of them, and a hash of the linkage
+/// decisions made for each local module's definitions.
+///
+/// Incremental compilation persists this between sessions: a codegen unit
+/// which hasn't changed can only reuse its previous post-LTO object if it
+/// imports the very same code as it did back then, and if ThinLTO exports,
+/// internalizes and resolves its weak definitions the same way. The latter
+/// depends on every other module, including ones in other crates.
+#[derive(Debug, Default)]
+pub struct ThinLTOImports {
+    imports: FxHashMap<String, Vec<(String, u64)>>,
+    linkage: FxHashMap<String, u64>,
+}
+
+impl ThinLTOImports {
+    unsafe fn from_thin_lto_data(data: *const llvm::ThinLTOData,
+                                 local_modules: &[String],
+                                 module_names: &[CString],
+                                 modules: &[SerializedModule]) -> ThinLTOImports {
+        unsafe extern "C" fn imported_module_callback(payload: *mut libc::c_void,
+                                                      importing_module_name: *const libc::c_char,
+                                                      imported_module_name: *const libc::c_char) {
+            let map = &mut *(payload as *mut FxHashMap<String, Vec<String>>);
+            let importing_module_name = CStr::from_ptr(importing_module_name);
+            let imported_module_name = CStr::from_ptr(imported_module_name);
+            map.entry(importing_module_name.to_string_lossy().into_owned())
+               .or_insert(Vec::new())
+               .push(imported_module_name.to_string_lossy().into_owned());
+        }
+
+        let mut raw: FxHashMap<String, Vec<String>> = FxHashMap();
+        llvm::LLVMRustGetThinLTOModuleImports(data,
+                                              imported_module_callback,
+                                              &mut raw as *mut _ as *mut libc::c_void);
+
+        let mut hashes = FxHashMap();
+        let mut imports = FxHashMap();
+        let mut linkage = FxHashMap();
+        for local in local_modules {
+            let local_c = CString::new(&local[..]).unwrap();
+            let key = llvm::build_string(|s| {
+                llvm::LLVMRustGetThinLTOModuleLinkageKey(data, local_c.as_ptr(), s)
+            }).expect("non-UTF8 ThinLTO linkage key");
+            let mut hasher = DefaultHasher::new();
+            hasher.write(key.as_bytes());
+            linkage.insert(local.clone(), hasher.finish());
+
+            let mut list = raw.remove(local).unwrap_or(Vec::new()).into_iter().map(|name| {
+                let hash = *hashes.entry(name.clone()).or_insert_with(|| {
+                    let idx = module_names.iter()
+                                          .position(|n| n.to_bytes() == name.as_bytes())
+                                          .expect("imported from an unknown module");
+                    let mut hasher = DefaultHasher::new();
+                    hasher.write(modules[idx].data());
+                    hasher.finish()
+                });
+                (name, hash)
+            }).collect::<Vec<_>>();
+            list.sort();
+            imports.insert(local.clone(), list);
+        }
+        ThinLTOImports { imports, linkage }
+    }
+
+    /// Saves the imports in a simple line-based format: the linkage hash and
+    /// name of each importing module on a line of its own, followed by one
+    /// tab-indented line per imported module holding its bitcode hash and its
+    /// name.
+    fn save_to_file(&self, path: &Path) -> io::Result<()> {
+        let mut modules = self.imports.iter().collect::<Vec<_>>();
+        modules.sort();
+
+        // The file may be a hard link into the previous session's directory,
+        // so make sure we don't write through it.
+        let tmp = path.with_extension("tmp");
+        {
+            let mut file = io::BufWriter::new(File::create(&tmp)?);
+            for (importing_module, imported_modules) in modules {
+                let linkage = self.linkage[importing_module];
+                writeln!(file, "{:016x} {}", linkage, importing_module)?;
+                for &(ref name, hash) in imported_modules {
+                    writeln!(file, "\t{:016x} {}", hash, name)?;
+                }
+            }
+            file.flush()?;
+        }
+        fs::rename(&tmp, path)
+    }
+
+    fn load_from_file(path: &Path) -> io::Result<ThinLTOImports> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData,
+                                        "malformed ThinLTO import information");
+        let parse_line = |line: &str| -> io::Result<(u64, String)> {
+            let mut parts = line.splitn(2, ' ');
+            let hash = parts.next()
+                            .and_then(|h| u64::from_str_radix(h, 16).ok())
+                            .ok_or_else(&invalid)?;
+            let name = parts.next().ok_or_else(&invalid)?;
+            Ok((hash, name.to_string()))
+        };
+        let mut imports = FxHashMap();
+        let mut linkage = FxHashMap();
+        let mut current_module = None;
+        for line in BufReader::new(File::open(path)?).lines() {
+            let line = line?;
+            if line.starts_with('\t') {
+                let (hash, name) = parse_line(&line[1..])?;
+                let module = current_module.as_ref().ok_or_else(&invalid)?;
+                imports.entry(module.clone())
+                       .or_insert(Vec::new())
+                       .push((name, hash));
+            } else {
+                let (hash, name) = parse_line(&line)?;
+                imports.entry(name.clone()).or_insert(Vec::new());
+                linkage.insert(name.clone(), hash);
+                current_module = Some(name);
+            }
+        }
+        Ok(ThinLTOImports { imports, linkage })
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Support for emitting PNaCl-stable bitcode.
+//!
+//! Targets with `is_like_pnacl` don't go through an external linker. Instead
+//! the whole crate graph is merged into a single module (exactly as `-C lto`
+//! would do), the module is lowered to the portable PNaCl ABI, checked against
+//! the PNaCl ABI verifier and finally written out in the frozen bitcode format
+//! as the `.pexe` itself.
+
+use back::write::{self, CodegenContext};
+use errors::{FatalError, Handler};
+use llvm::{self, ModuleRef};
+use rustc::session::Session;
+use rustc::session::config;
+use rustc::util::common::{time, path2cstr};
+use rustc::util::fs::link_or_copy;
+use {CrateTranslation, ModuleKind};
+
+use std::path::Path;
+
+/// Returns whether this compilation produces a PNaCl-stable module, in which
+/// case the crate is always translated with LTO.
+pub fn emit_stable_bitcode(sess: &Session) -> bool {
+    sess.target.target.options.is_like_pnacl &&
+        sess.crate_types.borrow().iter().all(|&ct| ct == config::CrateTypeExecutable)
+}
+
+/// Runs either half of the PNaCl ABI simplification passes. The "pre-opt"
+/// half expands varargs, aggregate arguments and returns, and exception
+/// handling, while the "post-opt" half legalizes integer types (such as i128)
+/// and strips whatever the optimizer reintroduced that the stable ABI doesn't
+/// allow.
+pub unsafe fn simplify_abi(cgcx: &CodegenContext,
+                           handler: &Handler,
+                           llmod: ModuleRef,
+                           post_opt: bool) -> Result<(), FatalError> {
+    let what = if post_opt { "post-opt" } else { "pre-opt" };
+    time(cgcx.time_passes, &format!("pnacl abi simplification ({})", what), || {
+        if llvm::LLVMRustPNaClSimplifyABI(llmod, post_opt).into_result().is_err() {
+            let msg = format!("failed to run {} PNaCl ABI simplification", what);
+            Err(write::llvm_err(handler, msg))
+        } else {
+            Ok(())
+        }
+    })
+}
+
+/// Checks the module against the PNaCl ABI verifier, reporting everything it
+/// rejects as a single fatal error.
+pub unsafe fn verify(cgcx: &CodegenContext,
+                     handler: &Handler,
+                     llmod: ModuleRef) -> Result<(), FatalError> {
+    let mut ok = true;
+    let errors = time(cgcx.time_passes, "pnacl abi verification", || {
+        llvm::build_string(|s| ok = llvm::LLVMRustPNaClVerifyModule(llmod, s))
+    });
+    if ok {
+        return Ok(())
+    }
+    let errors = errors.unwrap_or_default();
+    Err(handler.fatal(&format!("module does not conform to the PNaCl ABI:\n{}",
+                               errors.trim_right())))
+}
+
+/// Writes the module in the frozen PNaCl bitcode format.
+pub unsafe fn write_bitcode(handler: &Handler,
+                            llmod: ModuleRef,
+                            output: &Path) -> Result<(), FatalError> {
+    let output_c = path2cstr(output);
+    if llvm::LLVMRustPNaClWriteBitcodeToFile(llmod, output_c.as_ptr()).into_result().is_err() {
+        let msg = format!("could not write PNaCl bitcode to {}", output.display());
+        Err(write::llvm_err(handler, msg))
+    } else {
+        Ok(())
+    }
+}
+
+/// "Links" a PNaCl executable. By the time we get here LTO has already merged
+/// everything into a single module which was written out as frozen bitcode,
+/// so all that's left is to put it in place.
+pub fn link(sess: &Session, trans: &CrateTranslation, out_filename: &Path) {
+    if !emit_stable_bitcode(sess) {
+        sess.fatal("PNaCl executables can't be produced alongside other crate types");
+    }
+    let modules = trans.modules.iter()
+        .filter(|m| m.kind == ModuleKind::Regular)
+        .collect::<Vec<_>>();
+    if modules.len() != 1 {
+        sess.fatal(&format!("PNaCl bitcode must be written from a single LTO module, \
+                             but {} modules were produced", modules.len()));
+    }
+    let module = modules[0];
+    if let Err(e) = link_or_copy(&module.object, out_filename) {
+        sess.fatal(&format!("failed to write {}: {}", out_filename.display(), e));
+    }
+}
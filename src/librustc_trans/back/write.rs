@@ -11,12 +11,13 @@
 use back::lto;
 use back::link::{self, get_linker, remove};
 use back::linker::LinkerInfo;
+use back::pnacl;
 use back::symbol_export::ExportedSymbols;
 use rustc_incremental::{save_trans_partition, in_incr_comp_dir};
-use rustc::dep_graph::DepGraph;
+use rustc::dep_graph::{DepGraph, WorkProductFileKind};
 use rustc::middle::cstore::{LinkMeta, EncodedMetadata};
 use rustc::session::config::{self, OutputFilenames, OutputType, OutputTypes, Passes, SomePasses,
-                             AllPasses, Sanitizer};
+                             AllPasses, Sanitizer, Lto};
 use rustc::session::Session;
 use rustc::util::nodemap::FxHashMap;
 use time_graph::{self, TimeGraph};
@@ -302,7 +303,8 @@ impl ModuleConfig {
 pub struct CodegenContext {
     // Resouces needed when running LTO
     pub time_passes: bool,
-    pub lto: bool,
+    pub lto: Lto,
+    pub emit_pnacl_bitcode: bool,
     pub no_landing_pads: bool,
     pub save_temps: bool,
     pub exported_symbols: Arc<ExportedSymbols>,
@@ -340,7 +342,8 @@ impl CodegenContext {
 
     pub fn config(&self, kind: ModuleKind) -> &ModuleConfig {
         match kind {
-            ModuleKind::Regular => &self.regular_module_config,
+            ModuleKind::Regular |
+            ModuleKind::Upstream => &self.regular_module_config,
             ModuleKind::Metadata => &self.metadata_module_config,
             ModuleKind::Allocator => &self.allocator_module_config,
         }
@@ -541,14 +544,16 @@ unsafe fn optimize(cgcx: &CodegenContext,
 
 fn generate_lto_work(cgcx: &CodegenContext,
                      modules: Vec<ModuleTranslation>)
-    -> Vec<(WorkItem, u64)>
+    -> (Vec<(WorkItem, u64)>, Vec<CompiledModule>)
 {
-    let lto_modules = lto::run(cgcx, modules).unwrap_or_else(|e| panic!(e));
+    let (lto_modules, reused_modules) = lto::run(cgcx, modules)
+        .unwrap_or_else(|e| panic!(e));
 
-    lto_modules.into_iter().map(|module| {
+    let work = lto_modules.into_iter().map(|module| {
         let cost = module.cost();
         (WorkItem::LTO(module), cost)
-    }).collect()
+    }).collect();
+    (work, reused_modules)
 }
 
 unsafe fn codegen(cgcx: &CodegenContext,
@@ -591,10 +596,15 @@ unsafe fn codegen(cgcx: &CodegenContext,
     // just llvm bitcode. In that case write bitcode, and possibly
     // delete the bitcode if it wasn't requested. Don't generate the
     // machine code, instead copy the .o file from the .bc
-    let write_bc = config.emit_bc || config.obj_is_bitcode;
-    let rm_bc = !config.emit_bc && config.obj_is_bitcode;
+    //
+    // When producing PNaCl-stable bitcode the object is instead the frozen
+    // form of the (already ABI-simplified) module.
+    let write_pnacl = config.emit_obj && cgcx.emit_pnacl_bitcode;
+    let obj_is_llvm_bitcode = config.obj_is_bitcode && !write_pnacl;
+    let write_bc = config.emit_bc || obj_is_llvm_bitcode;
+    let rm_bc = !config.emit_bc && obj_is_llvm_bitcode;
     let write_obj = config.emit_obj && !config.obj_is_bitcode;
-    let copy_bc_to_obj = config.emit_obj && config.obj_is_bitcode;
+    let copy_bc_to_obj = config.emit_obj && obj_is_llvm_bitcode;
 
     let bc_out = cgcx.output_filenames.temp_path(OutputType::Bitcode, module_name);
     let obj_out = cgcx.output_filenames.temp_path(OutputType::Object, module_name);
@@ -674,6 +684,10 @@ unsafe fn codegen(cgcx: &CodegenContext,
             })?;
         }
 
+        if write_pnacl {
+            pnacl::write_bitcode(diag_handler, llmod, &obj_out)?;
+        }
+
         Ok(())
     })?;
 
@@ -869,16 +883,30 @@ fn copy_module_artifacts_into_incr_comp_cache(sess: &Session,
     }
 
     for module in compiled_modules.modules.iter() {
+        // Upstream crates' modules aren't codegen units of this crate, and
+        // are code generated again by ThinLTO in every session anyway.
+        if module.kind == ModuleKind::Upstream {
+            continue
+        }
+
         let mut files = vec![];
 
         if module.emit_obj {
             let path = crate_output.temp_path(OutputType::Object, Some(&module.name));
-            files.push((OutputType::Object, path));
+            files.push((WorkProductFileKind::Object, path));
         }
 
         if module.emit_bc {
             let path = crate_output.temp_path(OutputType::Bitcode, Some(&module.name));
-            files.push((OutputType::Bitcode, path));
+            files.push((WorkProductFileKind::Bytecode, path));
+        }
+
+        // With ThinLTO the bitcode going into the LTO pass is needed as well,
+        // so a later session can run ThinLTO again without retranslating.
+        let pre_lto_bc = crate_output.temp_path_ext(lto::PRE_THIN_LTO_BC_EXT,
+                                                    Some(&module.name));
+        if sess.lto() == Lto::Thin && pre_lto_bc.exists() {
+            files.push((WorkProductFileKind::PreThinLtoBytecode, pre_lto_bc));
         }
 
         save_trans_partition(sess,
@@ -1064,7 +1092,7 @@ impl WorkItem {
     fn kind(&self) -> ModuleKind {
         match *self {
             WorkItem::Optimize(ref m) => m.kind,
-            WorkItem::LTO(_) => ModuleKind::Regular,
+            WorkItem::LTO(ref m) => m.kind(),
         }
     }
 
@@ -1109,7 +1137,17 @@ fn execute_work_item(cgcx: &CodegenContext, work_item: WorkItem)
                                         .unwrap();
         let name = &mtrans.name;
         for (kind, saved_file) in wp.saved_files {
-            let obj_out = cgcx.output_filenames.temp_path(kind, Some(name));
+            let obj_out = match kind {
+                WorkProductFileKind::Object => {
+                    cgcx.output_filenames.temp_path(OutputType::Object, Some(name))
+                }
+                WorkProductFileKind::Bytecode => {
+                    cgcx.output_filenames.temp_path(OutputType::Bitcode, Some(name))
+                }
+                WorkProductFileKind::PreThinLtoBytecode => {
+                    cgcx.output_filenames.temp_path_ext(lto::PRE_THIN_LTO_BC_EXT, Some(name))
+                }
+            };
             let source_file = in_incr_comp_dir(&incr_comp_session_dir,
                                                &saved_file);
             debug!("copying pre-existing module `{}` from {:?} to {}",
@@ -1126,6 +1164,13 @@ fn execute_work_item(cgcx: &CodegenContext, work_item: WorkItem)
                 }
             }
         }
+
+        // With ThinLTO even unchanged modules take part in LTO, which decides
+        // whether the object we just copied can still be used.
+        if cgcx.lto == Lto::Thin {
+            return Ok(WorkItemResult::NeedsLTO(mtrans))
+        }
+
         let object = cgcx.output_filenames.temp_path(OutputType::Object, Some(name));
 
         Ok(WorkItemResult::Compiled(CompiledModule {
@@ -1143,7 +1188,7 @@ fn execute_work_item(cgcx: &CodegenContext, work_item: WorkItem)
 
         unsafe {
             optimize(cgcx, &diag_handler, &mtrans, config)?;
-            if !cgcx.lto || mtrans.kind == ModuleKind::Metadata {
+            if cgcx.lto == Lto::No || mtrans.kind == ModuleKind::Metadata {
                 let module = codegen(cgcx, &diag_handler, mtrans, config)?;
                 Ok(WorkItemResult::Compiled(module))
             } else {
@@ -1227,10 +1272,13 @@ fn start_executing_work(tcx: TyCtxt,
         each_linked_rlib_for_lto.push((cnum, path.to_path_buf()));
     }));
 
+    let emit_pnacl_bitcode = pnacl::emit_stable_bitcode(sess);
+
     let cgcx = CodegenContext {
         crate_types: sess.crate_types.borrow().clone(),
         each_linked_rlib_for_lto,
-        lto: sess.lto(),
+        lto: if emit_pnacl_bitcode { Lto::Fat } else { sess.lto() },
+        emit_pnacl_bitcode,
         no_landing_pads: sess.no_landing_pads(),
         save_temps: sess.opts.cg.save_temps,
         opts: Arc::new(sess.opts.clone()),
@@ -1473,7 +1521,9 @@ fn start_executing_work(tcx: TyCtxt,
                     assert!(needs_lto.len() > 0);
                     started_lto = true;
                     let modules = mem::replace(&mut needs_lto, Vec::new());
-                    for (work, cost) in generate_lto_work(&cgcx, modules) {
+                    let (work, reused) = generate_lto_work(&cgcx, modules);
+                    compiled_modules.extend(reused);
+                    for (work, cost) in work {
                         let insertion_index = work_items
                             .binary_search_by_key(&cost, |&(_, cost)| cost)
                             .unwrap_or_else(|e| e);
@@ -1613,7 +1663,8 @@ fn start_executing_work(tcx: TyCtxt,
                     free_worker_ids.push(worker_id);
 
                     match compiled_module.kind {
-                        ModuleKind::Regular => {
+                        ModuleKind::Regular |
+                        ModuleKind::Upstream => {
                             compiled_modules.push(compiled_module);
                         }
                         ModuleKind::Metadata => {
@@ -1970,7 +2021,7 @@ pub struct OngoingCrateTranslation {
     trans_worker_receive: Receiver<Message>,
     shared_emitter_main: SharedEmitterMain,
     future: thread::JoinHandle<CompiledModules>,
-    output_filenames: Arc<OutputFilenames>,
+    pub output_filenames: Arc<OutputFilenames>,
 }
 
 impl OngoingCrateTranslation {
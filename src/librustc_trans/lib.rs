@@ -92,6 +92,7 @@ pub mod back {
     pub(crate) mod linker;
     pub mod link;
     mod lto;
+    mod pnacl;
     pub(crate) mod symbol_export;
     pub(crate) mod symbol_names;
     pub mod write;
@@ -214,6 +215,8 @@ pub enum ModuleKind {
     Regular,
     Metadata,
     Allocator,
+    /// A module of an upstream crate, code generated in this crate by ThinLTO.
+    Upstream,
 }
 
 impl ModuleTranslation {
@@ -12,7 +12,7 @@ use syntax_pos::symbol::Symbol;
 use back::write::create_target_machine;
 use llvm;
 use rustc::session::Session;
-use rustc::session::config::PrintRequest;
+use rustc::session::config::{PrintRequest, Lto};
 use libc::{c_int, c_char};
 use std::ffi::CString;
 
@@ -37,6 +37,16 @@ pub fn init(sess: &Session) {
         if POISONED.load(Ordering::SeqCst) {
             bug!("couldn't enable multi-threaded LLVM");
         }
+
+        if sess.lto() == Lto::Thin && !llvm::LLVMRustThinLTOAvailable() {
+            sess.fatal("ThinLTO is not available with the LLVM rustc was built against");
+        }
+
+        if sess.target.target.options.is_like_pnacl && !llvm::LLVMRustPNaClAvailable() {
+            sess.fatal(&format!("the `{}` target requires an LLVM with PNaCl support, \
+                                 which the LLVM rustc was built against lacks",
+                                sess.opts.target_triple));
+        }
     }
 }
 
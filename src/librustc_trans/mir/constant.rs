@@ -16,6 +16,7 @@ use rustc::hir::def_id::DefId;
 use rustc::infer::TransNormalize;
 use rustc::mir;
 use rustc::mir::tcx::LvalueTy;
+use rustc::traits::Reveal;
 use rustc::ty::{self, Ty, TyCtxt, TypeFoldable};
 use rustc::ty::layout::{self, LayoutTyper};
 use rustc::ty::cast::{CastTy, IntTy};
@@ -256,6 +257,29 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
         context
     }
 
+    /// Translates a use of the constant `def_id`. Constants are evaluated
+    /// from their unoptimized MIR by `const_eval`, which unlike this
+    /// evaluator supports control flow in the `const fn`s they call, so its
+    /// value is used when it is a scalar or string.
+    fn trans_unevaluated(ccx: &'a CrateContext<'a, 'tcx>,
+                         def_id: DefId,
+                         substs: &'tcx Substs<'tcx>,
+                         ty: Ty<'tcx>)
+                         -> Result<Const<'tcx>, ConstEvalErr<'tcx>> {
+        let param_env = ty::ParamEnv::empty(Reveal::All);
+        if let Ok(value) = ccx.tcx().const_eval(param_env.and((def_id, substs))) {
+            match value.val {
+                ConstVal::Integral(_) | ConstVal::Float(_) | ConstVal::Bool(_) |
+                ConstVal::Char(_) | ConstVal::Str(_) | ConstVal::Function(..) => {
+                    return Ok(Const::from_constval(ccx, &value.val, ty));
+                }
+                _ => {}
+            }
+        }
+        // Errors are reported by translating the constant's MIR.
+        MirConstContext::trans_def(ccx, def_id, substs, IndexVec::new())
+    }
+
     fn trans_def(ccx: &'a CrateContext<'a, 'tcx>,
                  def_id: DefId,
                  substs: &'tcx Substs<'tcx>,
@@ -517,7 +541,7 @@ impl<'a, 'tcx> MirConstContext<'a, 'tcx> {
                     mir::Literal::Value { value } => {
                         if let ConstVal::Unevaluated(def_id, substs) = value.val {
                             let substs = self.monomorphize(&substs);
-                            MirConstContext::trans_def(self.ccx, def_id, substs, IndexVec::new())
+                            MirConstContext::trans_unevaluated(self.ccx, def_id, substs, ty)
                         } else {
                             Ok(Const::from_constval(self.ccx, &value.val, ty))
                         }
@@ -968,7 +992,7 @@ impl<'a, 'tcx> MirContext<'a, 'tcx> {
             mir::Literal::Value { value } => {
                 if let ConstVal::Unevaluated(def_id, substs) = value.val {
                     let substs = self.monomorphize(&substs);
-                    MirConstContext::trans_def(bcx.ccx, def_id, substs, IndexVec::new())
+                    MirConstContext::trans_unevaluated(bcx.ccx, def_id, substs, ty)
                 } else {
                     Ok(Const::from_constval(bcx.ccx, &value.val, ty))
                 }
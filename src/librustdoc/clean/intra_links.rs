@@ -0,0 +1,233 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Resolution of intra-doc links.
+//!
+//! Links such as `[Foo]`, ``[`bar::Baz`]`` or `[text](Foo::method)` are
+//! resolved as paths in the scope of the module containing the documented
+//! item, by the same resolver which resolved the crate's own paths. Resolved
+//! links are recorded in `Attributes::links` and turned into URLs once
+//! rendering knows where each item ends up; those that can't be resolved are
+//! left alone and reported through the `intra_doc_link_resolution_failure`
+//! lint, unless they're a shortcut link which might just as well be square
+//! brackets in prose.
+//!
+//! The last segment of a path is looked up in the type namespace, then in
+//! the value namespace, unless it ends in `()`, which restricts it to the
+//! value namespace. The resolver doesn't know about associated items of
+//! types, so a path which doesn't resolve as a whole is tried again as a
+//! type or trait followed by one of its associated items, variants or
+//! fields. Macros, generic parameters, `Self` and primitive types are never
+//! found, and links are only resolved for local items: the documentation of
+//! items inlined from other crates keeps its links as they were written.
+
+use rustc::hir::def::Def;
+use rustc::hir::def_id::DefId;
+use rustc::lint::builtin::INTRA_DOC_LINK_RESOLUTION_FAILURE;
+use rustc::ty;
+use syntax::ast;
+use syntax_pos::DUMMY_SP;
+
+use core::DocContext;
+use html::markdown::markdown_links;
+
+use super::{Attributes, Item, ItemEnum, VariantKind};
+
+/// The namespaces names are looked up in; macros aren't supported.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Namespace {
+    Type,
+    Value,
+}
+
+/// Resolves the links in the documentation of `item` and of everything it
+/// contains, except modules, which take care of their own.
+pub fn resolve_item(cx: &DocContext, scope: DefId, item: &mut Item) {
+    if let ItemEnum::ModuleItem(..) = item.inner {
+        return;
+    }
+    let node_id = match cx.tcx.hir.as_local_node_id(item.def_id) {
+        Some(node_id) => node_id,
+        None => return,
+    };
+    resolve(cx, scope, node_id, &mut item.attrs);
+
+    let children = match item.inner {
+        ItemEnum::StructItem(ref mut s) => &mut s.fields,
+        ItemEnum::UnionItem(ref mut u) => &mut u.fields,
+        ItemEnum::EnumItem(ref mut e) => &mut e.variants,
+        ItemEnum::TraitItem(ref mut t) => &mut t.items,
+        ItemEnum::ImplItem(ref mut i) => &mut i.items,
+        ItemEnum::VariantItem(ref mut v) => match v.kind {
+            VariantKind::Struct(ref mut s) => &mut s.fields,
+            _ => return,
+        },
+        _ => return,
+    };
+    for child in children {
+        resolve_item(cx, scope, child);
+    }
+}
+
+/// Resolves the links in `attrs`, the attributes of the item `node_id`, in
+/// the module `scope`.
+pub fn resolve(cx: &DocContext, scope: DefId, node_id: ast::NodeId, attrs: &mut Attributes) {
+    let scope = match cx.tcx.hir.as_local_node_id(scope) {
+        Some(scope) => scope,
+        None => return,
+    };
+    let doc = attrs.doc_strings.join("\n");
+    for (link, explicit) in markdown_links(&doc) {
+        if attrs.links.iter().any(|&(ref l, ..)| *l == link) {
+            continue;
+        }
+        let (path, namespaces) = match link_path(&link) {
+            Some((path, namespaces)) => (path.to_owned(), namespaces),
+            None => continue,
+        };
+        let resolved = namespaces.iter().filter_map(|&ns| resolve_path(cx, scope, &path, ns))
+                                 .next();
+        match resolved {
+            Some((def_id, fragment)) => attrs.links.push((link, def_id, fragment)),
+            None if explicit || path != link || path.contains("::") => {
+                let sp = attrs.span.unwrap_or(DUMMY_SP);
+                let msg = format!("`[{}]` cannot be resolved, ignoring it", link);
+                cx.tcx.struct_span_lint_node(INTRA_DOC_LINK_RESOLUTION_FAILURE, node_id, sp, &msg)
+                    .help("to escape `[` and `]` characters, add '\\' before them like \
+                           `\\[` or `\\]`")
+                    .emit();
+            }
+            None => {}
+        }
+    }
+}
+
+/// Returns the path a link target refers to, if it looks like one, along
+/// with the namespaces to look it up in: the target, without surrounding
+/// backticks or a trailing `()`, must be a sequence of identifiers separated
+/// by `::`. A trailing `()` marks a function or method.
+fn link_path(link: &str) -> Option<(&str, &'static [Namespace])> {
+    let mut path = link.trim();
+    if path.len() > 2 && path.starts_with('`') && path.ends_with('`') {
+        path = &path[1..path.len() - 1];
+    }
+    let mut namespaces: &'static [Namespace] = &[Namespace::Type, Namespace::Value];
+    if path.ends_with("()") {
+        path = &path[..path.len() - 2];
+        namespaces = &[Namespace::Value];
+    }
+    let segments = if path.starts_with("::") { &path[2..] } else { path };
+    let is_ident = |s: &str| {
+        s.chars().next().map_or(false, |c| c.is_alphabetic() || c == '_') &&
+            s.chars().all(|c| c.is_alphanumeric() || c == '_')
+    };
+    if segments.split("::").all(is_ident) {
+        Some((path, namespaces))
+    } else {
+        None
+    }
+}
+
+/// Resolves `path` in the module `scope`, with its last segment in the
+/// namespace `ns`, returning the item it refers to. For associated items,
+/// enum variants and fields, that's the item containing them, along with the
+/// anchor of the page it's documented on.
+fn resolve_path(cx: &DocContext, scope: ast::NodeId, path: &str, ns: Namespace)
+                -> Option<(DefId, Option<String>)> {
+    let tcx = cx.tcx;
+    let path = if path.starts_with("crate::") { &path["crate".len()..] } else { path };
+    let resolve = |path: &str, ns: Namespace| {
+        cx.resolver.borrow_mut().with_scope(scope, |resolver| {
+            resolver.resolve_str_path_error(DUMMY_SP, path, ns == Namespace::Value)
+        }).ok().map(|path| path.def)
+    };
+
+    if let Some(def) = resolve(path, ns) {
+        return match def {
+            // Constructors are documented with the type they construct.
+            Def::StructCtor(did, _) => tcx.parent_def_id(did).map(|did| (did, None)),
+            Def::Variant(did) | Def::VariantCtor(did, _) => {
+                let name = tcx.item_name(did);
+                tcx.parent_def_id(did).map(|did| (did, Some(format!("variant.{}", name))))
+            }
+            // Associated items of traits are documented with the trait.
+            Def::Method(did) | Def::AssociatedConst(did) | Def::AssociatedTy(did) => {
+                let item = tcx.associated_item(did);
+                match item.container {
+                    ty::TraitContainer(trait_did) => {
+                        Some((trait_did, Some(assoc_anchor(item, true))))
+                    }
+                    ty::ImplContainer(_) => None,
+                }
+            }
+            Def::PrimTy(..) | Def::SelfTy(..) | Def::TyParam(..) | Def::Local(..) |
+            Def::Upvar(..) | Def::Label(..) | Def::Macro(..) | Def::Err => None,
+            def => Some((def.def_id(), None)),
+        };
+    }
+
+    // Associated items, variants and fields are documented on the page of
+    // the type or trait, whatever their namespace.
+    let mut split = path.rsplitn(2, "::");
+    let (name, parent) = match (split.next(), split.next()) {
+        (Some(name), Some(parent)) => (name, parent),
+        _ => return None,
+    };
+    let parent = match resolve(parent, Namespace::Type) {
+        Some(parent) => parent,
+        None => return None,
+    };
+    member(cx, parent, name).map(|fragment| (parent.def_id(), Some(fragment)))
+}
+
+/// Returns the anchor of the associated item `item` on the page of the type
+/// or trait it belongs to.
+fn assoc_anchor(item: ty::AssociatedItem, in_trait: bool) -> String {
+    let kind = match item.kind {
+        ty::AssociatedKind::Const => "associatedconstant",
+        ty::AssociatedKind::Type => "associatedtype",
+        ty::AssociatedKind::Method if in_trait && !item.defaultness.has_value() => "tymethod",
+        ty::AssociatedKind::Method => "method",
+    };
+    format!("{}.{}", kind, item.name)
+}
+
+/// Finds the associated item, variant or field `name` of the item `def`,
+/// returning its anchor on the page of that item.
+fn member(cx: &DocContext, def: Def, name: &str) -> Option<String> {
+    let tcx = cx.tcx;
+    match def {
+        Def::Trait(did) => {
+            tcx.associated_items(did)
+               .find(|item| item.name == name)
+               .map(|item| assoc_anchor(item, true))
+        }
+        Def::Enum(did) if tcx.adt_def(did).variants.iter().any(|v| v.name == name) => {
+            Some(format!("variant.{}", name))
+        }
+        Def::Struct(did) | Def::Union(did) | Def::Enum(did) | Def::TyAlias(did) => {
+            let inherent = tcx.inherent_impls(did).iter()
+                .filter_map(|&impl_did| tcx.associated_items(impl_did).find(|i| i.name == name))
+                .next()
+                .map(|item| assoc_anchor(item, false));
+            if inherent.is_some() {
+                return inherent;
+            }
+            match def {
+                Def::Struct(did) | Def::Union(did)
+                    if tcx.adt_def(did).struct_variant().fields.iter().any(|f| f.name == name) => {
+                    Some(format!("structfield.{}", name))
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -56,6 +56,7 @@ use html::item_type::ItemType;
 
 pub mod inline;
 pub mod cfg;
+mod intra_links;
 mod simplify;
 
 use self::cfg::Cfg;
@@ -123,7 +124,7 @@ pub struct Crate {
     pub masked_crates: FxHashSet<CrateNum>,
 }
 
-impl<'a, 'tcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx> {
+impl<'a, 'tcx, 'rcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx, 'rcx> {
     fn clean(&self, cx: &DocContext) -> Crate {
         use ::visit_lib::LibEmbargoVisitor;
 
@@ -456,6 +457,7 @@ impl Clean<Item> for doctree::Module {
             "".to_string()
         };
 
+        let def_id = cx.tcx.hir.local_def_id(self.id);
         let mut items: Vec<Item> = vec![];
         items.extend(self.extern_crates.iter().map(|x| x.clean(cx)));
         items.extend(self.imports.iter().flat_map(|x| x.clean(cx)));
@@ -473,6 +475,14 @@ impl Clean<Item> for doctree::Module {
         items.extend(self.macros.iter().map(|x| x.clean(cx)));
         items.extend(self.def_traits.iter().map(|x| x.clean(cx)));
 
+        // Links in the documentation of the items of this module (but not of
+        // its submodules) are resolved in its scope, just like its own.
+        for item in &mut items {
+            intra_links::resolve_item(cx, def_id, item);
+        }
+        let mut attrs = self.attrs.clean(cx);
+        intra_links::resolve(cx, def_id, self.id, &mut attrs);
+
         // determine if we should display the inner contents or
         // the outer `mod` item for the source code.
         let whence = {
@@ -490,12 +500,12 @@ impl Clean<Item> for doctree::Module {
 
         Item {
             name: Some(name),
-            attrs: self.attrs.clean(cx),
+            attrs,
             source: whence.clean(cx),
             visibility: self.vis.clean(cx),
             stability: self.stab.clean(cx),
             deprecation: self.depr.clean(cx),
-            def_id: cx.tcx.hir.local_def_id(self.id),
+            def_id,
             inner: ModuleItem(Module {
                is_crate: self.is_crate,
                items,
@@ -565,6 +575,9 @@ pub struct Attributes {
     pub other_attrs: Vec<ast::Attribute>,
     pub cfg: Option<Rc<Cfg>>,
     pub span: Option<syntax_pos::Span>,
+    /// Intra-doc links: the link targets as written in the documentation,
+    /// with the item they resolved to and the anchor within its page, if any.
+    pub links: Vec<(String, DefId, Option<String>)>,
 }
 
 impl Attributes {
@@ -639,6 +652,7 @@ impl Attributes {
             other_attrs,
             cfg: if cfg == Cfg::True { None } else { Some(Rc::new(cfg)) },
             span: sp,
+            links: vec![],
         }
     }
 
@@ -12,16 +12,17 @@ use rustc_lint;
 use rustc_driver::{driver, target_features, abort_on_err};
 use rustc_driver::pretty::ReplaceBodyWithLoop;
 use rustc::session::{self, config};
-use rustc::hir::def_id::DefId;
+use rustc::hir::def_id::{DefId, LOCAL_CRATE};
 use rustc::hir::def::Def;
 use rustc::middle::privacy::AccessLevels;
-use rustc::ty::{self, TyCtxt, GlobalArenas};
+use rustc::ty::{self, TyCtxt, GlobalArenas, Resolutions};
 use rustc::hir::map as hir_map;
 use rustc::lint;
 use rustc::util::nodemap::FxHashMap;
 use rustc_trans;
 use rustc_trans::back::link;
 use rustc_resolve as resolve;
+use rustc_metadata::creader::CrateLoader;
 use rustc_metadata::cstore::CStore;
 
 use syntax::codemap;
@@ -36,6 +37,8 @@ use std::rc::Rc;
 use std::path::PathBuf;
 
 use visit_ast::RustdocVisitor;
+use lints;
+use source_links;
 use clean;
 use clean::Clean;
 use html::render::RenderInfo;
@@ -46,8 +49,10 @@ pub use rustc::session::search_paths::SearchPaths;
 
 pub type ExternalPaths = FxHashMap<DefId, (Vec<String>, clean::TypeKind)>;
 
-pub struct DocContext<'a, 'tcx: 'a> {
+pub struct DocContext<'a, 'tcx: 'a, 'rcx: 'a> {
     pub tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    /// Kept around after expansion to resolve the paths of intra-doc links
+    pub resolver: &'a RefCell<resolve::Resolver<'rcx>>,
     pub populated_all_crate_impls: Cell<bool>,
     // Note that external items for which `doc(hidden)` applies to are shown as
     // non-reachable while local items aren't. This is because we're reusing
@@ -68,7 +73,7 @@ pub struct DocContext<'a, 'tcx: 'a> {
     pub lt_substs: RefCell<FxHashMap<DefId, clean::Lifetime>>,
 }
 
-impl<'a, 'tcx> DocContext<'a, 'tcx> {
+impl<'a, 'tcx, 'rcx> DocContext<'a, 'tcx, 'rcx> {
     pub fn sess(&self) -> &session::Session {
         &self.tcx.sess
     }
@@ -108,7 +113,8 @@ pub fn run_core(search_paths: SearchPaths,
                 triple: Option<String>,
                 maybe_sysroot: Option<PathBuf>,
                 allow_warnings: bool,
-                force_unstable_if_unmarked: bool) -> (clean::Crate, RenderInfo)
+                force_unstable_if_unmarked: bool,
+                generate_link_to_definition: bool) -> (clean::Crate, RenderInfo)
 {
     // Parse, resolve, and typecheck the given crate.
 
@@ -119,12 +125,15 @@ pub fn run_core(search_paths: SearchPaths,
 
     let warning_lint = lint::builtin::WARNINGS.name_lower();
 
+    // The compiler's lints are silenced, but rustdoc's own lints are reported
+    // at whatever level they're set to.
     let sessopts = config::Options {
         maybe_sysroot,
         search_paths,
         crate_types: vec![config::CrateTypeRlib],
         lint_opts: if !allow_warnings { vec![(warning_lint, lint::Allow)] } else { vec![] },
         lint_cap: Some(lint::Allow),
+        uncapped_lints: lints::DOC_LINTS.iter().map(|lint| lint.name_lower()).collect(),
         externs,
         target_triple: triple.unwrap_or(config::host_triple().to_string()),
         // Ensure that rustdoc works even if rustc is feature-staged
@@ -157,21 +166,55 @@ pub fn run_core(search_paths: SearchPaths,
     let krate = panictry!(driver::phase_1_parse_input(&driver::CompileController::basic(),
                                                       &sess,
                                                       &input));
-    let krate = ReplaceBodyWithLoop::new().fold_crate(krate);
+    // Linking the source to the definitions needs the function bodies, so
+    // they're only type checked then.
+    let krate = if generate_link_to_definition {
+        krate
+    } else {
+        ReplaceBodyWithLoop::new().fold_crate(krate)
+    };
 
     let name = link::find_crate_name(Some(&sess), &krate.attrs, &input);
 
-    let driver::ExpansionResult { defs, analysis, resolutions, mut hir_forest, .. } = {
-        let result = driver::phase_2_configure_and_expand(&sess,
-                                                          &cstore,
-                                                          krate,
-                                                          None,
-                                                          &name,
-                                                          None,
-                                                          resolve::MakeGlobMap::No,
-                                                          |_| Ok(()));
-        abort_on_err(result, &sess)
+    let make_glob_map = if generate_link_to_definition {
+        resolve::MakeGlobMap::Yes
+    } else {
+        resolve::MakeGlobMap::No
+    };
+    // The resolver outlives expansion so that intra-doc links can be
+    // resolved against the crate's own scopes later on.
+    let mut crate_loader = CrateLoader::new(&sess, &cstore, &name);
+    let resolver_arenas = resolve::Resolver::arenas();
+    let result = driver::phase_2_configure_and_expand_inner(&sess,
+                                                            &cstore,
+                                                            krate,
+                                                            None,
+                                                            &name,
+                                                            None,
+                                                            make_glob_map,
+                                                            &resolver_arenas,
+                                                            &mut crate_loader,
+                                                            |_| Ok(()));
+    let driver::InnerExpansionResult {
+        expanded_crate, resolver, mut hir_forest
+    } = abort_on_err(result, &sess);
+
+    // The analysis passes take the resolver's tables by value, so hand them
+    // copies and keep the resolver itself.
+    let defs = resolver.definitions.clone();
+    let analysis = ty::CrateAnalysis {
+        access_levels: Rc::new(AccessLevels::default()),
+        name: name.to_string(),
+        glob_map: if resolver.make_glob_map { Some(resolver.glob_map.clone()) } else { None },
+    };
+    let resolutions = Resolutions {
+        freevars: resolver.freevars.clone(),
+        export_map: resolver.export_map.clone(),
+        trait_map: resolver.trait_map.clone(),
+        maybe_unused_trait_imports: resolver.maybe_unused_trait_imports.clone(),
+        maybe_unused_extern_crates: resolver.maybe_unused_extern_crates.clone(),
     };
+    let resolver = RefCell::new(resolver);
 
     let arena = DroplessArena::new();
     let arenas = GlobalArenas::new();
@@ -196,6 +239,12 @@ pub fn run_core(search_paths: SearchPaths,
             sess.fatal("Compilation failed, aborting rustdoc");
         }
 
+        let source_links = if generate_link_to_definition {
+            source_links::collect(tcx, &expanded_crate, &analysis, &name)
+        } else {
+            FxHashMap()
+        };
+
         let ty::CrateAnalysis { access_levels, .. } = analysis;
 
         // Convert from a NodeId set to a DefId set since we don't always have easy access
@@ -208,6 +257,7 @@ pub fn run_core(search_paths: SearchPaths,
 
         let ctxt = DocContext {
             tcx,
+            resolver: &resolver,
             populated_all_crate_impls: Cell::new(false),
             access_levels: RefCell::new(access_levels),
             external_traits: Default::default(),
@@ -217,12 +267,22 @@ pub fn run_core(search_paths: SearchPaths,
         };
         debug!("crate: {:?}", tcx.hir.krate());
 
+        lints::check_crate(&ctxt);
+        tcx.sess.abort_if_errors();
+
         let krate = {
             let mut v = RustdocVisitor::new(&*cstore, &ctxt);
             v.visit(tcx.hir.krate());
             v.clean(&ctxt)
         };
+        // Intra-doc links are resolved, and their lint reported, while cleaning.
+        tcx.sess.abort_if_errors();
+        source_links::record_external_paths(&ctxt, &source_links);
 
-        (krate, ctxt.renderinfo.into_inner())
+        let mut renderinfo = ctxt.renderinfo.into_inner();
+        renderinfo.source_links = source_links;
+        tcx.hir.definitions().def_path_table()
+           .add_def_path_hashes_to(LOCAL_CRATE, &mut renderinfo.def_path_hashes);
+        (krate, renderinfo)
     }), &sess)
 }
@@ -0,0 +1,186 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Documentation coverage reports, for `--show-coverage`
+//!
+//! This counts the items left in the cleaned crate after the passes have run,
+//! so by default only public items, and how many of them are documented and
+//! have a code example in their documentation. Like the `missing_docs` lint,
+//! items of trait implementations aren't counted since they're documented by
+//! the trait, and neither are imports or the implementations themselves.
+
+use std::cmp;
+use std::ops;
+
+use serialize::json;
+
+use clean;
+use html::markdown;
+
+#[derive(Copy, Clone, Default, RustcEncodable)]
+struct ItemCount {
+    total: u64,
+    with_docs: u64,
+    with_examples: u64,
+}
+
+impl ItemCount {
+    fn count_item(&mut self, item: &clean::Item) {
+        self.total += 1;
+        if let Some(doc) = item.doc_value() {
+            if !doc.trim().is_empty() {
+                self.with_docs += 1;
+            }
+            if markdown::has_code_example(doc) {
+                self.with_examples += 1;
+            }
+        }
+    }
+}
+
+impl ops::AddAssign for ItemCount {
+    fn add_assign(&mut self, rhs: ItemCount) {
+        self.total += rhs.total;
+        self.with_docs += rhs.with_docs;
+        self.with_examples += rhs.with_examples;
+    }
+}
+
+#[derive(RustcEncodable)]
+struct ModuleCoverage {
+    path: String,
+    counts: ItemCount,
+}
+
+#[derive(RustcEncodable)]
+struct CoverageReport {
+    name: String,
+    modules: Vec<ModuleCoverage>,
+    total: ItemCount,
+}
+
+/// Prints the coverage report for `krate` to stdout, as a table or, if `json`
+/// is set, as a single JSON object.
+pub fn run(krate: &clean::Crate, json: bool) {
+    let mut report = CoverageReport {
+        name: krate.name.clone(),
+        modules: Vec::new(),
+        total: ItemCount::default(),
+    };
+    if let Some(ref root) = krate.module {
+        // The crate root has no parent to be counted in.
+        let mut counts = ItemCount::default();
+        counts.count_item(root);
+        collect(root, krate.name.clone(), counts, &mut report.modules);
+    }
+    for module in &report.modules {
+        report.total += module.counts;
+    }
+
+    if json {
+        println!("{}", json::as_json(&report));
+    } else {
+        print_table(&report);
+    }
+}
+
+/// Counts the items of `module`, adding them to `counts`, then recurses into
+/// its submodules. Modules are listed before their submodules.
+fn collect(module: &clean::Item, path: String, mut counts: ItemCount,
+           modules: &mut Vec<ModuleCoverage>) {
+    let items = match module.inner {
+        clean::ModuleItem(ref m) => &m.items,
+        _ => return,
+    };
+    let index = modules.len();
+    modules.push(ModuleCoverage { path: path.clone(), counts: ItemCount::default() });
+
+    for item in items {
+        match item.inner {
+            clean::StrippedItem(..) | clean::ImportItem(..) | clean::ExternCrateItem(..) => {}
+            clean::ImplItem(ref i) => {
+                if i.trait_.is_none() {
+                    count_items(&i.items, &mut counts);
+                }
+            }
+            clean::ModuleItem(..) => {
+                counts.count_item(item);
+                let name = item.name.as_ref().map(|s| &s[..]).unwrap_or("");
+                collect(item, format!("{}::{}", path, name), ItemCount::default(), modules);
+            }
+            _ => {
+                counts.count_item(item);
+                count_members(item, &mut counts);
+            }
+        }
+    }
+    modules[index].counts = counts;
+}
+
+/// Counts the fields, variants and trait items of `item`.
+fn count_members(item: &clean::Item, counts: &mut ItemCount) {
+    match item.inner {
+        clean::StructItem(ref s) => count_items(&s.fields, counts),
+        clean::UnionItem(ref u) => count_items(&u.fields, counts),
+        clean::TraitItem(ref t) => count_items(&t.items, counts),
+        clean::EnumItem(ref e) => {
+            for variant in &e.variants {
+                if variant.is_stripped() {
+                    continue;
+                }
+                counts.count_item(variant);
+                if let clean::VariantItem(clean::Variant {
+                    kind: clean::VariantKind::Struct(ref s)
+                }) = variant.inner {
+                    count_items(&s.fields, counts);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn count_items(items: &[clean::Item], counts: &mut ItemCount) {
+    for item in items.iter().filter(|i| !i.is_stripped()) {
+        counts.count_item(item);
+    }
+}
+
+fn print_table(report: &CoverageReport) {
+    fn percentage(n: u64, total: u64) -> String {
+        if total == 0 {
+            "-".to_owned()
+        } else {
+            format!("{:.1}%", n as f64 * 100.0 / total as f64)
+        }
+    }
+
+    fn print_row(width: usize, name: &str, counts: &ItemCount) {
+        println!("| {:<width$} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} |",
+                 name, counts.total,
+                 counts.with_docs, percentage(counts.with_docs, counts.total),
+                 counts.with_examples, percentage(counts.with_examples, counts.total),
+                 width = width);
+    }
+
+    let width = report.modules.iter().map(|m| m.path.len()).fold(6, cmp::max);
+    let separator = format!("+-{}-+{}", "-".repeat(width), "------------+".repeat(5));
+    println!("{}", separator);
+    println!("| {:<width$} | {:>10} | {:>10} | {:>10} | {:>10} | {:>10} |",
+             "Module", "Items", "Documented", "Percentage", "Examples", "Percentage",
+             width = width);
+    println!("{}", separator);
+    for module in &report.modules {
+        print_row(width, &module.path, &module.counts);
+    }
+    println!("{}", separator);
+    print_row(width, "Total", &report.total);
+    println!("{}", separator);
+}
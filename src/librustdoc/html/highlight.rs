@@ -26,11 +26,12 @@ use std::fmt::Display;
 use std::io;
 use std::io::prelude::*;
 
+use rustc::util::nodemap::FxHashMap;
 use syntax::codemap::{CodeMap, FilePathMapping};
 use syntax::parse::lexer::{self, TokenAndSpan};
 use syntax::parse::token;
 use syntax::parse;
-use syntax_pos::Span;
+use syntax_pos::{BytePos, Span};
 
 /// Highlights `src`, returning the HTML output.
 pub fn render_with_highlighting(src: &str, class: Option<&str>, id: Option<&str>,
@@ -60,6 +61,25 @@ pub fn render_with_highlighting(src: &str, class: Option<&str>, id: Option<&str>
     String::from_utf8_lossy(&out[..]).into_owned()
 }
 
+/// Highlights the source file `src` like `render_with_highlighting`, making the
+/// tokens starting at the byte offsets in `links` links to the given URLs.
+pub fn render_with_links(src: &str, links: &FxHashMap<usize, String>) -> String {
+    let sess = parse::ParseSess::new(FilePathMapping::empty());
+    let fm = sess.codemap().new_filemap("<stdin>".to_string(), src.to_string());
+    let start = fm.start_pos;
+
+    let mut out = Vec::new();
+    write_header(None, None, &mut out).unwrap();
+
+    let mut classifier = Classifier::new(lexer::StringReader::new(&sess, fm), sess.codemap());
+    if let Err(_) = classifier.write_source(&mut LinkWriter { out: &mut out, links, start }) {
+        return format!("<pre>{}</pre>", src);
+    }
+
+    write_footer(&mut out).unwrap();
+    String::from_utf8_lossy(&out[..]).into_owned()
+}
+
 /// Highlights `src`, returning the HTML output. Returns only the inner html to
 /// be inserted into an element. C.f., `render_with_highlighting` which includes
 /// an enclosing `<pre>` block.
@@ -167,6 +187,41 @@ impl<U: Write> Writer for U {
     }
 }
 
+/// Writes like the default `Writer`, wrapping the tokens that start at the
+/// offsets in `links` in links.
+struct LinkWriter<'a> {
+    out: &'a mut Vec<u8>,
+    links: &'a FxHashMap<usize, String>,
+    /// The position of the start of the source in the codemap.
+    start: BytePos,
+}
+
+impl<'a> Writer for LinkWriter<'a> {
+    fn string<T: Display>(&mut self,
+                          text: T,
+                          klass: Class,
+                          tas: Option<&TokenAndSpan>)
+                          -> io::Result<()> {
+        let href = tas.and_then(|tas| self.links.get(&((tas.sp.lo() - self.start).0 as usize)));
+        match href {
+            Some(href) => {
+                write!(self.out, "<a href=\"{}\">", Escape(href))?;
+                self.out.string(text, klass, tas)?;
+                write!(self.out, "</a>")
+            }
+            None => self.out.string(text, klass, tas),
+        }
+    }
+
+    fn enter_span(&mut self, klass: Class) -> io::Result<()> {
+        self.out.enter_span(klass)
+    }
+
+    fn exit_span(&mut self) -> io::Result<()> {
+        self.out.exit_span()
+    }
+}
+
 impl<'a> Classifier<'a> {
     pub fn new(lexer: lexer::StringReader<'a>, codemap: &'a CodeMap) -> Classifier<'a> {
         Classifier {
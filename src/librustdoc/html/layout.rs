@@ -125,7 +125,16 @@ r##"<!DOCTYPE html>
 
                 <p>
                     Search functions by type signature (e.g.
-                    <code>vec -> usize</code> or <code>* -> vec</code>)
+                    <code>vec -> usize</code> or <code>* -> vec</code>),
+                    optionally with generic arguments (e.g.
+                    <code>vec&lt;t&gt; -> option&lt;t&gt;</code>)
+                </p>
+
+                <p>
+                    Search for the types implementing a trait, in every crate
+                    documented alongside this one, with <code>impl:</code>
+                    (e.g. <code>impl:display</code> or
+                    <code>impl:std::fmt::display</code>).
                 </p>
             </div>
         </div>
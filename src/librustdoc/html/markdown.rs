@@ -21,7 +21,7 @@
 //! use rustdoc::html::markdown::{RenderType, Markdown};
 //!
 //! let s = "My *markdown* _text_";
-//! let html = format!("{}", Markdown(s, RenderType::Pulldown));
+//! let html = format!("{}", Markdown(s, &[], RenderType::Pulldown));
 //! // ... something using html
 //! ```
 
@@ -31,13 +31,17 @@ use libc;
 use std::slice;
 
 use std::ascii::AsciiExt;
+use std::borrow::Cow;
 use std::cell::RefCell;
+use std::cmp;
 use std::collections::{HashMap, VecDeque};
 use std::default::Default;
 use std::fmt::{self, Write};
+use std::mem;
 use std::str;
 use syntax::feature_gate::UnstableFeatures;
 use syntax::codemap::Span;
+use syntax::util::lev_distance::lev_distance;
 
 use html::render::derive_id;
 use html::toc::TocBuilder;
@@ -57,8 +61,9 @@ pub enum RenderType {
 /// A unit struct which has the `fmt::Display` trait implemented. When
 /// formatted, this struct will emit the HTML corresponding to the rendered
 /// version of the contained markdown string.
-// The second parameter is whether we need a shorter version or not.
-pub struct Markdown<'a>(pub &'a str, pub RenderType);
+// The second parameter is a list of link replacements: the destinations of
+// links matching the first element of a pair are replaced with the second.
+pub struct Markdown<'a>(pub &'a str, pub &'a [(String, String)], pub RenderType);
 /// A unit struct like `Markdown`, that renders the markdown with a
 /// table of contents.
 pub struct MarkdownWithToc<'a>(pub &'a str, pub RenderType);
@@ -305,6 +310,54 @@ impl<'a, 'b, I: Iterator<Item = Event<'a>>> Iterator for HeadingLinks<'a, 'b, I>
     }
 }
 
+/// Points the links to intra-doc link targets at the URLs they resolved to.
+struct LinkReplacer<'a, 'b, I: Iterator<Item = Event<'a>>> {
+    inner: I,
+    links: &'b [(String, String)],
+}
+
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> LinkReplacer<'a, 'b, I> {
+    fn new(iter: I, links: &'b [(String, String)]) -> Self {
+        LinkReplacer {
+            inner: iter,
+            links,
+        }
+    }
+}
+
+impl<'a, 'b, I: Iterator<Item = Event<'a>>> Iterator for LinkReplacer<'a, 'b, I> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.inner.next() {
+            Some(Event::Start(Tag::Link(dest, title))) => {
+                let dest = match self.links.iter().find(|link| *link.0 == *dest) {
+                    Some(&(_, ref url)) => url.clone().into(),
+                    None => dest,
+                };
+                Some(Event::Start(Tag::Link(dest, title)))
+            }
+            event => event,
+        }
+    }
+}
+
+/// Adds a reference definition for each link replacement, so that shortcut
+/// and reference links to its target (`[Foo]`, `[text][Foo]`) become links
+/// too: neither markdown parser lets us resolve references which aren't
+/// defined. Definitions in `md` itself come first and take precedence.
+fn with_link_definitions<'a>(md: &'a str, links: &[(String, String)]) -> Cow<'a, str> {
+    if links.is_empty() {
+        return Cow::Borrowed(md);
+    }
+    let mut md = md.to_owned();
+    md.push('\n');
+    for &(ref link, ref url) in links {
+        md.push_str(&format!("\n[{}]: {}", link, url));
+    }
+    Cow::Owned(md)
+}
+
 /// Extracts just the first paragraph.
 struct SummaryLine<'a, I: Iterator<Item = Event<'a>>> {
     inner: I,
@@ -523,11 +576,13 @@ struct hoedown_buffer {
     unit: libc::size_t,
 }
 
-struct MyOpaque {
+struct MyOpaque<'a> {
     dfltblk: extern "C" fn(*mut hoedown_buffer, *const hoedown_buffer,
                            *const hoedown_buffer, *const hoedown_renderer_data,
                            libc::size_t),
+    dfltlink: linkfn,
     toc_builder: Option<TocBuilder>,
+    links: &'a [(String, String)],
 }
 
 extern {
@@ -558,6 +613,7 @@ impl hoedown_buffer {
 
 pub fn render(w: &mut fmt::Formatter,
               s: &str,
+              links: &[(String, String)],
               print_toc: bool,
               html_flags: libc::c_uint) -> fmt::Result {
     extern fn block(ob: *mut hoedown_buffer, orig_text: *const hoedown_buffer,
@@ -739,18 +795,41 @@ pub fn render(w: &mut fmt::Formatter,
         1
     }
 
+    extern fn link(ob: *mut hoedown_buffer, content: *const hoedown_buffer,
+                   link: *const hoedown_buffer, title: *const hoedown_buffer,
+                   data: *const hoedown_renderer_data, line: libc::size_t) -> libc::c_int {
+        unsafe {
+            let opaque = (*data).opaque as *mut hoedown_html_renderer_state;
+            let my_opaque: &MyOpaque = &*((*opaque).opaque as *const MyOpaque);
+            if !link.is_null() {
+                let dest = str::from_utf8((*link).as_bytes()).unwrap();
+                if let Some(&(_, ref url)) = my_opaque.links.iter().find(|l| l.0 == dest) {
+                    let url_buf = hoedown_buffer_new(DEF_OUNIT);
+                    hoedown_buffer_put(url_buf, url.as_ptr(), url.len());
+                    let ret = (my_opaque.dfltlink)(ob, content, url_buf, title, data, line);
+                    hoedown_buffer_free(url_buf);
+                    return ret;
+                }
+            }
+            (my_opaque.dfltlink)(ob, content, link, title, data, line)
+        }
+    }
+
     unsafe {
         let ob = hoedown_buffer_new(DEF_OUNIT);
         let renderer = hoedown_html_renderer_new(html_flags, 0);
         let mut opaque = MyOpaque {
             dfltblk: (*renderer).blockcode.unwrap(),
-            toc_builder: if print_toc {Some(TocBuilder::new())} else {None}
+            dfltlink: (*renderer).link.unwrap(),
+            toc_builder: if print_toc {Some(TocBuilder::new())} else {None},
+            links,
         };
         (*((*renderer).opaque as *mut hoedown_html_renderer_state)).opaque
                 = &mut opaque as *mut _ as *mut libc::c_void;
         (*renderer).blockcode = Some(block);
         (*renderer).header = Some(header);
         (*renderer).codespan = Some(codespan);
+        (*renderer).link = Some(link);
 
         let document = hoedown_document_new(renderer, HOEDOWN_EXTENSIONS, 16);
         hoedown_document_render(document, ob, s.as_ptr(),
@@ -798,11 +877,7 @@ pub fn old_find_testable_code(doc: &str, tests: &mut ::test::Collector, position
 
             if tests.render_type == RenderType::Hoedown {
                 let line = tests.get_line() + line;
-                tests.add_test(text.to_owned(),
-                               block_info.should_panic, block_info.no_run,
-                               block_info.ignore, block_info.test_harness,
-                               block_info.compile_fail, block_info.error_codes,
-                               line, filename, block_info.allow_fail);
+                tests.add_test(text.to_owned(), block_info, line, filename);
             } else {
                 tests.add_old_test(text, filename);
             }
@@ -888,11 +963,7 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector, position: Sp
                 nb_lines += doc[prev_offset..offset].lines().count();
                 let line = tests.get_line() + (nb_lines - 1);
                 let filename = tests.get_filename();
-                tests.add_test(text.to_owned(),
-                               block_info.should_panic, block_info.no_run,
-                               block_info.ignore, block_info.test_harness,
-                               block_info.compile_fail, block_info.error_codes,
-                               line, filename, block_info.allow_fail);
+                tests.add_test(text.to_owned(), block_info, line, filename);
                 prev_offset = offset;
             }
             Event::Start(Tag::Header(level)) => {
@@ -912,17 +983,27 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector, position: Sp
     }
 }
 
-#[derive(Eq, PartialEq, Clone, Debug)]
-struct LangString {
+/// The attributes of a code block, from the string after its opening fence,
+/// such as `rust,should_panic="overflow",ignore-windows`.
+#[derive(Eq, PartialEq, Clone, Debug, Hash)]
+pub struct LangString {
     original: String,
-    should_panic: bool,
-    no_run: bool,
-    ignore: bool,
-    rust: bool,
-    test_harness: bool,
-    compile_fail: bool,
-    error_codes: Vec<String>,
-    allow_fail: bool,
+    pub should_panic: bool,
+    pub no_run: bool,
+    pub ignore: bool,
+    pub rust: bool,
+    pub test_harness: bool,
+    pub compile_fail: bool,
+    pub error_codes: Vec<String>,
+    pub allow_fail: bool,
+    /// The targets the example is ignored on, from `ignore-<target>`.
+    pub ignore_targets: Vec<String>,
+    /// The message the example should panic with, from `should_panic="msg"`.
+    pub panic_message: Option<String>,
+    /// The `--cfg`s to compile the example with, from `cfg=spec`.
+    pub cfgs: Vec<String>,
+    /// The features to enable for the example, from `feature=name`.
+    pub features: Vec<String>,
 }
 
 impl LangString {
@@ -937,9 +1018,50 @@ impl LangString {
             compile_fail: false,
             error_codes: Vec::new(),
             allow_fail: false,
+            ignore_targets: Vec::new(),
+            panic_message: None,
+            cfgs: Vec::new(),
+            features: Vec::new(),
         }
     }
 
+    /// Splits the attributes of a code block. Attributes are separated by
+    /// anything but alphanumerics, `_`, `-` and `=`, and may have a value
+    /// after a `=`, which may be surrounded by spaces. Values in double quotes
+    /// may contain anything, with `\"` and `\\` escaping a quote and a
+    /// backslash; the quotes are removed.
+    fn tokens(string: &str) -> Vec<String> {
+        let mut tokens = Vec::new();
+        let mut token = String::new();
+        let mut chars = string.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => {
+                    while let Some(c) = chars.next() {
+                        match c {
+                            '"' => break,
+                            '\\' => token.extend(chars.next()),
+                            c => token.push(c),
+                        }
+                    }
+                }
+                c if c == '_' || c == '-' || c == '=' || c.is_alphanumeric() => token.push(c),
+                c if c.is_whitespace() &&
+                     (token.ends_with('=') ||
+                      chars.clone().find(|c| !c.is_whitespace()) == Some('=')) => {}
+                _ => {
+                    if !token.is_empty() {
+                        tokens.push(mem::replace(&mut token, String::new()));
+                    }
+                }
+            }
+        }
+        if !token.is_empty() {
+            tokens.push(token);
+        }
+        tokens
+    }
+
     fn parse(string: &str) -> LangString {
         let mut seen_rust_tags = false;
         let mut seen_other_tags = false;
@@ -950,31 +1072,48 @@ impl LangString {
         }
 
         data.original = string.to_owned();
-        let tokens = string.split(|c: char|
-            !(c == '_' || c == '-' || c.is_alphanumeric())
-        );
-
-        for token in tokens {
-            match token.trim() {
-                "" => {},
-                "should_panic" => {
+
+        for token in LangString::tokens(string) {
+            let (name, value) = match token.find('=') {
+                Some(i) => (&token[..i], Some(&token[i + 1..])),
+                None => (&token[..], None),
+            };
+            match (name, value) {
+                ("should_panic", value) => {
                     data.should_panic = true;
+                    data.panic_message = value.map(|s| s.to_owned());
                     seen_rust_tags = seen_other_tags == false;
                 }
-                "no_run" => { data.no_run = true; seen_rust_tags = !seen_other_tags; }
-                "ignore" => { data.ignore = true; seen_rust_tags = !seen_other_tags; }
-                "allow_fail" => { data.allow_fail = true; seen_rust_tags = !seen_other_tags; }
-                "rust" => { data.rust = true; seen_rust_tags = true; }
-                "test_harness" => {
+                ("cfg", Some(spec)) => {
+                    data.cfgs.push(spec.to_owned());
+                    seen_rust_tags = !seen_other_tags || seen_rust_tags;
+                }
+                ("feature", Some(feature)) => {
+                    data.features.push(feature.to_owned());
+                    seen_rust_tags = !seen_other_tags || seen_rust_tags;
+                }
+                (_, Some(_)) => { seen_other_tags = true }
+                (x, None) if x.starts_with("ignore-") && x.len() > "ignore-".len() => {
+                    data.ignore_targets.push(x["ignore-".len()..].to_owned());
+                    seen_rust_tags = !seen_other_tags;
+                }
+                ("no_run", None) => { data.no_run = true; seen_rust_tags = !seen_other_tags; }
+                ("ignore", None) => { data.ignore = true; seen_rust_tags = !seen_other_tags; }
+                ("allow_fail", None) => {
+                    data.allow_fail = true;
+                    seen_rust_tags = !seen_other_tags;
+                }
+                ("rust", None) => { data.rust = true; seen_rust_tags = true; }
+                ("test_harness", None) => {
                     data.test_harness = true;
                     seen_rust_tags = !seen_other_tags || seen_rust_tags;
                 }
-                "compile_fail" => {
+                ("compile_fail", None) => {
                     data.compile_fail = true;
                     seen_rust_tags = !seen_other_tags || seen_rust_tags;
                     data.no_run = true;
                 }
-                x if allow_error_code_check && x.starts_with("E") && x.len() == 5 => {
+                (x, None) if allow_error_code_check && x.starts_with("E") && x.len() == 5 => {
                     if let Ok(_) = x[1..].parse::<u32>() {
                         data.error_codes.push(x.to_owned());
                         seen_rust_tags = !seen_other_tags || seen_rust_tags;
@@ -990,22 +1129,93 @@ impl LangString {
 
         data
     }
+
+    /// Returns the tokens of `string` which aren't known attributes but are
+    /// close enough to one to likely be a misspelling of it, along with that
+    /// attribute.
+    fn misspelled_attributes(string: &str) -> Vec<(String, &'static str)> {
+        const KNOWN: &[&str] = &["should_panic", "no_run", "ignore", "allow_fail", "rust",
+                                 "test_harness", "compile_fail", "cfg", "feature"];
+        let tokens = LangString::tokens(string).into_iter().map(|token| {
+            match token.find('=') {
+                Some(i) => token[..i].to_owned(),
+                None => token,
+            }
+        });
+        tokens.filter(|token| {
+            !token.is_empty() && !KNOWN.contains(&&token[..]) && !token.starts_with("ignore-")
+        }).filter_map(|token| {
+            let normalized = token.to_lowercase().replace('-', "_");
+            KNOWN.iter().find(|known| {
+                lev_distance(&normalized, known) <= cmp::min(2, known.len() / 3)
+            }).map(|known| (token.to_owned(), *known))
+        }).collect()
+    }
+}
+
+/// A problem with the Markdown of a doc comment, found by `doc_problems`.
+#[derive(Debug, PartialEq)]
+pub enum DocProblem {
+    /// The code block opened on the given line of the doc comment, counting
+    /// from zero, is never closed.
+    UnclosedCodeBlock(usize),
+    /// The code block opened on the given line has an unknown attribute, which
+    /// looks like a misspelling of the known one.
+    MisspelledCodeBlockAttribute(usize, String, &'static str),
+}
+
+/// Returns the problems with the code blocks of `md` which would make
+/// rustdoc silently render or test them differently than intended.
+pub fn doc_problems(md: &str) -> Vec<DocProblem> {
+    let mut problems = vec![];
+    // The line, character and length of the opening fence of the current block
+    let mut fence: Option<(usize, char, usize)> = None;
+    for (i, line) in md.lines().enumerate() {
+        let trimmed = line.trim_left();
+        let c = match trimmed.chars().next() {
+            Some(c @ '`') | Some(c @ '~') => c,
+            _ => continue,
+        };
+        let len = trimmed.chars().take_while(|&x| x == c).count();
+        if len < 3 {
+            continue;
+        }
+        match fence {
+            Some((_, open_c, open_len)) => {
+                if c == open_c && len >= open_len && trimmed[len..].trim().is_empty() {
+                    fence = None;
+                }
+            }
+            None => {
+                fence = Some((i, c, len));
+                for (token, known) in LangString::misspelled_attributes(&trimmed[len..]) {
+                    problems.push(DocProblem::MisspelledCodeBlockAttribute(i, token, known));
+                }
+            }
+        }
+    }
+    if let Some((line, _, _)) = fence {
+        problems.push(DocProblem::UnclosedCodeBlock(line));
+    }
+    problems
 }
 
 impl<'a> fmt::Display for Markdown<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        let Markdown(md, render_type) = *self;
+        let Markdown(md, links, render_type) = *self;
 
         // This is actually common enough to special-case
         if md.is_empty() { return Ok(()) }
+        let md = with_link_definitions(md, links);
         if render_type == RenderType::Hoedown {
-            render(fmt, md, false, 0)
+            render(fmt, &md, links, false, 0)
         } else {
             let mut opts = Options::empty();
             opts.insert(OPTION_ENABLE_TABLES);
             opts.insert(OPTION_ENABLE_FOOTNOTES);
 
-            let p = Parser::new_ext(md, opts);
+            let p = Parser::new_ext(&md, opts);
+            let p = LinkReplacer::new(p, links);
 
             let mut s = String::with_capacity(md.len() * 3 / 2);
 
@@ -1022,7 +1232,7 @@ impl<'a> fmt::Display for MarkdownWithToc<'a> {
         let MarkdownWithToc(md, render_type) = *self;
 
         if render_type == RenderType::Hoedown {
-            render(fmt, md, true, 0)
+            render(fmt, md, &[], true, 0)
         } else {
             let mut opts = Options::empty();
             opts.insert(OPTION_ENABLE_TABLES);
@@ -1051,7 +1261,7 @@ impl<'a> fmt::Display for MarkdownHtml<'a> {
         // This is actually common enough to special-case
         if md.is_empty() { return Ok(()) }
         if render_type == RenderType::Hoedown {
-            render(fmt, md, false, HOEDOWN_HTML_ESCAPE)
+            render(fmt, md, &[], false, HOEDOWN_HTML_ESCAPE)
         } else {
             let mut opts = Options::empty();
             opts.insert(OPTION_ENABLE_TABLES);
@@ -1141,10 +1351,142 @@ pub fn plain_summary_line(md: &str) -> String {
     s
 }
 
+/// Finds the link targets in `md` that could be intra-doc links: the
+/// destinations of inline links (`[text](target)`) and the labels of
+/// reference links (`[target]`, `[target][]`, `[text][target]`) which have
+/// no definition in `md` itself. Code blocks and code spans are skipped.
+///
+/// The second element is `false` for shortcut links (`[target]`), which are
+/// just as likely to be square brackets in prose.
+pub fn markdown_links(md: &str) -> Vec<(String, bool)> {
+    // Returns the index right after the `close` matching the `open` at `start`,
+    // treating code spans as opaque.
+    fn find_closing(s: &[u8], start: usize, open: u8, close: u8) -> Option<usize> {
+        let mut depth = 0;
+        let mut i = start;
+        while i < s.len() {
+            match s[i] {
+                b'\\' => i += 1,
+                b'`' => i = skip_code_span(s, i) - 1,
+                c if c == open => depth += 1,
+                c if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(i + 1);
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+        None
+    }
+
+    // Returns the index right after the code span starting at `start`, or
+    // right after its opening backticks if it's never closed.
+    fn skip_code_span(s: &[u8], start: usize) -> usize {
+        let ticks = s[start..].iter().take_while(|&&c| c == b'`').count();
+        let mut i = start + ticks;
+        while i < s.len() {
+            let run = s[i..].iter().take_while(|&&c| c == b'`').count();
+            if run == ticks {
+                return i + run;
+            }
+            i += cmp::max(run, 1);
+        }
+        start + ticks
+    }
+
+    let mut links = vec![];
+    let mut definitions = vec![];
+    let mut in_code_block = false;
+    for line in md.lines() {
+        let trimmed = line.trim_left();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block || line.starts_with("    ") || line.starts_with('\t') {
+            continue;
+        }
+        if trimmed.starts_with('[') {
+            if let Some(end) = trimmed.find("]:") {
+                definitions.push(trimmed[1..end].to_owned());
+                continue;
+            }
+        }
+
+        let s = line.as_bytes();
+        let mut i = 0;
+        while i < s.len() {
+            match s[i] {
+                b'\\' => i += 2,
+                b'`' => i = skip_code_span(s, i),
+                // `a[i]` is indexing and `![..]` an image, not links.
+                b'[' if i > 0 && (s[i - 1] == b'!' || s[i - 1] == b']' ||
+                                  s[i - 1] == b')' || (s[i - 1] as char).is_alphanumeric()) => {
+                    i += 1
+                }
+                b'[' => {
+                    let end = match find_closing(s, i, b'[', b']') {
+                        Some(end) => end,
+                        None => break,
+                    };
+                    let label = &line[i + 1..end - 1];
+                    match s.get(end) {
+                        Some(&b'(') => {
+                            if let Some(dest_end) = find_closing(s, end, b'(', b')') {
+                                let dest = line[end + 1..dest_end - 1].trim();
+                                let dest = dest.split_whitespace().next().unwrap_or("");
+                                links.push((dest.to_owned(), true));
+                                i = dest_end;
+                                continue;
+                            }
+                            links.push((label.to_owned(), false));
+                        }
+                        Some(&b'[') => {
+                            if let Some(ref_end) = find_closing(s, end, b'[', b']') {
+                                let reference = &line[end + 1..ref_end - 1];
+                                let target = if reference.is_empty() { label } else { reference };
+                                links.push((target.to_owned(), true));
+                                i = ref_end;
+                                continue;
+                            }
+                            links.push((label.to_owned(), false));
+                        }
+                        _ => links.push((label.to_owned(), false)),
+                    }
+                    i = end;
+                }
+                _ => i += 1,
+            }
+        }
+    }
+    links.retain(|&(ref link, _)| !link.is_empty() && !definitions.contains(link));
+    links
+}
+
+/// Returns whether `md` contains a fenced code block of Rust code, which would
+/// be run as a doctest.
+pub fn has_code_example(md: &str) -> bool {
+    let mut in_code_block = false;
+    for line in md.lines() {
+        let trimmed = line.trim_left();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            if !in_code_block && LangString::parse(&trimmed[3..]).rust {
+                return true;
+            }
+            in_code_block = !in_code_block;
+        }
+    }
+    false
+}
+
 #[cfg(test)]
 mod tests {
     use super::{LangString, Markdown, MarkdownHtml};
-    use super::plain_summary_line;
+    use super::{has_code_example, markdown_links, plain_summary_line};
+    use super::{doc_problems, DocProblem};
     use super::RenderType;
     use html::render::reset_ids;
 
@@ -1163,6 +1505,10 @@ mod tests {
                 error_codes,
                 original: s.to_owned(),
                 allow_fail,
+                ignore_targets: vec![],
+                panic_message: None,
+                cfgs: vec![],
+                features: vec![],
             })
         }
 
@@ -1189,17 +1535,40 @@ mod tests {
         t("text,no_run",           false,        true,   false,  false, false, false, false, v());
     }
 
+    #[test]
+    fn test_lang_string_parse_values() {
+        let parsed = LangString::parse("rust,ignore-windows, ignore-arm");
+        assert!(parsed.rust && !parsed.ignore);
+        assert_eq!(parsed.ignore_targets, vec!["windows".to_owned(), "arm".to_owned()]);
+
+        let parsed = LangString::parse(r#"should_panic="index out of bounds, at 3""#);
+        assert!(parsed.rust && parsed.should_panic);
+        assert_eq!(parsed.panic_message, Some("index out of bounds, at 3".to_owned()));
+
+        let parsed = LangString::parse(r#"ignore, should_panic = "overflow""#);
+        assert!(parsed.ignore && parsed.should_panic);
+        assert_eq!(parsed.panic_message, Some("overflow".to_owned()));
+
+        let parsed = LangString::parse(r#"cfg=unix,cfg="feature=\"std\"",feature=box_syntax"#);
+        assert!(parsed.rust);
+        assert_eq!(parsed.cfgs, vec!["unix".to_owned(), r#"feature="std""#.to_owned()]);
+        assert_eq!(parsed.features, vec!["box_syntax".to_owned()]);
+
+        let parsed = LangString::parse("text,cfg=unix");
+        assert!(!parsed.rust);
+    }
+
     #[test]
     fn issue_17736() {
         let markdown = "# title";
-        format!("{}", Markdown(markdown, RenderType::Pulldown));
+        format!("{}", Markdown(markdown, &[], RenderType::Pulldown));
         reset_ids(true);
     }
 
     #[test]
     fn test_header() {
         fn t(input: &str, expect: &str) {
-            let output = format!("{}", Markdown(input, RenderType::Pulldown));
+            let output = format!("{}", Markdown(input, &[], RenderType::Pulldown));
             assert_eq!(output, expect, "original: {}", input);
             reset_ids(true);
         }
@@ -1221,7 +1590,7 @@ mod tests {
     #[test]
     fn test_header_ids_multiple_blocks() {
         fn t(input: &str, expect: &str) {
-            let output = format!("{}", Markdown(input, RenderType::Pulldown));
+            let output = format!("{}", Markdown(input, &[], RenderType::Pulldown));
             assert_eq!(output, expect, "original: {}", input);
         }
 
@@ -1270,4 +1639,42 @@ mod tests {
         t("Struct<'a, T>", "<p>Struct&lt;'a, T&gt;</p>\n");
         t("Struct<br>", "<p>Struct&lt;br&gt;</p>\n");
     }
+
+    #[test]
+    fn test_markdown_links() {
+        fn t(input: &str, expect: &[(&str, bool)]) {
+            let output = markdown_links(input);
+            let expect = expect.iter().map(|&(l, e)| (l.to_owned(), e)).collect::<Vec<_>>();
+            assert_eq!(output, expect, "original: {}", input);
+        }
+
+        t("see [Foo] and [`bar::Baz`]", &[("Foo", false), ("`bar::Baz`", false)]);
+        t("[text](Foo) and [text][Bar] and [Baz][]",
+          &[("Foo", true), ("Bar", true), ("Baz", true)]);
+        t("[Foo]\n\n[Foo]: https://example.com", &[]);
+        t("`[Foo]` a[i] ![img](x.png)", &[]);
+        t("```\n[Foo]\n```\n    [Bar]\n[Baz]", &[("Baz", false)]);
+    }
+
+    #[test]
+    fn test_doc_problems() {
+        assert_eq!(doc_problems("```\nfoo();\n```\n\n~~~~text\n```\n~~~~"), vec![]);
+        assert_eq!(doc_problems("Foo\n\n```rust\nfoo();\n"),
+                   vec![DocProblem::UnclosedCodeBlock(2)]);
+        assert_eq!(doc_problems("```\n```\n````\n```"),
+                   vec![DocProblem::UnclosedCodeBlock(2)]);
+        assert_eq!(doc_problems("```should-panic,no_rum,text\nfoo();\n```"),
+                   vec![DocProblem::MisspelledCodeBlockAttribute(0, "should-panic".to_owned(),
+                                                                 "should_panic"),
+                        DocProblem::MisspelledCodeBlockAttribute(0, "no_rum".to_owned(),
+                                                                 "no_run")]);
+    }
+
+    #[test]
+    fn test_has_code_example() {
+        assert!(has_code_example("Foo\n\n```\nfoo();\n```"));
+        assert!(has_code_example("```text\nfoo\n```\n\n~~~should_panic\nfoo();\n~~~"));
+        assert!(!has_code_example("Foo `bar()`"));
+        assert!(!has_code_example("```text\nfoo();\n```"));
+    }
 }
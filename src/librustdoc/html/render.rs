@@ -54,12 +54,15 @@ use externalfiles::ExternalHtml;
 
 use serialize::json::{ToJson, Json, as_json};
 use syntax::{abi, ast};
-use rustc::hir::def_id::{CrateNum, CRATE_DEF_INDEX, DefId};
+use rustc::hir::def_id::{CrateNum, CRATE_DEF_INDEX, DefId, LOCAL_CRATE};
 use rustc::middle::privacy::AccessLevels;
 use rustc::middle::stability;
 use rustc::hir;
+use rustc::hir::map::DefPathHash;
 use rustc::util::nodemap::{FxHashMap, FxHashSet};
 use rustc_data_structures::flock;
+use scrape::{Callee, CallSite, ScrapedExamples};
+use source_links::{LinkTarget, SourceLinks};
 
 use clean::{self, AttributesExt, GetDefId, SelfTy, Mutability, Span};
 use doctree;
@@ -125,6 +128,10 @@ pub struct SharedContext {
     /// Warnings for the user if rendering would differ using different markdown
     /// parsers.
     pub markdown_warnings: RefCell<Vec<(Span, String, Vec<html_diff::Difference>)>>,
+    /// The calls of the crate's functions and methods found by
+    /// `--scrape-examples`, by the path the function or method is documented
+    /// at, as given by `full_path`.
+    pub scraped_examples: FxHashMap<String, Vec<CallSite>>,
 }
 
 /// Indicates where an external crate can be found.
@@ -262,6 +269,7 @@ pub struct Cache {
     parent_stack: Vec<DefId>,
     parent_is_trait_impl: bool,
     search_index: Vec<IndexItem>,
+    full_text_search: bool,
     stripped_mod: bool,
     deref_trait_did: Option<DefId>,
     deref_mut_trait_did: Option<DefId>,
@@ -286,6 +294,11 @@ pub struct RenderInfo {
     pub deref_trait_did: Option<DefId>,
     pub deref_mut_trait_did: Option<DefId>,
     pub owned_box_did: Option<DefId>,
+    pub scraped_examples: ScrapedExamples,
+    /// The local items by the hashes of their paths, to find the callees of
+    /// `scraped_examples`.
+    pub def_path_hashes: FxHashMap<DefPathHash, DefId>,
+    pub source_links: SourceLinks,
 }
 
 /// Helper struct to render all source code to HTML pages
@@ -294,11 +307,19 @@ struct SourceCollector<'a> {
 
     /// Root destination to place all HTML output into
     dst: PathBuf,
+
+    /// The name of the crate, under which its sources are rendered
+    crate_name: String,
+
+    /// What the identifiers of each source file link to, with
+    /// `--generate-link-to-definition`
+    source_links: SourceLinks,
 }
 
 /// Wrapper struct to render the source code of a file. This will do things like
-/// adding line numbers to the left-hand side.
-struct Source<'a>(&'a str);
+/// adding line numbers to the left-hand side. The identifiers starting at the
+/// byte offsets in the map are made links to the given URLs.
+struct Source<'a>(&'a str, &'a FxHashMap<usize, String>);
 
 // Helper structs for rendering items/sidebars and carrying along contextual
 // information
@@ -321,6 +342,8 @@ struct IndexItem {
     parent: Option<DefId>,
     parent_idx: Option<usize>,
     search_type: Option<IndexItemFunctionType>,
+    /// The words of the item's documentation, if building a full-text index.
+    doc_words: Vec<String>,
 }
 
 impl ToJson for IndexItem {
@@ -342,6 +365,8 @@ impl ToJson for IndexItem {
 /// A type used for the search index.
 struct Type {
     name: Option<String>,
+    /// The generic arguments of the type which have a name themselves.
+    generics: Vec<Type>,
 }
 
 impl ToJson for Type {
@@ -350,6 +375,9 @@ impl ToJson for Type {
             Some(ref name) => {
                 let mut data = BTreeMap::new();
                 data.insert("name".to_owned(), name.to_json());
+                if !self.generics.is_empty() {
+                    data.insert("generics".to_owned(), self.generics.to_json());
+                }
                 Json::Object(data)
             },
             None => Json::Null
@@ -440,8 +468,9 @@ pub fn run(mut krate: clean::Crate,
            dst: PathBuf,
            passes: FxHashSet<String>,
            css_file_extension: Option<PathBuf>,
-           renderinfo: RenderInfo,
-           render_type: RenderType) -> Result<(), Error> {
+           mut renderinfo: RenderInfo,
+           render_type: RenderType,
+           full_text_search: bool) -> Result<(), Error> {
     let src_root = match krate.src.parent() {
         Some(p) => p.to_path_buf(),
         None => PathBuf::new(),
@@ -460,6 +489,7 @@ pub fn run(mut krate: clean::Crate,
         },
         css_file_extension: css_file_extension.clone(),
         markdown_warnings: RefCell::new(vec![]),
+        scraped_examples: FxHashMap(),
     };
 
     // If user passed in `--playground-url` arg, we fill in crate name here
@@ -498,14 +528,9 @@ pub fn run(mut krate: clean::Crate,
         }
     }
     try_err!(fs::create_dir_all(&dst), &dst);
-    krate = render_sources(&dst, &mut scx, krate)?;
-    let cx = Context {
-        current: Vec::new(),
-        dst,
-        render_redirect_pages: false,
-        shared: Arc::new(scx),
-        render_type,
-    };
+    let source_links = mem::replace(&mut renderinfo.source_links, FxHashMap());
+    let scraped_examples = mem::replace(&mut renderinfo.scraped_examples, FxHashMap());
+    let def_path_hashes = mem::replace(&mut renderinfo.def_path_hashes, FxHashMap());
 
     // Crawl the crate to build various caches used for the output
     let RenderInfo {
@@ -515,6 +540,9 @@ pub fn run(mut krate: clean::Crate,
         deref_trait_did,
         deref_mut_trait_did,
         owned_box_did,
+        scraped_examples: _,
+        def_path_hashes: _,
+        source_links: _,
     } = renderinfo;
 
     let external_paths = external_paths.into_iter()
@@ -529,6 +557,7 @@ pub fn run(mut krate: clean::Crate,
         stack: Vec::new(),
         parent_stack: Vec::new(),
         search_index: Vec::new(),
+        full_text_search,
         parent_is_trait_impl: false,
         extern_locations: FxHashMap(),
         primitive_locations: FxHashMap(),
@@ -550,7 +579,7 @@ pub fn run(mut krate: clean::Crate,
             None => PathBuf::new(),
         };
         cache.extern_locations.insert(n, (e.name.clone(), src_root,
-                                          extern_location(e, &cx.dst)));
+                                          extern_location(e, &dst)));
 
         let did = DefId { krate: n, index: CRATE_DEF_INDEX };
         cache.external_paths.insert(did, (vec![e.name.to_string()], ItemType::Module));
@@ -572,8 +601,34 @@ pub fn run(mut krate: clean::Crate,
     cache.stack.push(krate.name.clone());
     krate = cache.fold_crate(krate);
 
+    // Now that it's known where everything is documented, file the scraped
+    // calls under the paths the functions and methods are documented at.
+    scx.scraped_examples = scraped_examples.into_iter().filter_map(|(callee, calls)| {
+        let (hash, method) = match callee {
+            Callee::Fn(hash) => (hash, None),
+            Callee::Method(hash, name) => (hash, Some(name)),
+        };
+        let did = match def_path_hashes.get(&hash) {
+            Some(did) => did,
+            None => return None,
+        };
+        cache.paths.get(did).map(|&(ref fqp, _)| {
+            let mut path = fqp.join("::");
+            if let Some(name) = method {
+                path.push_str("::");
+                path.push_str(&name);
+            }
+            (path, calls)
+        })
+    }).collect();
+
     // Build our search index
     let index = build_index(&krate, &mut cache);
+    let full_text_index = if full_text_search {
+        Some(build_full_text_index(&krate, &cache))
+    } else {
+        None
+    };
 
     // Freeze the cache now that the index has been built. Put an Arc into TLS
     // for future parallelization opportunities
@@ -581,7 +636,19 @@ pub fn run(mut krate: clean::Crate,
     CACHE_KEY.with(|v| *v.borrow_mut() = cache.clone());
     CURRENT_LOCATION_KEY.with(|s| s.borrow_mut().clear());
 
-    write_shared(&cx, &krate, &*cache, index)?;
+    // The sources link to other crates like the rest of the documentation, so
+    // they're rendered once the cache knows where those are.
+    krate = render_sources(&dst, &mut scx, source_links, krate)?;
+
+    let cx = Context {
+        current: Vec::new(),
+        dst,
+        render_redirect_pages: false,
+        shared: Arc::new(scx),
+        render_type,
+    };
+
+    write_shared(&cx, &krate, &*cache, index, full_text_index)?;
 
     let scx = cx.shared.clone();
 
@@ -721,6 +788,9 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
 
     let Cache { ref mut search_index,
                 ref orphan_impl_items,
+                ref implementors,
+                ref external_paths,
+                full_text_search,
                 ref mut paths, .. } = *cache;
 
     // Attach all orphan items to the type's definition if the type
@@ -735,6 +805,7 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
                 parent: Some(did),
                 parent_idx: None,
                 search_type: get_index_search_type(&item),
+                doc_words: if full_text_search { doc_words(item.doc_value()) } else { vec![] },
             });
         }
     }
@@ -768,6 +839,27 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
         crate_items.push(item.to_json());
     }
 
+    // The trait implementations of this crate, so that the implementors of a
+    // trait can be searched for across all the crates documented together.
+    let mut crate_impls = vec![];
+    for (trait_did, imps) in implementors {
+        let trait_path = match paths.get(trait_did).or_else(|| external_paths.get(trait_did)) {
+            Some(&(ref fqp, _)) => fqp.join("::"),
+            None => continue,
+        };
+        for imp in imps.iter().filter(|imp| imp.def_id.krate == LOCAL_CRATE) {
+            let did = match imp.impl_.for_.def_id() {
+                Some(did) => did,
+                None => continue,
+            };
+            if let Some(&(ref fqp, short)) = paths.get(&did).or_else(|| external_paths.get(&did)) {
+                crate_impls.push((trait_path.clone(), short as usize,
+                                  fqp[..fqp.len() - 1].join("::"), fqp.last().unwrap().clone()));
+            }
+        }
+    }
+    crate_impls.sort();
+
     let crate_doc = krate.module.as_ref().map(|module| {
         plain_summary_line(module.doc_value())
     }).unwrap_or(String::new());
@@ -776,6 +868,7 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
     crate_data.insert("doc".to_owned(), Json::String(crate_doc));
     crate_data.insert("items".to_owned(), Json::Array(crate_items));
     crate_data.insert("paths".to_owned(), Json::Array(crate_paths));
+    crate_data.insert("impls".to_owned(), crate_impls.to_json());
 
     // Collect the index into a string
     format!("searchIndex[{}] = {};",
@@ -783,10 +876,35 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> String {
             Json::Object(crate_data))
 }
 
+/// Builds the full-text index of the crate, mapping every word found in the
+/// documentation to the positions of the items using it in the crate's
+/// search index. This has to be called after `build_index`.
+fn build_full_text_index(krate: &clean::Crate, cache: &Cache) -> String {
+    let mut words = BTreeMap::new();
+    for (i, item) in cache.search_index.iter().enumerate() {
+        for word in &item.doc_words {
+            words.entry(&word[..]).or_insert_with(Vec::new).push(i);
+        }
+    }
+    format!("fullTextIndex[{}] = {};", as_json(&krate.name), as_json(&words))
+}
+
+/// Returns the distinct, lowercased words of `doc` for the full-text index.
+fn doc_words(doc: Option<&str>) -> Vec<String> {
+    let mut words = doc.unwrap_or("").split(|c: char| !(c == '_' || c.is_alphanumeric()))
+        .filter(|word| word.len() > 2)
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>();
+    words.sort();
+    words.dedup();
+    words
+}
+
 fn write_shared(cx: &Context,
                 krate: &clean::Crate,
                 cache: &Cache,
-                search_index: String) -> Result<(), Error> {
+                search_index: String,
+                full_text_index: Option<String>) -> Result<(), Error> {
     // Write out the shared files. Note that these are shared among all rustdoc
     // docs placed in the output directory, so this needs to be a synchronized
     // operation with respect to all other rustdocs running around.
@@ -866,15 +984,20 @@ fn write_shared(cx: &Context,
     let dst = cx.dst.join("search-index.js");
     let mut all_indexes = try_err!(collect(&dst, &krate.name, "searchIndex"), &dst);
     all_indexes.push(search_index);
+    // The full-text indexes of the crates which have one live in the same file
+    let mut full_text_indexes = try_err!(collect(&dst, &krate.name, "fullTextIndex"), &dst);
+    full_text_indexes.extend(full_text_index);
     // Sort the indexes by crate so the file will be generated identically even
     // with rustdoc running in parallel.
     all_indexes.sort();
+    full_text_indexes.sort();
     let mut w = try_err!(File::create(&dst), &dst);
     try_err!(writeln!(&mut w, "var searchIndex = {{}};"), &dst);
-    for index in &all_indexes {
+    try_err!(writeln!(&mut w, "var fullTextIndex = {{}};"), &dst);
+    for index in all_indexes.iter().chain(&full_text_indexes) {
         try_err!(writeln!(&mut w, "{}", *index), &dst);
     }
-    try_err!(writeln!(&mut w, "initSearch(searchIndex);"), &dst);
+    try_err!(writeln!(&mut w, "initSearch(searchIndex, fullTextIndex);"), &dst);
 
     // Update the list of all implementors for traits
     let dst = cx.dst.join("implementors");
@@ -949,7 +1072,7 @@ fn write_shared(cx: &Context,
     Ok(())
 }
 
-fn render_sources(dst: &Path, scx: &mut SharedContext,
+fn render_sources(dst: &Path, scx: &mut SharedContext, source_links: SourceLinks,
                   krate: clean::Crate) -> Result<clean::Crate, Error> {
     info!("emitting source files");
     let dst = dst.join("src").join(&krate.name);
@@ -957,6 +1080,8 @@ fn render_sources(dst: &Path, scx: &mut SharedContext,
     let mut folder = SourceCollector {
         dst,
         scx,
+        crate_name: krate.name.clone(),
+        source_links,
     };
     Ok(folder.fold_crate(krate))
 }
@@ -1077,24 +1202,35 @@ impl<'a> SourceCollector<'a> {
         // Create the intermediate directories
         let mut cur = self.dst.clone();
         let mut root_path = String::from("../../");
-        let mut href = String::new();
+        let mut location = vec!["src".to_string(), self.crate_name.clone()];
         clean_srcpath(&self.scx.src_root, &p, false, |component| {
             cur.push(component);
             fs::create_dir_all(&cur).unwrap();
             root_path.push_str("../");
-            href.push_str(component);
-            href.push('/');
+            location.push(component.to_string());
         });
         let mut fname = p.file_name().expect("source has no filename")
                          .to_os_string();
         fname.push(".html");
         cur.push(&fname);
-        href.push_str(&fname.to_string_lossy());
+        let href = source_href(&self.scx.src_root, &p);
 
         let mut w = BufWriter::new(File::create(&cur)?);
         let title = format!("{} -- source", cur.file_name().unwrap()
                                                .to_string_lossy());
         let desc = format!("Source to the Rust file `{}`.", filename);
+        // Links to other crates are relative to the source page, like those of
+        // the documentation are to the page of the item.
+        CURRENT_LOCATION_KEY.with(|s| *s.borrow_mut() = location);
+        let links = match self.source_links.get(filename) {
+            Some(targets) => {
+                targets.iter().filter_map(|(&offset, target)| {
+                    self.link_target_href(target, &root_path).map(|href| (offset, href))
+                }).collect()
+            }
+            None => FxHashMap(),
+        };
+        CURRENT_LOCATION_KEY.with(|s| s.borrow_mut().clear());
         let page = layout::Page {
             title: &title,
             css_class: "source",
@@ -1103,12 +1239,52 @@ impl<'a> SourceCollector<'a> {
             keywords: BASIC_KEYWORDS,
         };
         layout::render(&mut w, &self.scx.layout,
-                       &page, &(""), &Source(contents),
+                       &page, &(""), &Source(contents, &links),
                        self.scx.css_file_extension.is_some())?;
         w.flush()?;
         self.scx.local_sources.insert(p, href);
         Ok(())
     }
+
+    /// Returns the URL of `target` from a source page at `root_path`, which is
+    /// also the `CURRENT_LOCATION_KEY` for links to other crates.
+    fn link_target_href(&self, target: &LinkTarget, root_path: &str) -> Option<String> {
+        match *target {
+            LinkTarget::Local { ref file, line } => {
+                // Definitions expanded from macros of other crates have no
+                // source page.
+                if file.starts_with("<") {
+                    return None;
+                }
+                Some(format!("{}src/{}/{}#{}", root_path, self.crate_name,
+                             source_href(&self.scx.src_root, Path::new(file)), line))
+            }
+            LinkTarget::External { did, ref anchor, .. } => {
+                let mut url = match href(did) {
+                    Some((url, ..)) => url,
+                    None => return None,
+                };
+                if let Some(ref anchor) = *anchor {
+                    url.push_str("#");
+                    url.push_str(anchor);
+                }
+                Some(url)
+            }
+        }
+    }
+}
+
+/// Returns the path of the source page of the file `p`, relative to the
+/// crate's directory of sources.
+fn source_href(src_root: &Path, p: &Path) -> String {
+    let mut href = String::new();
+    clean_srcpath(src_root, p, false, |component| {
+        href.push_str(component);
+        href.push('/');
+    });
+    href.push_str(&p.file_name().expect("source has no filename").to_string_lossy());
+    href.push_str(".html");
+    href
 }
 
 impl DocFolder for Cache {
@@ -1207,6 +1383,11 @@ impl DocFolder for Cache {
                             parent,
                             parent_idx: None,
                             search_type: get_index_search_type(&item),
+                            doc_words: if self.full_text_search {
+                                doc_words(item.doc_value())
+                            } else {
+                                vec![]
+                            },
                         });
                     }
                 }
@@ -1785,10 +1966,31 @@ fn document(w: &mut fmt::Formatter, cx: &Context, item: &clean::Item) -> fmt::Re
     Ok(())
 }
 
+/// Renders the calls of the function or method at `path` that were found by
+/// `--scrape-examples`, if any.
+fn render_scraped_examples(w: &mut fmt::Formatter, cx: &Context, path: &str) -> fmt::Result {
+    let calls = match cx.shared.scraped_examples.get(path) {
+        Some(calls) if !calls.is_empty() => calls,
+        _ => return Ok(()),
+    };
+    write!(w, "<div class='scraped-examples'>\
+               <h4 class='scraped-examples-header'>Examples found in the repository</h4>")?;
+    for call in calls {
+        write!(w, "<div class='scraped-example'><div class='scraped-example-title'>{}:{}</div>",
+               Escape(&call.file), call.line)?;
+        write!(w, "{}", highlight::render_with_highlighting(&call.code,
+                                                            Some("rust-example-rendered"),
+                                                            None, None, None))?;
+        write!(w, "</div>")?;
+    }
+    write!(w, "</div>")
+}
+
 /// Render md_text as markdown. Warns the user if there are difference in
 /// rendering between Pulldown and Hoedown.
 fn render_markdown(w: &mut fmt::Formatter,
                    md_text: &str,
+                   links: &[(String, String)],
                    span: Span,
                    render_type: RenderType,
                    prefix: &str,
@@ -1799,9 +2001,9 @@ fn render_markdown(w: &mut fmt::Formatter,
         // Save the state of USED_ID_MAP so it only gets updated once even
         // though we're rendering twice.
         let orig_used_id_map = USED_ID_MAP.with(|map| map.borrow().clone());
-        let hoedown_output = format!("{}", Markdown(md_text, RenderType::Hoedown));
+        let hoedown_output = format!("{}", Markdown(md_text, links, RenderType::Hoedown));
         USED_ID_MAP.with(|map| *map.borrow_mut() = orig_used_id_map);
-        let pulldown_output = format!("{}", Markdown(md_text, RenderType::Pulldown));
+        let pulldown_output = format!("{}", Markdown(md_text, links, RenderType::Pulldown));
         let mut differences = html_diff::get_differences(&pulldown_output, &hoedown_output);
         differences.retain(|s| {
             match *s {
@@ -1821,12 +2023,27 @@ fn render_markdown(w: &mut fmt::Formatter,
 
         pulldown_output
     } else {
-        format!("{}", Markdown(md_text, RenderType::Hoedown))
+        format!("{}", Markdown(md_text, links, RenderType::Hoedown))
     };
 
     write!(w, "<div class='docblock'>{}{}</div>", prefix, output)
 }
 
+/// Returns the URLs of the pages of the items the intra-doc links of `item`
+/// were resolved to, along with the link targets as written in its
+/// documentation.
+fn intra_links(item: &clean::Item) -> Vec<(String, String)> {
+    item.attrs.links.iter().filter_map(|&(ref link, def_id, ref fragment)| {
+        href(def_id).map(|(mut url, ..)| {
+            if let Some(ref fragment) = *fragment {
+                url.push('#');
+                url.push_str(fragment);
+            }
+            (link.clone(), url)
+        })
+    }).collect()
+}
+
 fn document_short(w: &mut fmt::Formatter, item: &clean::Item, link: AssocItemLink,
                   cx: &Context, prefix: &str) -> fmt::Result {
     if let Some(s) = item.doc_value() {
@@ -1836,7 +2053,8 @@ fn document_short(w: &mut fmt::Formatter, item: &clean::Item, link: AssocItemLin
         } else {
             format!("{}", &plain_summary_line(Some(s)))
         };
-        render_markdown(w, &markdown, item.source.clone(), cx.render_type, prefix, &cx.shared)?;
+        render_markdown(w, &markdown, &intra_links(item), item.source.clone(), cx.render_type,
+                        prefix, &cx.shared)?;
     } else if !prefix.is_empty() {
         write!(w, "<div class='docblock'>{}</div>", prefix)?;
     }
@@ -1861,7 +2079,8 @@ fn render_assoc_const_value(item: &clean::Item) -> String {
 fn document_full(w: &mut fmt::Formatter, item: &clean::Item,
                  cx: &Context, prefix: &str) -> fmt::Result {
     if let Some(s) = item.doc_value() {
-        render_markdown(w, s, item.source.clone(), cx.render_type, prefix, &cx.shared)?;
+        render_markdown(w, s, &intra_links(item), item.source.clone(), cx.render_type, prefix,
+                        &cx.shared)?;
     } else if !prefix.is_empty() {
         write!(w, "<div class='docblock'>{}</div>", prefix)?;
     }
@@ -2085,6 +2304,7 @@ fn item_module(w: &mut fmt::Formatter, cx: &Context,
                        docs = if cx.render_type == RenderType::Hoedown {
                            format!("{}",
                                    shorter(Some(&Markdown(doc_value,
+                                                          &intra_links(myitem),
                                                           RenderType::Hoedown).to_string())))
                        } else {
                            format!("{}", MarkdownSummaryLine(doc_value))
@@ -2250,7 +2470,8 @@ fn item_function(w: &mut fmt::Formatter, cx: &Context, it: &clean::Item,
                name_len,
                indent: 0,
            })?;
-    document(w, cx, it)
+    document(w, cx, it)?;
+    render_scraped_examples(w, cx, &full_path(cx, it))
 }
 
 fn item_trait(w: &mut fmt::Formatter, cx: &Context, it: &clean::Item,
@@ -2365,7 +2586,8 @@ fn item_trait(w: &mut fmt::Formatter, cx: &Context, it: &clean::Item,
         render_stability_since(w, m, t)?;
         write!(w, "</span></h3>")?;
         document(w, cx, m)?;
-        Ok(())
+        let path = format!("{}::{}", full_path(cx, t), name);
+        render_scraped_examples(w, cx, &path)
     }
 
     if !types.is_empty() {
@@ -3188,14 +3410,16 @@ fn render_impl(w: &mut fmt::Formatter, cx: &Context, i: &Impl, link: AssocItemLi
         write!(w, "</span>")?;
         write!(w, "</h3>\n")?;
         if let Some(ref dox) = i.impl_item.doc_value() {
-            write!(w, "<div class='docblock'>{}</div>", Markdown(dox, cx.render_type))?;
+            write!(w, "<div class='docblock'>{}</div>",
+                   Markdown(dox, &intra_links(&i.impl_item), cx.render_type))?;
         }
     }
 
     fn doc_impl_item(w: &mut fmt::Formatter, cx: &Context, item: &clean::Item,
                      link: AssocItemLink, render_mode: RenderMode,
                      is_default_item: bool, outer_version: Option<&str>,
-                     trait_: Option<&clean::Trait>, show_def_docs: bool) -> fmt::Result {
+                     trait_: Option<&clean::Trait>, show_def_docs: bool,
+                     type_path: Option<&str>) -> fmt::Result {
         let item_type = item.type_();
         let name = item.name.as_ref().unwrap();
 
@@ -3300,6 +3524,10 @@ fn render_impl(w: &mut fmt::Formatter, cx: &Context, i: &Impl, link: AssocItemLi
                     document_stability(w, cx, item)?;
                     if show_def_docs {
                         document_full(w, item, cx, &prefix)?;
+                        if let Some(type_path) = type_path {
+                            let path = format!("{}::{}", type_path, name);
+                            render_scraped_examples(w, cx, &path)?;
+                        }
                     }
                 }
             } else {
@@ -3319,10 +3547,22 @@ fn render_impl(w: &mut fmt::Formatter, cx: &Context, i: &Impl, link: AssocItemLi
         write!(w, "<span class='docblock autohide'>")?;
     }
 
+    // Scraped calls are only shown for the methods of inherent impls, those
+    // of trait impls are shown with the trait.
+    let type_path = match (render_mode, &i.inner_impl().trait_) {
+        (RenderMode::Normal, &None) => {
+            i.inner_impl().for_.def_id()
+                .and_then(|did| cache().paths.get(&did))
+                .map(|&(ref fqp, _)| fqp.join("::"))
+        }
+        _ => None,
+    };
+
     write!(w, "<div class='impl-items'>")?;
     for trait_item in &i.inner_impl().items {
         doc_impl_item(w, cx, trait_item, link, render_mode,
-                      false, outer_version, trait_, show_def_docs)?;
+                      false, outer_version, trait_, show_def_docs,
+                      type_path.as_ref().map(|s| &**s))?;
     }
 
     fn render_default_items(w: &mut fmt::Formatter,
@@ -3341,7 +3581,7 @@ fn render_impl(w: &mut fmt::Formatter, cx: &Context, i: &Impl, link: AssocItemLi
             let assoc_link = AssocItemLink::GotoSource(did, &i.provided_trait_methods);
 
             doc_impl_item(w, cx, trait_item, assoc_link, render_mode, true,
-                          outer_version, None, show_def_docs)?;
+                          outer_version, None, show_def_docs, None)?;
         }
         Ok(())
     }
@@ -3671,7 +3911,7 @@ fn sidebar_module(fmt: &mut fmt::Formatter, _it: &clean::Item,
 
 impl<'a> fmt::Display for Source<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        let Source(s) = *self;
+        let Source(s, links) = *self;
         let lines = s.lines().count();
         let mut cols = 0;
         let mut tmp = lines;
@@ -3684,8 +3924,7 @@ impl<'a> fmt::Display for Source<'a> {
             write!(fmt, "<span id=\"{0}\">{0:1$}</span>\n", i, cols)?;
         }
         write!(fmt, "</pre>")?;
-        write!(fmt, "{}",
-               highlight::render_with_highlighting(s, None, None, None, None))?;
+        write!(fmt, "{}", highlight::render_with_links(s, links))?;
         Ok(())
     }
 }
@@ -3731,7 +3970,26 @@ fn get_index_search_type(item: &clean::Item) -> Option<IndexItemFunctionType> {
 }
 
 fn get_index_type(clean_type: &clean::Type) -> Type {
-    Type { name: get_index_type_name(clean_type).map(|s| s.to_ascii_lowercase()) }
+    Type {
+        name: get_index_type_name(clean_type).map(|s| s.to_ascii_lowercase()),
+        generics: get_index_type_generics(clean_type),
+    }
+}
+
+fn get_index_type_generics(clean_type: &clean::Type) -> Vec<Type> {
+    match *clean_type {
+        clean::ResolvedPath { ref path, .. } => {
+            let segments = &path.segments;
+            match segments[segments.len() - 1].params {
+                clean::PathParameters::AngleBracketed { ref types, .. } => {
+                    types.iter().map(get_index_type).filter(|t| t.name.is_some()).collect()
+                }
+                clean::PathParameters::Parenthesized { .. } => Vec::new(),
+            }
+        },
+        clean::BorrowedRef { ref type_, .. } => get_index_type_generics(type_),
+        _ => Vec::new()
+    }
 }
 
 fn get_index_type_name(clean_type: &clean::Type) -> Option<String> {
@@ -306,8 +306,13 @@
         };
     })();
 
-    function initSearch(rawSearchIndex) {
+    function initSearch(rawSearchIndex, rawFullTextIndex) {
         var currentResults, index, searchIndex;
+        // the position in `searchIndex` of the first item of each crate
+        var crateOffsets = {};
+        // the position in `searchIndex` of the first trait implementation,
+        // which come after the items of all crates
+        var implsOffset;
         var MAX_LEV_DISTANCE = 3;
         var params = getQueryStringParams();
 
@@ -366,7 +371,21 @@
 
             // quoted values mean literal search
             var nSearchWords = searchWords.length;
-            if ((val.charAt(0) === "\"" || val.charAt(0) === "'") &&
+            if (query.type === "impl") {
+                // the types implementing a trait, in every crate
+                for (var i = implsOffset; i < searchIndex.length; ++i) {
+                    var traitPath = searchIndex[i].trait.toLowerCase();
+                    if (traitPath === valLower ||
+                        traitPath.substr(traitPath.length - valLower.length - 2) ===
+                            "::" + valLower) {
+                        results.push({id: i, index: -1, dontValidate: true,
+                                      word: searchIndex[i].name.toLowerCase()});
+                    }
+                    if (results.length === max) {
+                        break;
+                    }
+                }
+            } else if ((val.charAt(0) === "\"" || val.charAt(0) === "'") &&
                 val.charAt(val.length - 1) === val.charAt(0))
             {
                 val = val.substr(1, val.length - 2);
@@ -385,10 +404,8 @@
             } else if (val.search("->") > -1) {
                 var trimmer = function (s) { return s.trim(); };
                 var parts = val.split("->").map(trimmer);
-                var input = parts[0];
-                // sort inputs so that order does not matter
-                var inputs = input.split(",").map(trimmer).sort().toString();
-                var output = parts[1];
+                var inputs = parts[0] === "*" ? null : parseTypes(parts[0]);
+                var output = parts[1] === "*" ? null : parseTypes(parts[1]);
 
                 for (var i = 0; i < nSearchWords; ++i) {
                     var type = searchIndex[i].type;
@@ -396,15 +413,10 @@
                         continue;
                     }
 
-                    // sort index inputs so that order does not matter
-                    var typeInputs = type.inputs.map(function (input) {
-                        return input.name;
-                    }).sort();
-
                     // allow searching for void (no output) functions as well
-                    var typeOutput = type.output ? type.output.name : "";
-                    if ((inputs === "*" || inputs === typeInputs.toString()) &&
-                        (output === "*" || output == typeOutput)) {
+                    var typeOutput = type.output ? [type.output] : [];
+                    if ((inputs === null || matchTypes(inputs, type.inputs)) &&
+                        (output === null || matchTypes(output, typeOutput))) {
                         results.push({id: i, index: -1, dontValidate: true});
                     }
                 }
@@ -443,11 +455,21 @@
                         }
                     }
                 }
+
+                // then the items with every word of the query in their docs
+                var words = valLower.split(/[^\w]+/).filter(function (word) {
+                    return word.length > 2;
+                });
+                searchFullText(words, function (ty) {
+                    return typePassesFilter(typeFilter, ty);
+                }, results, max);
             }
 
             var nresults = results.length;
             for (var i = 0; i < nresults; ++i) {
-                results[i].word = searchWords[results[i].id];
+                if (results[i].word === undefined) {
+                    results[i].word = searchWords[results[i].id];
+                }
                 results[i].item = searchIndex[results[i].id] || {};
             }
             // if there are no results then return to default and fail
@@ -549,6 +571,138 @@
             return results;
         }
 
+        /**
+         * Parses a comma-separated list of types, which may have generic
+         * arguments, such as `vec<t>, &str`.
+         * @param  {[string]} s [The types to parse]
+         * @return {[Array]}    [The types as objects with a name and generics]
+         */
+        function parseTypes(s) {
+            var types = [];
+            var stack = [{generics: types}];
+            var name = "";
+            for (var i = 0; i <= s.length; ++i) {
+                var c = s.charAt(i);
+                if (c === "<" || c === ">" || c === "," || i === s.length) {
+                    // references aren't part of the types in the index
+                    name = name.trim().replace(/^(&\s*(mut\s+)?)+/, "");
+                    var list = stack[stack.length - 1].generics;
+                    if (name !== "") {
+                        list.push({name: name, generics: []});
+                    }
+                    name = "";
+                    if (c === "<" && list.length > 0) {
+                        stack.push(list[list.length - 1]);
+                    } else if (c === ">" && stack.length > 1) {
+                        stack.pop();
+                    }
+                } else {
+                    name += c;
+                }
+            }
+            return types;
+        }
+
+        /**
+         * Checks whether a type of the search index matches a type of the
+         * query: the name has to be the same, unless the query has `*`, and
+         * each generic argument of the query has to match one of the type's,
+         * in order. Generic arguments left out of the query match anything.
+         * @param  {[Object]} query [The type in the query]
+         * @param  {[Object]} type  [The type in the search index]
+         * @return {[boolean]}      [Whether the type matches]
+         */
+        function matchType(query, type) {
+            if (query.name !== "*" && query.name !== type.name) {
+                return false;
+            }
+            var generics = type.generics || [];
+            var j = 0;
+            for (var i = 0; i < query.generics.length; ++i) {
+                while (j < generics.length && !matchType(query.generics[i], generics[j])) {
+                    ++j;
+                }
+                if (j === generics.length) {
+                    return false;
+                }
+                ++j;
+            }
+            return true;
+        }
+
+        /**
+         * Checks whether each type of the query matches a different type of
+         * the search index, in any order.
+         * @param  {[Array]} queries [The types in the query]
+         * @param  {[Array]} types   [The types in the search index]
+         * @return {[boolean]}       [Whether the types match]
+         */
+        function matchTypes(queries, types) {
+            if (queries.length !== types.length) {
+                return false;
+            }
+            var used = [];
+            for (var i = 0; i < queries.length; ++i) {
+                var found = false;
+                for (var j = 0; j < types.length && !found; ++j) {
+                    if (used.indexOf(j) === -1 && matchType(queries[i], types[j])) {
+                        used.push(j);
+                        found = true;
+                    }
+                }
+                if (!found) {
+                    return false;
+                }
+            }
+            return true;
+        }
+
+        /**
+         * Adds the items with all of the given words in their documentation to
+         * the results, after the others, using the full-text indexes of the
+         * crates which have one.
+         * @param  {[Array]}    words   [The lowercased words to look for]
+         * @param  {[Function]} filter  [Whether to keep items of a given type]
+         * @param  {[Array]}    results [The results so far]
+         * @param  {[Number]}   max     [The maximum number of results]
+         */
+        function searchFullText(words, filter, results, max) {
+            if (words.length === 0) {
+                return;
+            }
+            var found = {};
+            for (var i = 0; i < results.length; ++i) {
+                found[results[i].id] = true;
+            }
+            for (var crate in rawFullTextIndex) {
+                if (!rawFullTextIndex.hasOwnProperty(crate) ||
+                    !crateOffsets.hasOwnProperty(crate)) {
+                    continue;
+                }
+                var crateIndex = rawFullTextIndex[crate];
+                var ids = null;
+                for (var i = 0; i < words.length; ++i) {
+                    var postings = crateIndex.hasOwnProperty(words[i]) ? crateIndex[words[i]] : [];
+                    ids = ids === null ? postings : ids.filter(function (id) {
+                        return postings.indexOf(id) !== -1;
+                    });
+                }
+                for (var i = 0; i < ids.length && results.length < max; ++i) {
+                    var id = crateOffsets[crate] + ids[i];
+                    if (!found[id] && filter(searchIndex[id].ty)) {
+                        found[id] = true;
+                        results.push({
+                            id: id,
+                            index: -1,
+                            // we want these to go lower than all name matches
+                            lev: MAX_LEV_DISTANCE + 1,
+                            dontValidate: true,
+                        });
+                    }
+                }
+            }
+        }
+
         /**
          * Validate performs the following boolean logic. For example:
          * "File::open" will give IF A PARENT EXISTS => ("file" && "open")
@@ -589,7 +743,7 @@
                 document.getElementsByClassName('search-input')[0].value;
             query = raw;
 
-            matches = query.match(/^(fn|mod|struct|enum|trait|type|const|macro)\s*:\s*/i);
+            matches = query.match(/^(fn|mod|struct|enum|trait|impl|type|const|macro)\s*:\s*/i);
             if (matches) {
                 type = matches[1].replace(/^const$/, 'constant');
                 query = query.substring(matches[0].length);
@@ -844,6 +998,7 @@
                     desc: rawSearchIndex[crate].doc,
                     type: null,
                 });
+                crateOffsets[crate] = searchIndex.length;
 
                 // an array of [(Number) item type,
                 //              (String) name,
@@ -886,6 +1041,23 @@
                     lastPath = row.path;
                 }
             }
+
+            // an array of [(String) full path of the trait,
+            //              (Number) item type of the implementing type,
+            //              (String) path of the implementing type,
+            //              (String) name of the implementing type]
+            //
+            // These aren't searched by name, so they get no search words.
+            implsOffset = searchIndex.length;
+            for (var crate in rawSearchIndex) {
+                if (!rawSearchIndex.hasOwnProperty(crate)) { continue; }
+                var impls = rawSearchIndex[crate].impls || [];
+                for (var i = 0; i < impls.length; ++i) {
+                    searchIndex.push({crate: crate, ty: impls[i][1], name: impls[i][3],
+                                      path: impls[i][2], desc: "impl " + impls[i][0],
+                                      type: null, trait: impls[i][0]});
+                }
+            }
             return searchWords;
         }
 
@@ -0,0 +1,160 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Rustdoc's JSON backend
+//!
+//! This serializes the cleaned crate, after all passes have run, into a single
+//! `<crate>.json` file for consumption by other tools. The item tree is the
+//! `clean` model as is; it's accompanied by indexes of the paths of all items
+//! it refers to, of the trait implementations it contains and of the external
+//! crates it mentions, so that references by `DefId` can be resolved without
+//! walking the whole tree.
+//!
+//! `FORMAT_VERSION` has to be bumped whenever the shape of the output changes,
+//! including changes to the `clean` types themselves.
+
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+
+use rustc::hir::def_id::{CrateNum, DefId};
+use serialize::json;
+
+use clean::{self, GetDefId};
+use html::item_type::ItemType;
+use html::render::RenderInfo;
+
+/// The version of the JSON format produced, found at the top level of the
+/// output as `format_version`.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(RustcEncodable)]
+struct JsonCrate<'a> {
+    format_version: u32,
+    name: &'a str,
+    root: &'a Option<clean::Item>,
+    paths: Vec<PathEntry>,
+    impls: Vec<ImplEntry>,
+    external_crates: Vec<ExternalCrateEntry<'a>>,
+    external_traits: Vec<ExternalTraitEntry<'a>>,
+    primitives: &'a [(DefId, clean::PrimitiveType, clean::Attributes)],
+}
+
+/// The fully qualified path of an item, local or not.
+#[derive(RustcEncodable)]
+struct PathEntry {
+    def_id: DefId,
+    path: Vec<String>,
+    kind: &'static str,
+}
+
+/// A trait or inherent impl found in the item tree, by `DefId` of the impl
+/// item, the implemented trait and the implementing type, where known.
+#[derive(RustcEncodable)]
+struct ImplEntry {
+    def_id: DefId,
+    trait_: Option<DefId>,
+    for_: Option<DefId>,
+}
+
+#[derive(RustcEncodable)]
+struct ExternalCrateEntry<'a> {
+    crate_num: CrateNum,
+    name: &'a str,
+}
+
+#[derive(RustcEncodable)]
+struct ExternalTraitEntry<'a> {
+    def_id: DefId,
+    trait_: &'a clean::Trait,
+}
+
+pub fn run(krate: clean::Crate, dst: PathBuf, renderinfo: RenderInfo) -> io::Result<()> {
+    let mut paths = Vec::new();
+    let mut impls = Vec::new();
+    if let Some(ref root) = krate.module {
+        collect(root, &mut Vec::new(), &mut paths, &mut impls);
+    }
+    for (&def_id, &(ref path, kind)) in &renderinfo.external_paths {
+        paths.push(PathEntry {
+            def_id,
+            path: path.clone(),
+            kind: ItemType::from(kind).css_class(),
+        });
+    }
+    // Hash map iteration order is unspecified, keep the output stable.
+    paths.sort_by(|a, b| a.path.cmp(&b.path).then(a.def_id.cmp(&b.def_id)));
+
+    let mut external_traits = krate.external_traits.iter().map(|(&def_id, trait_)| {
+        ExternalTraitEntry { def_id, trait_ }
+    }).collect::<Vec<_>>();
+    external_traits.sort_by_key(|t| t.def_id);
+
+    let json_crate = JsonCrate {
+        format_version: FORMAT_VERSION,
+        name: &krate.name,
+        root: &krate.module,
+        paths,
+        impls,
+        external_crates: krate.externs.iter().map(|&(crate_num, ref ext)| {
+            ExternalCrateEntry { crate_num, name: &ext.name }
+        }).collect(),
+        external_traits,
+        primitives: &krate.primitives,
+    };
+
+    ::std::fs::create_dir_all(&dst)?;
+    let mut out = BufWriter::new(File::create(dst.join(&format!("{}.json", krate.name)))?);
+    write!(out, "{}", json::as_json(&json_crate))?;
+    out.flush()
+}
+
+/// Records the path of every named item reachable through modules, and every
+/// impl, below `item`.
+fn collect(item: &clean::Item,
+           path: &mut Vec<String>,
+           paths: &mut Vec<PathEntry>,
+           impls: &mut Vec<ImplEntry>) {
+    match item.inner {
+        clean::StrippedItem(..) => return,
+        clean::ImplItem(ref i) => {
+            impls.push(ImplEntry {
+                def_id: item.def_id,
+                trait_: i.trait_.def_id(),
+                for_: i.for_.def_id(),
+            });
+            return;
+        }
+        _ => {}
+    }
+
+    let name = match item.name {
+        Some(ref name) if !name.is_empty() => name,
+        _ => return,
+    };
+    path.push(name.clone());
+    match item.inner {
+        clean::ExternCrateItem(..) | clean::ImportItem(..) => {}
+        _ => {
+            paths.push(PathEntry {
+                def_id: item.def_id,
+                path: path.clone(),
+                kind: ItemType::from(item).css_class(),
+            });
+        }
+    }
+    if let clean::ModuleItem(ref m) = item.inner {
+        for item in &m.items {
+            collect(item, path, paths, impls);
+        }
+    }
+    path.pop();
+}
@@ -39,6 +39,7 @@ extern crate rustc_resolve;
 extern crate rustc_lint;
 extern crate rustc_back;
 extern crate rustc_metadata;
+extern crate rustc_save_analysis;
 extern crate rustc_typeck;
 extern crate serialize;
 #[macro_use] extern crate syntax;
@@ -71,6 +72,7 @@ pub mod externalfiles;
 
 pub mod clean;
 pub mod core;
+pub mod coverage;
 pub mod doctree;
 pub mod fold;
 pub mod html {
@@ -83,9 +85,13 @@ pub mod html {
     pub mod render;
     pub mod toc;
 }
+pub mod json;
+pub mod lints;
 pub mod markdown;
 pub mod passes;
 pub mod plugins;
+pub mod scrape;
+pub mod source_links;
 pub mod visit_ast;
 pub mod visit_lib;
 pub mod test;
@@ -139,7 +145,7 @@ pub fn opts() -> Vec<RustcOptGroup> {
                      "[rust]")
         }),
         stable("w", |o| {
-            o.optopt("w", "output-format", "the output type to write", "[html]")
+            o.optopt("w", "output-format", "the output type to write", "[html|json]")
         }),
         stable("o", |o| o.optopt("o", "output", "where to place the output", "PATH")),
         stable("crate-name", |o| {
@@ -243,6 +249,33 @@ pub fn opts() -> Vec<RustcOptGroup> {
         unstable("display-warnings", |o| {
             o.optflag("", "display-warnings", "to print code warnings when testing doc")
         }),
+        unstable("enable-full-text-search", |o| {
+            o.optflag("", "enable-full-text-search",
+                      "also index the words of the documentation for the search")
+        }),
+        unstable("show-coverage", |o| {
+            o.optflag("", "show-coverage",
+                      "print how many public items are documented and have examples, \
+                       as a table or as JSON with `--output-format json`")
+        }),
+        unstable("doctest-cache-dir", |o| {
+            o.optopt("", "doctest-cache-dir",
+                     "with --test, record the doctests which pass in DIR and skip them, \
+                      as cached, while their source, the libraries and compiler they \
+                      may use and the options are unchanged",
+                     "DIR")
+        }),
+        unstable("generate-link-to-definition", |o| {
+            o.optflag("", "generate-link-to-definition",
+                      "link the paths, method calls and field accesses of the source view \
+                       to their definitions")
+        }),
+        unstable("scrape-examples", |o| {
+            o.optmulti("", "scrape-examples",
+                       "show the calls of the crate's functions and methods found in FILE, \
+                        such as an example or a test, in their documentation",
+                       "FILE")
+        }),
     ]
 }
 
@@ -354,15 +387,16 @@ pub fn main_args(args: &[String]) -> isize {
     let playground_url = matches.opt_str("playground-url");
     let maybe_sysroot = matches.opt_str("sysroot").map(PathBuf::from);
     let display_warnings = matches.opt_present("display-warnings");
+    let doctest_cache_dir = matches.opt_str("doctest-cache-dir").map(PathBuf::from);
 
     match (should_test, markdown_input) {
         (true, true) => {
             return markdown::test(input, cfgs, libs, externs, test_args, maybe_sysroot, render_type,
-                                  display_warnings)
+                                  display_warnings, doctest_cache_dir)
         }
         (true, false) => {
             return test::run(input, cfgs, libs, externs, test_args, crate_name, maybe_sysroot,
-                             render_type, display_warnings)
+                             render_type, display_warnings, doctest_cache_dir)
         }
         (false, true) => return markdown::render(input,
                                                  output.unwrap_or(PathBuf::from("doc")),
@@ -373,8 +407,19 @@ pub fn main_args(args: &[String]) -> isize {
     }
 
     let output_format = matches.opt_str("w");
+    if output_format.as_ref().map(|s| &**s) == Some("json") &&
+       !nightly_options::is_unstable_enabled(&matches) {
+        print_error("the JSON output format is unstable, pass `-Z unstable-options` to use it");
+        return 1;
+    }
+    let show_coverage = matches.opt_present("show-coverage");
+    let full_text_search = matches.opt_present("enable-full-text-search");
     let res = acquire_input(input, externs, &matches, move |out| {
         let Output { krate, passes, renderinfo } = out;
+        if show_coverage {
+            coverage::run(&krate, output_format.as_ref().map(|s| &**s) == Some("json"));
+            return 0;
+        }
         info!("going to format");
         match output_format.as_ref().map(|s| &**s) {
             Some("html") | None => {
@@ -383,7 +428,13 @@ pub fn main_args(args: &[String]) -> isize {
                                   passes.into_iter().collect(),
                                   css_file_extension,
                                   renderinfo,
-                                  render_type)
+                                  render_type,
+                                  full_text_search)
+                    .expect("failed to generate documentation");
+                0
+            }
+            Some("json") => {
+                json::run(krate, output.unwrap_or(PathBuf::from("doc")), renderinfo)
                     .expect("failed to generate documentation");
                 0
             }
@@ -461,10 +512,12 @@ where R: 'static + Send, F: 'static + Send + FnOnce(Output) -> R {
     let maybe_sysroot = matches.opt_str("sysroot").map(PathBuf::from);
     let crate_name = matches.opt_str("crate-name");
     let plugin_path = matches.opt_str("plugin-path");
+    let scrape_files = matches.opt_strs("scrape-examples");
 
     let cr = PathBuf::from(cratefile);
     info!("starting to run rustc");
     let display_warnings = matches.opt_present("display-warnings");
+    let generate_link_to_definition = matches.opt_present("generate-link-to-definition");
 
     let force_unstable_if_unmarked = matches.opt_strs("Z").iter().any(|x| {
         *x == "force-unstable-if-unmarked"
@@ -474,9 +527,18 @@ where R: 'static + Send, F: 'static + Send + FnOnce(Output) -> R {
     rustc_driver::monitor(move || {
         use rustc::session::config::Input;
 
-        let (mut krate, renderinfo) =
+        // The scraped files are compiled with the same options as the crate.
+        let scrape_options = if scrape_files.is_empty() {
+            None
+        } else {
+            Some((paths.clone(), cfgs.clone(), externs.clone(), triple.clone(),
+                  maybe_sysroot.clone()))
+        };
+
+        let (mut krate, mut renderinfo) =
             core::run_core(paths, cfgs, externs, Input::File(cr), triple, maybe_sysroot,
-                           display_warnings, force_unstable_if_unmarked);
+                           display_warnings, force_unstable_if_unmarked,
+                           generate_link_to_definition);
 
         info!("finished with rustc");
 
@@ -484,6 +546,13 @@ where R: 'static + Send, F: 'static + Send + FnOnce(Output) -> R {
             krate.name = name
         }
 
+        if let Some((paths, cfgs, externs, triple, maybe_sysroot)) = scrape_options {
+            info!("scraping examples");
+            renderinfo.scraped_examples = scrape::scrape_examples(&scrape_files, &krate.name,
+                                                                  paths, cfgs, externs,
+                                                                  triple, maybe_sysroot);
+        }
+
         // Process all of the crate attributes, extracting plugin metadata along
         // with the passes which we are supposed to run.
         for attr in krate.module.as_ref().unwrap().attrs.lists("doc") {
@@ -0,0 +1,237 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Lints for problems in documentation
+//!
+//! The lints are declared along with the compiler's builtin ones so that the
+//! usual lint attributes and flags accept them, but they're checked here,
+//! except for `intra_doc_link_resolution_failure`, which is reported while
+//! resolving the links (see `clean::intra_links`).
+//! Their levels come from the compiler's lint levels like any other lint's,
+//! except that rustdoc's cap on the compiler's lints doesn't apply to them
+//! (see `core::run_core`).
+
+use rustc::hir::{self, intravisit};
+use rustc::lint::Lint;
+use rustc::lint::builtin::{EMPTY_DOCS, INVALID_CODEBLOCK_ATTRIBUTES, INVALID_DOC_ATTRIBUTES};
+use rustc::lint::builtin::{INTRA_DOC_LINK_RESOLUTION_FAILURE, UNCLOSED_DOC_CODE_BLOCKS};
+use syntax::ast;
+use syntax_pos::{BytePos, Span};
+
+use core::DocContext;
+use html::markdown::{self, DocProblem};
+
+/// The arguments of `#[doc(...)]` used by rustdoc or the compiler.
+const DOC_ATTRIBUTES: &[&str] = &[
+    "cfg", "hidden", "html_favicon_url", "html_logo_url", "html_no_source",
+    "html_playground_url", "html_root_url", "inline", "issue_tracker_base_url", "masked",
+    "no_default_passes", "no_inline", "passes", "plugins", "primitive", "test",
+];
+
+/// The lints checked by rustdoc.
+pub const DOC_LINTS: &[&Lint] = &[
+    EMPTY_DOCS, INVALID_CODEBLOCK_ATTRIBUTES, INVALID_DOC_ATTRIBUTES, UNCLOSED_DOC_CODE_BLOCKS,
+    INTRA_DOC_LINK_RESOLUTION_FAILURE,
+];
+
+/// Checks the documentation of every item in the crate, reporting any problems.
+pub fn check_crate(cx: &DocContext) {
+    let krate = cx.tcx.hir.krate();
+    let mut checker = DocLintChecker { cx, ids: vec![] };
+    checker.check(ast::CRATE_NODE_ID, &krate.attrs, |this| intravisit::walk_crate(this, krate));
+}
+
+struct DocLintChecker<'a, 'tcx: 'a, 'rcx: 'a> {
+    cx: &'a DocContext<'a, 'tcx, 'rcx>,
+    /// The items being checked, innermost last.
+    ids: Vec<ast::NodeId>,
+}
+
+/// A line of documentation, with the span of the attribute it comes from and
+/// its offset in that attribute's source, if it appears there verbatim.
+struct DocLine<'a> {
+    span: Span,
+    text: &'a str,
+    offset: Option<usize>,
+}
+
+impl<'a, 'tcx, 'rcx> DocLintChecker<'a, 'tcx, 'rcx> {
+    fn check<F>(&mut self, id: ast::NodeId, attrs: &'tcx [ast::Attribute], walk: F)
+        where F: FnOnce(&mut Self)
+    {
+        self.ids.push(id);
+        self.check_docs(attrs);
+        walk(self);
+        self.ids.pop();
+    }
+
+    fn check_docs(&self, attrs: &[ast::Attribute]) {
+        // The doc strings with the span of the attribute they come from.
+        let mut docs = vec![];
+        for attr in attrs.iter().filter(|a| a.check_name("doc")) {
+            if attr.is_sugared_doc {
+                if let Some(doc) = attr.with_desugared_doc(|a| a.value_str()) {
+                    docs.push((attr.span, doc.as_str().to_string()));
+                }
+                continue;
+            }
+            let meta = match attr.meta() {
+                Some(meta) => meta,
+                None => {
+                    self.lint(INVALID_DOC_ATTRIBUTES, attr.span, "malformed `doc` attribute",
+                              None);
+                    continue;
+                }
+            };
+            match meta.node {
+                ast::MetaItemKind::Word => {
+                    self.lint(INVALID_DOC_ATTRIBUTES, attr.span,
+                              "`doc` attribute without a value or arguments",
+                              Some("use `#[doc = \"...\"]` or `#[doc(...)]`"));
+                }
+                ast::MetaItemKind::NameValue(ref lit) => {
+                    if let ast::LitKind::Str(doc, _) = lit.node {
+                        docs.push((attr.span, doc.as_str().to_string()));
+                    } else {
+                        self.lint(INVALID_DOC_ATTRIBUTES, lit.span,
+                                  "the value of a `doc` attribute must be a string", None);
+                    }
+                }
+                ast::MetaItemKind::List(ref items) => {
+                    for item in items {
+                        match item.name() {
+                            Some(name) => {
+                                let name = name.as_str();
+                                if !DOC_ATTRIBUTES.contains(&&*name) {
+                                    self.lint(INVALID_DOC_ATTRIBUTES, item.span,
+                                              &format!("unknown `doc` attribute `{}`", name),
+                                              None);
+                                }
+                            }
+                            None => {
+                                self.lint(INVALID_DOC_ATTRIBUTES, item.span,
+                                          "the arguments of a `doc` attribute must be names",
+                                          None);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if docs.is_empty() {
+            return;
+        }
+        if docs.iter().all(|&(_, ref doc)| doc.trim().is_empty()) {
+            let sp = docs[0].0.to(docs[docs.len() - 1].0);
+            self.lint(EMPTY_DOCS, sp, "empty doc comment", None);
+            return;
+        }
+
+        // The doc strings are joined by newlines, as by the `collapse-docs`
+        // pass, remembering where each line comes from.
+        let codemap = self.cx.sess().codemap();
+        let mut lines = vec![];
+        for &(span, ref doc) in &docs {
+            let start = lines.len();
+            let snippet = codemap.span_to_snippet(span).ok();
+            let mut pos = 0;
+            for text in doc.lines() {
+                let offset = snippet.as_ref().and_then(|s| s[pos..].find(text)).map(|i| pos + i);
+                if let Some(offset) = offset {
+                    pos = offset + text.len();
+                }
+                lines.push(DocLine { span, text, offset });
+            }
+            if lines.len() == start {
+                lines.push(DocLine { span, text: "", offset: None });
+            }
+        }
+        let md = lines.iter().map(|line| line.text).collect::<Vec<_>>().join("\n");
+        for problem in markdown::doc_problems(&md) {
+            match problem {
+                DocProblem::UnclosedCodeBlock(line) => {
+                    let sp = text_span(&lines[line], lines[line].text.trim());
+                    self.lint(UNCLOSED_DOC_CODE_BLOCKS, sp, "unclosed code block",
+                              Some("the rest of the documentation is part of the code block"));
+                }
+                DocProblem::MisspelledCodeBlockAttribute(line, token, known) => {
+                    self.lint(INVALID_CODEBLOCK_ATTRIBUTES, text_span(&lines[line], &token),
+                              &format!("unknown attribute `{}` on code block", token),
+                              Some(&format!("did you mean `{}`?", known)));
+                }
+            }
+        }
+    }
+
+    /// Reports `lint` on the item being checked, at the level the lint
+    /// attributes and flags in effect there set it to.
+    fn lint(&self, lint: &'static Lint, sp: Span, msg: &str, help: Option<&str>) {
+        let id = *self.ids.last().unwrap();
+        let mut diag = self.cx.tcx.struct_span_lint_node(lint, id, sp, msg);
+        if let Some(help) = help {
+            diag.help(help);
+        }
+        diag.emit();
+    }
+}
+
+/// Returns the span of `text` on the documentation line `line`. That's only
+/// known if the line appears verbatim in the source of its attribute, as it
+/// does in doc comments; otherwise, it's the span of the whole attribute.
+fn text_span(line: &DocLine, text: &str) -> Span {
+    match (line.offset, line.text.find(text)) {
+        (Some(offset), Some(i)) => {
+            let lo = line.span.lo() + BytePos((offset + i) as u32);
+            line.span.with_lo(lo).with_hi(lo + BytePos(text.len() as u32))
+        }
+        _ => line.span,
+    }
+}
+
+impl<'a, 'tcx, 'rcx> intravisit::Visitor<'tcx> for DocLintChecker<'a, 'tcx, 'rcx> {
+    fn nested_visit_map<'this>(&'this mut self) -> intravisit::NestedVisitorMap<'this, 'tcx> {
+        intravisit::NestedVisitorMap::All(&self.cx.tcx.hir)
+    }
+
+    fn visit_item(&mut self, item: &'tcx hir::Item) {
+        self.check(item.id, &item.attrs, |this| intravisit::walk_item(this, item));
+    }
+
+    fn visit_trait_item(&mut self, item: &'tcx hir::TraitItem) {
+        self.check(item.id, &item.attrs, |this| intravisit::walk_trait_item(this, item));
+    }
+
+    fn visit_impl_item(&mut self, item: &'tcx hir::ImplItem) {
+        self.check(item.id, &item.attrs, |this| intravisit::walk_impl_item(this, item));
+    }
+
+    fn visit_foreign_item(&mut self, item: &'tcx hir::ForeignItem) {
+        self.check(item.id, &item.attrs, |this| intravisit::walk_foreign_item(this, item));
+    }
+
+    fn visit_variant(&mut self,
+                     v: &'tcx hir::Variant,
+                     g: &'tcx hir::Generics,
+                     item_id: ast::NodeId) {
+        self.check(v.node.data.id(), &v.node.attrs,
+                   |this| intravisit::walk_variant(this, v, g, item_id));
+    }
+
+    fn visit_struct_field(&mut self, f: &'tcx hir::StructField) {
+        self.check(f.id, &f.attrs, |this| intravisit::walk_struct_field(this, f));
+    }
+
+    fn visit_macro_def(&mut self, macro_def: &'tcx hir::MacroDef) {
+        // Macros have no lint levels of their own, so those of the crate
+        // apply to them.
+        self.check(ast::CRATE_NODE_ID, &macro_def.attrs, |_| ());
+    }
+}
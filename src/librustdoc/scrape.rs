@@ -0,0 +1,304 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Finding calls of the documented crate's functions and methods in other
+//! code, such as tests and examples, for `--scrape-examples`
+//!
+//! Each file is compiled as a test crate up to type checking, using the same
+//! search paths and `--extern`s as the documented crate, which it's expected
+//! to depend on. Every call resolving to a function or method of the
+//! documented crate is recorded under the definition of the callee, which
+//! the HTML renderer looks up under the path it's documented at, to be shown
+//! in its documentation.
+
+use std::borrow::Cow;
+use std::mem;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use arena::DroplessArena;
+use errors;
+use errors::emitter::ColorConfig;
+use rustc::hir::{self, intravisit};
+use rustc::hir::def::Def;
+use rustc::hir::def_id::DefId;
+use rustc::hir::map as hir_map;
+use rustc::hir::map::DefPathHash;
+use rustc::lint;
+use rustc::session::{self, config};
+use rustc::session::config::Input;
+use rustc::session::search_paths::SearchPaths;
+use rustc::ty::{self, GlobalArenas, TyCtxt};
+use rustc::util::nodemap::FxHashMap;
+use rustc_driver::{driver, target_features};
+use rustc_lint;
+use rustc_metadata::cstore::CStore;
+use rustc_resolve as resolve;
+use rustc_trans;
+use rustc_trans::back::link;
+use syntax::codemap::CodeMap;
+use syntax::feature_gate::UnstableFeatures;
+use syntax_pos::Span;
+
+/// The number of calls kept for each function or method.
+pub const MAX_CALLS_PER_ITEM: usize = 3;
+
+/// The number of lines shown before the line of a call.
+const CONTEXT_LINES: usize = 2;
+
+/// A call of a function or method of the documented crate.
+pub struct CallSite {
+    /// The file of the call, as given on the command line.
+    pub file: String,
+    /// The line of the call, starting from one.
+    pub line: usize,
+    /// The code of the call, along with a few lines before it.
+    pub code: String,
+}
+
+/// A function or method of the documented crate, by the hash of the path of
+/// its definition. Unlike its index, that's the same in the crate's metadata
+/// as while it's documented, even if the library the examples are compiled
+/// against was built from different sources or with different `cfg`s.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum Callee {
+    Fn(DefPathHash),
+    /// A method, by the trait or type it's documented with and its name.
+    Method(DefPathHash, String),
+}
+
+/// The calls found, by function or method called.
+pub type ScrapedExamples = FxHashMap<Callee, Vec<CallSite>>;
+
+pub fn scrape_examples(files: &[String],
+                       crate_name: &str,
+                       search_paths: SearchPaths,
+                       cfgs: Vec<String>,
+                       externs: config::Externs,
+                       triple: Option<String>,
+                       maybe_sysroot: Option<PathBuf>) -> ScrapedExamples {
+    let mut examples = FxHashMap();
+    for file in files {
+        scrape_file(file, crate_name, search_paths.clone(), cfgs.clone(), externs.clone(),
+                    triple.clone(), maybe_sysroot.clone(), &mut examples);
+    }
+    examples
+}
+
+fn scrape_file(file: &str,
+               crate_name: &str,
+               search_paths: SearchPaths,
+               cfgs: Vec<String>,
+               externs: config::Externs,
+               triple: Option<String>,
+               maybe_sysroot: Option<PathBuf>,
+               examples: &mut ScrapedExamples) {
+    let input = Input::File(PathBuf::from(file));
+    let sessopts = config::Options {
+        maybe_sysroot,
+        search_paths,
+        crate_types: vec![config::CrateTypeExecutable],
+        lint_cap: Some(lint::Allow),
+        externs,
+        target_triple: triple.unwrap_or(config::host_triple().to_string()),
+        test: true,
+        unstable_features: UnstableFeatures::Allow,
+        ..config::basic_options().clone()
+    };
+
+    let codemap = Rc::new(CodeMap::new(sessopts.file_path_mapping()));
+    let diagnostic_handler = errors::Handler::with_tty_emitter(ColorConfig::Auto,
+                                                               true,
+                                                               false,
+                                                               Some(codemap.clone()));
+
+    let cstore = Rc::new(CStore::new(box rustc_trans::LlvmMetadataLoader));
+    let mut sess = session::build_session_(
+        sessopts, Some(PathBuf::from(file)), diagnostic_handler, codemap,
+    );
+    rustc_trans::init(&sess);
+    rustc_lint::register_builtins(&mut sess.lint_store.borrow_mut(), Some(&sess));
+
+    let mut cfg = config::build_configuration(&sess, config::parse_cfgspecs(cfgs));
+    target_features::add_configuration(&mut cfg, &sess);
+    sess.parse_sess.config = cfg;
+
+    let warn = |sess: &session::Session| {
+        sess.warn(&format!("not scraping examples from `{}` since it failed to compile", file));
+    };
+
+    let krate = match driver::phase_1_parse_input(&driver::CompileController::basic(),
+                                                  &sess,
+                                                  &input) {
+        Ok(krate) => krate,
+        Err(_) => return warn(&sess),
+    };
+    let name = link::find_crate_name(Some(&sess), &krate.attrs, &input);
+
+    let driver::ExpansionResult { defs, analysis, resolutions, mut hir_forest, .. } = {
+        let result = driver::phase_2_configure_and_expand(&sess,
+                                                          &cstore,
+                                                          krate,
+                                                          None,
+                                                          &name,
+                                                          None,
+                                                          resolve::MakeGlobMap::No,
+                                                          |_| Ok(()));
+        match result {
+            Ok(result) => result,
+            Err(_) => return warn(&sess),
+        }
+    };
+
+    let arena = DroplessArena::new();
+    let arenas = GlobalArenas::new();
+    let hir_map = hir_map::map_crate(&sess, &*cstore, &mut hir_forest, &defs);
+    let output_filenames = driver::build_output_filenames(&input,
+                                                          &None,
+                                                          &None,
+                                                          &[],
+                                                          &sess);
+
+    let result = driver::phase_3_run_analysis_passes(&sess,
+                                                     &*cstore,
+                                                     hir_map,
+                                                     analysis,
+                                                     resolutions,
+                                                     &arena,
+                                                     &arenas,
+                                                     &name,
+                                                     &output_filenames,
+                                                     |tcx, _, _, result| {
+        if result.is_err() {
+            return Err(());
+        }
+        let mut finder = CallFinder { tcx, crate_name, file, tables: None, examples };
+        intravisit::walk_crate(&mut finder, tcx.hir.krate());
+        Ok(())
+    });
+    match result {
+        Ok(Ok(())) => {}
+        _ => warn(&sess),
+    }
+}
+
+struct CallFinder<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    crate_name: &'a str,
+    file: &'a str,
+    /// The type-checking results of the body being visited, if any
+    tables: Option<&'tcx ty::TypeckTables<'tcx>>,
+    examples: &'a mut ScrapedExamples,
+}
+
+impl<'a, 'tcx> CallFinder<'a, 'tcx> {
+    fn record(&mut self, def_id: DefId, span: Span) {
+        if def_id.is_local() || *self.tcx.crate_name(def_id.krate).as_str() != *self.crate_name {
+            return;
+        }
+        let callee = match self.callee(def_id) {
+            Some(callee) => callee,
+            None => return,
+        };
+
+        let span = span.source_callsite();
+        let codemap = self.tcx.sess.codemap();
+        let lo = codemap.lookup_char_pos(span.lo());
+        let hi = codemap.lookup_char_pos(span.hi());
+        let calls = self.examples.entry(callee).or_insert_with(Vec::new);
+        if calls.len() >= MAX_CALLS_PER_ITEM ||
+           calls.iter().any(|call| call.file == self.file && call.line == lo.line) {
+            return;
+        }
+        // `get_line` counts from zero, the lines of `Loc`s from one.
+        let first = lo.line.saturating_sub(CONTEXT_LINES + 1);
+        let lines = (first..hi.line).filter_map(|i| lo.file.get_line(i)).collect::<Vec<_>>();
+        calls.push(CallSite {
+            file: self.file.to_owned(),
+            line: lo.line,
+            code: unindent(&lines),
+        });
+    }
+
+    /// Returns how the function or method `def_id` is looked up when it's
+    /// documented. Methods are looked up under their trait, and inherent ones
+    /// under the type they're implemented for, if that's in the documented
+    /// crate too. Methods of trait implementations are skipped, they're
+    /// documented with the trait.
+    fn callee(&self, def_id: DefId) -> Option<Callee> {
+        let tcx = self.tcx;
+        if let Some(trait_did) = tcx.trait_of_item(def_id) {
+            return Some(Callee::Method(tcx.def_path_hash(trait_did),
+                                       tcx.item_name(def_id).to_string()));
+        }
+        let impl_def_id = match tcx.impl_of_method(def_id) {
+            Some(impl_def_id) => impl_def_id,
+            None => return Some(Callee::Fn(tcx.def_path_hash(def_id))),
+        };
+        if tcx.trait_id_of_impl(impl_def_id).is_some() {
+            return None;
+        }
+        match tcx.type_of(impl_def_id).sty {
+            ty::TyAdt(adt, _) if adt.did.krate == def_id.krate => {
+                Some(Callee::Method(tcx.def_path_hash(adt.did), tcx.item_name(def_id).to_string()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Joins `lines`, removing the indentation they have in common.
+fn unindent(lines: &[Cow<str>]) -> String {
+    let indent = lines.iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_left().len())
+        .min()
+        .unwrap_or(0);
+    lines.iter().map(|line| {
+        if line.len() >= indent && line.is_char_boundary(indent) {
+            &line[indent..]
+        } else {
+            line.trim_left()
+        }
+    }).collect::<Vec<_>>().join("\n")
+}
+
+impl<'a, 'tcx> intravisit::Visitor<'tcx> for CallFinder<'a, 'tcx> {
+    fn nested_visit_map<'this>(&'this mut self) -> intravisit::NestedVisitorMap<'this, 'tcx> {
+        intravisit::NestedVisitorMap::All(&self.tcx.hir)
+    }
+
+    fn visit_nested_body(&mut self, id: hir::BodyId) {
+        let tables = mem::replace(&mut self.tables, Some(self.tcx.body_tables(id)));
+        let body = self.tcx.hir.body(id);
+        self.visit_body(body);
+        self.tables = tables;
+    }
+
+    fn visit_expr(&mut self, expr: &'tcx hir::Expr) {
+        if let Some(tables) = self.tables {
+            let def = match expr.node {
+                hir::ExprCall(ref callee, _) => match callee.node {
+                    hir::ExprPath(ref qpath) => Some(tables.qpath_def(qpath, callee.hir_id)),
+                    _ => None,
+                },
+                hir::ExprMethodCall(..) => tables.type_dependent_defs().get(expr.hir_id).cloned(),
+                _ => None,
+            };
+            match def {
+                Some(Def::Fn(def_id)) | Some(Def::Method(def_id)) => {
+                    self.record(def_id, expr.span)
+                }
+                _ => {}
+            }
+        }
+        intravisit::walk_expr(self, expr);
+    }
+}
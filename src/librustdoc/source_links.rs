@@ -0,0 +1,186 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Links from the identifiers of the crate's rendered source to their
+//! definitions, for `--generate-link-to-definition`
+//!
+//! The references are those found by save-analysis: paths, method calls and
+//! field accesses. References to the documented crate link to the line of
+//! the definition in the source view, those to other crates to the
+//! documentation of the item, wherever the rest of the documentation links
+//! it to.
+
+use rustc::hir::def::Def;
+use rustc::hir::def_id::{CrateNum, DefId, DefIndex, LOCAL_CRATE};
+use rustc::hir::map::DefPathData;
+use rustc::ty::{self, TyCtxt};
+use rustc::util::nodemap::FxHashMap;
+use rustc_save_analysis::{self as save, CallbackHandler};
+use syntax::ast;
+use syntax_pos::BytePos;
+
+use clean::TypeKind;
+use clean::inline;
+use core::DocContext;
+
+/// What an identifier of the source links to.
+pub enum LinkTarget {
+    /// A definition of the documented crate, at `line` of `file`.
+    Local { file: String, line: usize },
+    /// An item of another crate, documented on the page of the item `did`,
+    /// of kind `kind`, and, for fields, variants and associated items, at
+    /// `anchor` on it.
+    External { did: DefId, kind: TypeKind, anchor: Option<String> },
+}
+
+/// The link targets of the identifiers of each source file, by file name and
+/// byte offset of the identifier in the file.
+pub type SourceLinks = FxHashMap<String, FxHashMap<usize, LinkTarget>>;
+
+pub fn collect<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                         krate: &ast::Crate,
+                         analysis: &'a ty::CrateAnalysis,
+                         crate_name: &str) -> SourceLinks {
+    let mut links = FxHashMap();
+    let codemap = tcx.sess.codemap();
+    save::process_crate(tcx, krate, analysis, crate_name, None, CallbackHandler {
+        callback: &mut |data| {
+            let def_spans = data.defs.iter().map(|def| {
+                ((def.id.krate, def.id.index), def.span.byte_start)
+            }).collect::<FxHashMap<_, _>>();
+
+            for reference in &data.refs {
+                let id = (reference.ref_id.krate, reference.ref_id.index);
+                let target = if id.0 == LOCAL_CRATE.as_u32() {
+                    let loc = match def_spans.get(&id) {
+                        Some(&lo) => codemap.lookup_char_pos(BytePos(lo)),
+                        None => continue,
+                    };
+                    LinkTarget::Local { file: loc.file.name.clone(), line: loc.line }
+                } else if id.0 != u32::max_value() {
+                    let def_id = DefId {
+                        krate: CrateNum::from_u32(id.0),
+                        index: DefIndex::from_u32(id.1),
+                    };
+                    match external_target(tcx, def_id) {
+                        Some(target) => target,
+                        None => continue,
+                    }
+                } else {
+                    continue
+                };
+
+                let pos = codemap.lookup_byte_offset(BytePos(reference.span.byte_start));
+                links.entry(pos.fm.name.clone())
+                     .or_insert_with(FxHashMap)
+                     .insert(pos.pos.0 as usize, target);
+            }
+        },
+    });
+    links
+}
+
+/// Records the paths of the pages the links to other crates point to, for the
+/// ones that aren't otherwise mentioned in the documentation.
+pub fn record_external_paths(cx: &DocContext, links: &SourceLinks) {
+    for target in links.values().flat_map(|targets| targets.values()) {
+        if let LinkTarget::External { did, kind, .. } = *target {
+            let known = cx.renderinfo.borrow().external_paths.contains_key(&did);
+            if !known {
+                inline::record_extern_fqn(cx, did, kind);
+            }
+        }
+    }
+}
+
+/// Finds where the item `did` of another crate is documented.
+fn external_target<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, did: DefId) -> Option<LinkTarget> {
+    let (page, kind, anchor) = match tcx.describe_def(did) {
+        Some(Def::Fn(_)) => (did, TypeKind::Function, None),
+        Some(Def::Mod(_)) => (did, TypeKind::Module, None),
+        Some(Def::Struct(_)) => (did, TypeKind::Struct, None),
+        Some(Def::Union(_)) => (did, TypeKind::Union, None),
+        Some(Def::Enum(_)) => (did, TypeKind::Enum, None),
+        Some(Def::Trait(_)) => (did, TypeKind::Trait, None),
+        Some(Def::TyAlias(_)) => (did, TypeKind::Typedef, None),
+        Some(Def::Static(..)) => (did, TypeKind::Static, None),
+        Some(Def::Const(_)) => (did, TypeKind::Const, None),
+        Some(Def::Variant(_)) => {
+            match tcx.parent_def_id(did) {
+                Some(parent) => (parent, TypeKind::Enum, Some(value_anchor(tcx, did))),
+                None => return None,
+            }
+        }
+        Some(Def::Method(_)) | Some(Def::AssociatedConst(_)) => {
+            match associated_item_parent(tcx, did) {
+                Some((parent, kind)) => (parent, kind, Some(value_anchor(tcx, did))),
+                None => return None,
+            }
+        }
+        Some(Def::AssociatedTy(_)) => {
+            match associated_item_parent(tcx, did) {
+                Some((parent, kind)) => {
+                    (parent, kind, Some(format!("{}.t", tcx.item_name(did))))
+                }
+                None => return None,
+            }
+        }
+        Some(_) => return None,
+        // Fields don't have a `Def`, they're documented on their struct or
+        // union.
+        None => {
+            if let DefPathData::Field(_) = tcx.def_key(did).disambiguated_data.data {
+                match tcx.parent_def_id(did).and_then(|parent| tcx.describe_def(parent)) {
+                    Some(Def::Struct(parent)) => {
+                        (parent, TypeKind::Struct, Some(value_anchor(tcx, did)))
+                    }
+                    Some(Def::Union(parent)) => {
+                        (parent, TypeKind::Union, Some(value_anchor(tcx, did)))
+                    }
+                    _ => return None,
+                }
+            } else {
+                return None
+            }
+        }
+    };
+
+    Some(LinkTarget::External { did: page, kind, anchor })
+}
+
+/// Returns the trait, or the type of the inherent impl, that the associated
+/// item `did` is documented with.
+fn associated_item_parent<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                    did: DefId) -> Option<(DefId, TypeKind)> {
+    let impl_did = match tcx.associated_item(did).container {
+        ty::TraitContainer(trait_did) => return Some((trait_did, TypeKind::Trait)),
+        ty::ImplContainer(impl_did) => impl_did,
+    };
+    // Items of trait impls are documented with the trait.
+    if let Some(trait_did) = tcx.trait_id_of_impl(impl_did) {
+        return Some((trait_did, TypeKind::Trait));
+    }
+    match tcx.type_of(impl_did).sty {
+        ty::TyAdt(adt, _) => {
+            let kind = match adt.adt_kind() {
+                ty::AdtKind::Struct => TypeKind::Struct,
+                ty::AdtKind::Union => TypeKind::Union,
+                ty::AdtKind::Enum => TypeKind::Enum,
+            };
+            Some((adt.did, kind))
+        }
+        _ => None,
+    }
+}
+
+/// The anchor of an item of the value namespace on the page of its parent.
+fn value_anchor<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, did: DefId) -> String {
+    format!("{}.v", tcx.item_name(did))
+}
@@ -11,6 +11,8 @@
 use std::collections::HashMap;
 use std::env;
 use std::ffi::OsString;
+use std::fs::{self, File};
+use std::hash::Hash;
 use std::io::prelude::*;
 use std::io;
 use std::path::{Path, PathBuf};
@@ -18,17 +20,22 @@ use std::panic::{self, AssertUnwindSafe};
 use std::process::Command;
 use std::rc::Rc;
 use std::str;
+use std::time::UNIX_EPOCH;
 use std::sync::{Arc, Mutex};
 
 use testing;
 use rustc_lint;
 use rustc::hir;
 use rustc::hir::intravisit;
-use rustc::session::{self, CompileIncomplete, config};
+use rustc::hir::svh::Svh;
+use rustc::ich::Fingerprint;
+use rustc::session::{self, CompileIncomplete, config, filesearch};
 use rustc::session::config::{OutputType, OutputTypes, Externs};
 use rustc::session::search_paths::{SearchPaths, PathKind};
 use rustc_back::dynamic_lib::DynamicLibrary;
 use rustc_back::tempdir::TempDir;
+use rustc_back::target::Target;
+use rustc_data_structures::stable_hasher::StableHasher;
 use rustc_driver::{self, driver, Compilation};
 use rustc_driver::driver::phase_2_configure_and_expand;
 use rustc_driver::pretty::ReplaceBodyWithLoop;
@@ -45,7 +52,7 @@ use errors;
 use errors::emitter::ColorConfig;
 
 use clean::Attributes;
-use html::markdown::{self, RenderType};
+use html::markdown::{self, LangString, RenderType};
 
 #[derive(Clone, Default)]
 pub struct TestOptions {
@@ -61,7 +68,8 @@ pub fn run(input: &str,
            crate_name: Option<String>,
            maybe_sysroot: Option<PathBuf>,
            render_type: RenderType,
-           display_warnings: bool)
+           display_warnings: bool,
+           cache_dir: Option<PathBuf>)
            -> isize {
     let input_path = PathBuf::from(input);
     let input = config::Input::File(input_path.clone());
@@ -111,6 +119,11 @@ pub fn run(input: &str,
     let crate_name = crate_name.unwrap_or_else(|| {
         link::find_crate_name(None, &hir_forest.krate().attrs, &input)
     });
+    // The crates loaded while expanding the documented crate are identified
+    // by their hashes in the doctest cache.
+    let mut crate_hashes = Vec::new();
+    cstore.iter_crate_data(|_, data| crate_hashes.push((data.name().to_string(), data.hash())));
+    crate_hashes.sort_by(|a, b| (&a.0, a.1.as_u64()).cmp(&(&b.0, b.1.as_u64())));
     let opts = scrape_test_config(hir_forest.krate());
     let mut collector = Collector::new(crate_name,
                                        cfgs,
@@ -121,7 +134,9 @@ pub fn run(input: &str,
                                        maybe_sysroot,
                                        Some(codemap),
                                        None,
-                                       render_type);
+                                       render_type,
+                                       cache_dir,
+                                       &crate_hashes);
 
     {
         let map = hir::map::map_crate(&sess, &*cstore, &mut hir_forest, &defs);
@@ -176,11 +191,22 @@ fn scrape_test_config(krate: &::rustc::hir::Crate) -> TestOptions {
     opts
 }
 
+// We're going to pass an explicit handle into rustc to collect output
+// messages, rather than using a default Handler printing them to the actual
+// stderr.
+struct Sink(Arc<Mutex<Vec<u8>>>);
+impl Write for Sink {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        Write::write(&mut *self.0.lock().unwrap(), data)
+    }
+    fn flush(&mut self) -> io::Result<()> { Ok(()) }
+}
+
 fn run_test(test: &str, cratename: &str, filename: &str, cfgs: Vec<String>, libs: SearchPaths,
             externs: Externs,
-            should_panic: bool, no_run: bool, as_test_harness: bool,
-            compile_fail: bool, mut error_codes: Vec<String>, opts: &TestOptions,
-            maybe_sysroot: Option<PathBuf>) {
+            should_panic: bool, panic_message: Option<&str>, no_run: bool,
+            as_test_harness: bool, compile_fail: bool, mut error_codes: Vec<String>,
+            opts: &TestOptions, maybe_sysroot: Option<PathBuf>) {
     // the test harness wants its own `main` & top level functions, so
     // never wrap the test in `fn main() { ... }`
     let test = make_test(test, Some(cratename), as_test_harness, opts);
@@ -188,6 +214,58 @@ fn run_test(test: &str, cratename: &str, filename: &str, cfgs: Vec<String>, libs
         name: filename.to_owned(),
         input: test.to_owned(),
     };
+
+    // Shuffle around a few input and output handles here. We want to catch
+    // the error message that rustc prints when it fails.
+    //
+    // We take our thread-local stderr (likely set by the test runner) and replace
+    // it with a sink that is also passed to rustc itself. When this function
+    // returns the output of the sink is copied onto the output of our own thread.
+    struct Bomb(Arc<Mutex<Vec<u8>>>, Box<Write+Send>);
+    impl Drop for Bomb {
+        fn drop(&mut self) {
+            let _ = self.1.write_all(&self.0.lock().unwrap());
+        }
+    }
+    let data = Arc::new(Mutex::new(Vec::new()));
+    let old = io::set_panic(Some(box Sink(data.clone())));
+    let _bomb = Bomb(data.clone(), old.unwrap_or(box io::stdout()));
+
+    let (compile_result, outdir, libdir) = compile_test(input, cfgs, libs, externs,
+                                                        maybe_sysroot, as_test_harness,
+                                                        no_run, data.clone());
+
+    match (compile_result, compile_fail) {
+        (Ok(()), true) => {
+            panic!("test compiled while it wasn't supposed to")
+        }
+        (Ok(()), false) => {}
+        (Err(()), true) => {
+            if error_codes.len() > 0 {
+                let out = String::from_utf8(data.lock().unwrap().to_vec()).unwrap();
+                error_codes.retain(|err| !out.contains(err));
+            }
+        }
+        (Err(()), false) => {
+            panic!("couldn't compile the test")
+        }
+    }
+
+    if error_codes.len() > 0 {
+        panic!("Some expected error codes were not found: {:?}", error_codes);
+    }
+
+    if no_run { return }
+
+    run_binary(&outdir.path().join("rust_out"), &[], &libdir, should_panic, panic_message);
+}
+
+/// Compiles a doctest crate into an executable in a new temporary directory,
+/// sending diagnostics to `diagnostics`. Returns whether compilation
+/// succeeded, the directory, and the target library path to run it with.
+fn compile_test(input: config::Input, cfgs: Vec<String>, libs: SearchPaths, externs: Externs,
+                maybe_sysroot: Option<PathBuf>, as_test_harness: bool, no_run: bool,
+                diagnostics: Arc<Mutex<Vec<u8>>>) -> (Result<(), ()>, TempDir, PathBuf) {
     let outputs = OutputTypes::new(&[(OutputType::Exe, None)]);
 
     let sessopts = config::Options {
@@ -206,35 +284,10 @@ fn run_test(test: &str, cratename: &str, filename: &str, cfgs: Vec<String>, libs
         ..config::basic_options().clone()
     };
 
-    // Shuffle around a few input and output handles here. We're going to pass
-    // an explicit handle into rustc to collect output messages, but we also
-    // want to catch the error message that rustc prints when it fails.
-    //
-    // We take our thread-local stderr (likely set by the test runner) and replace
-    // it with a sink that is also passed to rustc itself. When this function
-    // returns the output of the sink is copied onto the output of our own thread.
-    //
-    // The basic idea is to not use a default Handler for rustc, and then also
-    // not print things by default to the actual stderr.
-    struct Sink(Arc<Mutex<Vec<u8>>>);
-    impl Write for Sink {
-        fn write(&mut self, data: &[u8]) -> io::Result<usize> {
-            Write::write(&mut *self.0.lock().unwrap(), data)
-        }
-        fn flush(&mut self) -> io::Result<()> { Ok(()) }
-    }
-    struct Bomb(Arc<Mutex<Vec<u8>>>, Box<Write+Send>);
-    impl Drop for Bomb {
-        fn drop(&mut self) {
-            let _ = self.1.write_all(&self.0.lock().unwrap());
-        }
-    }
-    let data = Arc::new(Mutex::new(Vec::new()));
     let codemap = Rc::new(CodeMap::new(sessopts.file_path_mapping()));
-    let emitter = errors::emitter::EmitterWriter::new(box Sink(data.clone()),
-                                                      Some(codemap.clone()));
-    let old = io::set_panic(Some(box Sink(data.clone())));
-    let _bomb = Bomb(data.clone(), old.unwrap_or(box io::stdout()));
+    let emitter = errors::emitter::EmitterWriter::new(box Sink(diagnostics),
+                                                      Some(codemap.clone()),
+                                                      false);
 
     // Compile the code
     let diagnostic_handler = errors::Handler::with_emitter(true, false, box emitter);
@@ -246,12 +299,12 @@ fn run_test(test: &str, cratename: &str, filename: &str, cfgs: Vec<String>, libs
     rustc_trans::init(&sess);
     rustc_lint::register_builtins(&mut sess.lint_store.borrow_mut(), Some(&sess));
 
-    let outdir = Mutex::new(TempDir::new("rustdoctest").ok().expect("rustdoc needs a tempdir"));
+    let outdir = TempDir::new("rustdoctest").ok().expect("rustdoc needs a tempdir");
     let libdir = sess.target_filesearch(PathKind::All).get_lib_path();
     let mut control = driver::CompileController::basic();
     sess.parse_sess.config =
         config::build_configuration(&sess, config::parse_cfgspecs(cfgs.clone()));
-    let out = Some(outdir.lock().unwrap().path().to_path_buf());
+    let out = Some(outdir.path().to_path_buf());
 
     if no_run {
         control.after_analysis.stop = Compilation::Stop;
@@ -265,41 +318,26 @@ fn run_test(test: &str, cratename: &str, filename: &str, cfgs: Vec<String>, libs
         Ok(Ok(())) | Ok(Err(CompileIncomplete::Stopped)) => Ok(()),
         Err(_) | Ok(Err(CompileIncomplete::Errored(_))) => Err(())
     };
+    (compile_result, outdir, libdir)
+}
 
-    match (compile_result, compile_fail) {
-        (Ok(()), true) => {
-            panic!("test compiled while it wasn't supposed to")
-        }
-        (Ok(()), false) => {}
-        (Err(()), true) => {
-            if error_codes.len() > 0 {
-                let out = String::from_utf8(data.lock().unwrap().to_vec()).unwrap();
-                error_codes.retain(|err| !out.contains(err));
-            }
-        }
-        (Err(()), false) => {
-            panic!("couldn't compile the test")
-        }
-    }
-
-    if error_codes.len() > 0 {
-        panic!("Some expected error codes were not found: {:?}", error_codes);
-    }
-
-    if no_run { return }
-
+/// Runs a compiled doctest, panicking if it doesn't succeed or, for
+/// `should_panic` tests, fail with `panic_message` in its output if given.
+fn run_binary(binary: &Path, args: &[String], libdir: &Path, should_panic: bool,
+              panic_message: Option<&str>) {
     // Run the code!
     //
     // We're careful to prepend the *target* dylib search path to the child's
     // environment to ensure that the target loads the right libraries at
     // runtime. It would be a sad day if the *host* libraries were loaded as a
     // mistake.
-    let mut cmd = Command::new(&outdir.lock().unwrap().path().join("rust_out"));
+    let mut cmd = Command::new(binary);
+    cmd.args(args);
     let var = DynamicLibrary::envvar();
     let newpath = {
         let path = env::var_os(var).unwrap_or(OsString::new());
         let mut path = env::split_paths(&path).collect::<Vec<_>>();
-        path.insert(0, libdir.clone());
+        path.insert(0, libdir.to_path_buf());
         env::join_paths(path).unwrap()
     };
     cmd.env(var, &newpath);
@@ -312,6 +350,12 @@ fn run_test(test: &str, cratename: &str, filename: &str, cfgs: Vec<String>, libs
         Ok(out) => {
             if should_panic && out.status.success() {
                 panic!("test executable succeeded when it should have failed");
+            } else if should_panic && panic_message.map_or(false, |message| {
+                !String::from_utf8_lossy(&out.stderr).contains(message)
+            }) {
+                panic!("test executable didn't panic with the expected message `{}`:\n{}\n",
+                       panic_message.unwrap(),
+                       str::from_utf8(&out.stderr).unwrap_or(""));
             } else if !should_panic && !out.status.success() {
                 panic!("test executable failed:\n{}\n{}\n",
                        str::from_utf8(&out.stdout).unwrap_or(""),
@@ -321,6 +365,203 @@ fn run_test(test: &str, cratename: &str, filename: &str, cfgs: Vec<String>, libs
     }
 }
 
+/// Doctests which don't need to be compiled on their own are merged into a
+/// single crate, with one module and `#[test]` per example, which is built
+/// once the first of them runs. Each example still runs in its own process.
+struct CombinedDoctests {
+    cratename: String,
+    cfgs: Vec<String>,
+    libs: SearchPaths,
+    externs: Externs,
+    maybe_sysroot: Option<PathBuf>,
+    inject_crate: bool,
+    modules: Mutex<Vec<String>>,
+    /// The directory the test harness was built in and the target library
+    /// path, or `None` if the doctests failed to compile together.
+    build: Mutex<Option<Option<(TempDir, PathBuf)>>>,
+}
+
+impl CombinedDoctests {
+    /// Adds the doctest `test` to the combined crate, returning the name of
+    /// its `#[test]` function.
+    fn add(&self, test: &str) -> String {
+        let mut modules = self.modules.lock().unwrap();
+        let module = format!("__doctest_{}", modules.len());
+        let opts = TestOptions { no_crate_inject: !self.inject_crate, attrs: vec![] };
+        modules.push(format!("mod {} {{\n{}\n#[test]\nfn run() {{ main() }}\n}}\n",
+                             module, make_test(test, Some(&self.cratename), false, &opts)));
+        format!("{}::run", module)
+    }
+
+    /// Builds the combined test harness, if that hasn't been done yet, and
+    /// returns the path of the executable and the target library path.
+    fn binary(&self) -> Option<(PathBuf, PathBuf)> {
+        let mut build = self.build.lock().unwrap();
+        if build.is_none() {
+            let mut src = String::new();
+            let modules = self.modules.lock().unwrap();
+            // Examples may refer to the crate by absolute path, which needs it
+            // at the root as well.
+            let extern_crate = format!("extern crate {};", self.cratename);
+            if self.inject_crate && modules.iter().any(|m| m.contains(&extern_crate)) {
+                src.push_str(&extern_crate);
+                src.push('\n');
+            }
+            for module in modules.iter() {
+                src.push_str(module);
+            }
+            let input = config::Input::Str {
+                name: "<combined doctests>".to_owned(),
+                input: src,
+            };
+            let (cfgs, libs) = (self.cfgs.clone(), self.libs.clone());
+            let (externs, maybe_sysroot) = (self.externs.clone(), self.maybe_sysroot.clone());
+            let diagnostics = Arc::new(Mutex::new(Vec::new()));
+            let diagnostics2 = diagnostics.clone();
+            let res = rustc_driver::in_rustc_thread(move || {
+                compile_test(input, cfgs, libs, externs, maybe_sysroot, true, false, diagnostics2)
+            });
+            *build = Some(match res {
+                Ok((Ok(()), outdir, libdir)) => Some((outdir, libdir)),
+                _ => {
+                    // Every example gets compiled on its own instead, which
+                    // reports the errors where they belong, but is a lot
+                    // slower, so say why.
+                    let _ = writeln!(&mut io::stderr(),
+                                     "WARNING: the doctests could not be compiled together, \
+                                      compiling each of them on its own instead:\n{}",
+                                     String::from_utf8_lossy(&diagnostics.lock().unwrap()));
+                    None
+                }
+            });
+        }
+        build.as_ref().unwrap().as_ref().map(|&(ref outdir, ref libdir)| {
+            (outdir.path().join("rust_out"), libdir.clone())
+        })
+    }
+}
+
+/// The extensions of the files which are libraries a doctest may be linked to.
+const LIBRARY_EXTENSIONS: &[&str] = &["rlib", "rmeta", "so", "dylib", "dll", "a", "lib"];
+
+/// The record of the doctests which passed, kept with `--doctest-cache-dir`
+/// so that they're skipped while their inputs are unchanged.
+///
+/// A doctest's entry is an empty file named after the fingerprint of those
+/// inputs: its source and attributes, the options it's built with, the
+/// libraries it may link to and the compiler it's built with. Libraries are
+/// identified by their crate hash when the documented crate loaded them, and
+/// otherwise, like the compiler if it wasn't built from git, by the path,
+/// size and modification time of their files.
+struct DoctestCache {
+    dir: PathBuf,
+    /// The fingerprint of the inputs which all doctests share.
+    common: Fingerprint,
+}
+
+impl DoctestCache {
+    fn new(dir: PathBuf, cratename: &str, cfgs: &[String], libs: &SearchPaths,
+           externs: &Externs, opts: &TestOptions, maybe_sysroot: &Option<PathBuf>,
+           crate_hashes: &[(String, Svh)]) -> DoctestCache {
+        let mut hasher = StableHasher::<Fingerprint>::new();
+        option_env!("CFG_VERSION").hash(&mut hasher);
+        option_env!("CFG_VER_HASH").hash(&mut hasher);
+        cratename.hash(&mut hasher);
+        cfgs.hash(&mut hasher);
+        externs.hash(&mut hasher);
+        opts.no_crate_inject.hash(&mut hasher);
+        opts.attrs.hash(&mut hasher);
+        maybe_sysroot.hash(&mut hasher);
+        for &(ref name, ref svh) in crate_hashes {
+            name.hash(&mut hasher);
+            svh.hash(&mut hasher);
+        }
+
+        // Which libraries the paths of a doctest resolve to is only known once
+        // it's compiled, so all of those it could be linked to count: the ones
+        // passed with `--extern` or found in a library search path, and the
+        // standard library of the sysroot. That includes the library of the
+        // documented crate itself, which it didn't load.
+        let sysroot = maybe_sysroot.clone().unwrap_or_else(|| {
+            env::current_exe().unwrap().parent().unwrap().parent().unwrap().to_path_buf()
+        });
+        let mut dirs = libs.iter(PathKind::All).map(|(path, _)| {
+            path.to_path_buf()
+        }).collect::<Vec<_>>();
+        dirs.push(sysroot.join(filesearch::relative_target_lib_path(&sysroot,
+                                                                   config::host_triple())));
+        let mut files = externs.iter().flat_map(|(_, paths)| {
+            paths.iter().map(PathBuf::from)
+        }).collect::<Vec<_>>();
+        for dir in &dirs {
+            dir.hash(&mut hasher);
+            let entries = match fs::read_dir(dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                let is_library = path.extension().and_then(|ext| ext.to_str())
+                                     .map_or(false, |ext| LIBRARY_EXTENSIONS.contains(&ext));
+                if is_library {
+                    files.push(path);
+                }
+            }
+        }
+        files.sort();
+        files.dedup();
+        if option_env!("CFG_VER_HASH").is_none() {
+            if let Ok(rustdoc) = env::current_exe() {
+                files.push(rustdoc);
+            }
+        }
+        for file in &files {
+            file.hash(&mut hasher);
+            if let Ok(metadata) = fs::metadata(file) {
+                metadata.len().hash(&mut hasher);
+                if let Ok(modified) = metadata.modified() {
+                    let since_epoch = modified.duration_since(UNIX_EPOCH).unwrap_or_default();
+                    since_epoch.as_secs().hash(&mut hasher);
+                    since_epoch.subsec_nanos().hash(&mut hasher);
+                }
+            }
+        }
+
+        if let Err(e) = fs::create_dir_all(&dir) {
+            warn!("couldn't create the doctest cache directory {}: {}", dir.display(), e);
+        }
+        DoctestCache { dir, common: hasher.finish() }
+    }
+
+    /// Returns the path of the entry of the doctest `test`.
+    fn entry(&self, test: &str, block_info: &LangString) -> PathBuf {
+        let mut hasher = StableHasher::<Fingerprint>::new();
+        self.common.hash(&mut hasher);
+        test.hash(&mut hasher);
+        block_info.hash(&mut hasher);
+        self.dir.join(hasher.finish().to_hex())
+    }
+
+    /// Records that the doctest of `entry` passed.
+    fn record_pass(entry: &Path) {
+        if let Err(e) = File::create(entry) {
+            warn!("couldn't record a passed doctest in {}: {}", entry.display(), e);
+        }
+    }
+}
+
+/// Whether the doctest can be compiled along with others, as opposed to
+/// needing a crate of its own: anything with crate attributes (such as
+/// `#![feature]`), its own `--cfg`s, `main` or harness, or loading macros from
+/// another crate has to be on its own.
+fn can_combine(test: &str, block_info: &LangString, opts: &TestOptions) -> bool {
+    !block_info.test_harness && !block_info.compile_fail && block_info.cfgs.is_empty() &&
+        opts.attrs.is_empty() &&
+        !test.contains("#![") &&
+        !has_main(test) &&
+        !test.contains("#[macro_use]")
+}
+
 pub fn make_test(s: &str,
                  cratename: Option<&str>,
                  dont_insert_main: bool,
@@ -349,20 +590,7 @@ pub fn make_test(s: &str,
         }
     }
 
-    // FIXME (#21299): prefer libsyntax or some other actual parser over this
-    // best-effort ad hoc approach
-    let already_has_main = s.lines()
-        .map(|line| {
-            let comment = line.find("//");
-            if let Some(comment_begins) = comment {
-                &line[0..comment_begins]
-            } else {
-                line
-            }
-        })
-        .any(|code| code.contains("fn main"));
-
-    if dont_insert_main || already_has_main {
+    if dont_insert_main || has_main(s) {
         prog.push_str(&everything_else);
     } else {
         prog.push_str("fn main() {\n");
@@ -376,6 +604,42 @@ pub fn make_test(s: &str,
     prog
 }
 
+/// Whether `ignore-<name>` ignores doctests on `triple`. This accepts the same
+/// names as the `ignore-<name>` directives of the compiler's own test suite:
+/// any component of the triple, like `x86_64` or `linux`, as well as the
+/// target's `target_os` (`macos` for `x86_64-apple-darwin`), `target_arch`
+/// (`x86` for `i686-pc-windows-gnu`), `target_env`, `target_family` (`unix`
+/// or `windows`) and its pointer width, as in `32bit`.
+fn ignores_target(name: &str, triple: &str) -> bool {
+    if triple.split('-').any(|component| component == name) {
+        return true;
+    }
+    let target = match Target::search(triple) {
+        Ok(target) => target,
+        Err(_) => return false,
+    };
+    name == target.target_os ||
+        name == target.arch ||
+        (!target.target_env.is_empty() && name == target.target_env) ||
+        target.options.target_family.as_ref().map_or(false, |family| name == family) ||
+        name == format!("{}bit", target.target_pointer_width)
+}
+
+fn has_main(s: &str) -> bool {
+    // FIXME (#21299): prefer libsyntax or some other actual parser over this
+    // best-effort ad hoc approach
+    s.lines()
+        .map(|line| {
+            let comment = line.find("//");
+            if let Some(comment_begins) = comment {
+                &line[0..comment_begins]
+            } else {
+                line
+            }
+        })
+        .any(|code| code.contains("fn main"))
+}
+
 // FIXME(aburka): use a real parser to deal with multiline attributes
 fn partition_source(s: &str) -> (String, String) {
     use std_unicode::str::UnicodeStr;
@@ -418,6 +682,8 @@ pub struct Collector {
     position: Span,
     codemap: Option<Rc<CodeMap>>,
     filename: Option<String>,
+    combined: Arc<CombinedDoctests>,
+    cache: Option<DoctestCache>,
     // to be removed when hoedown will be removed as well
     pub render_type: RenderType,
 }
@@ -426,7 +692,22 @@ impl Collector {
     pub fn new(cratename: String, cfgs: Vec<String>, libs: SearchPaths, externs: Externs,
                use_headers: bool, opts: TestOptions, maybe_sysroot: Option<PathBuf>,
                codemap: Option<Rc<CodeMap>>, filename: Option<String>,
-               render_type: RenderType) -> Collector {
+               render_type: RenderType, cache_dir: Option<PathBuf>,
+               crate_hashes: &[(String, Svh)]) -> Collector {
+        let combined = Arc::new(CombinedDoctests {
+            cratename: cratename.clone(),
+            cfgs: cfgs.clone(),
+            libs: libs.clone(),
+            externs: externs.clone(),
+            maybe_sysroot: maybe_sysroot.clone(),
+            inject_crate: !opts.no_crate_inject && cratename != "std",
+            modules: Mutex::new(Vec::new()),
+            build: Mutex::new(None),
+        });
+        let cache = cache_dir.map(|dir| {
+            DoctestCache::new(dir, &cratename, &cfgs, &libs, &externs, &opts, &maybe_sysroot,
+                              crate_hashes)
+        });
         Collector {
             tests: Vec::new(),
             old_tests: HashMap::new(),
@@ -443,6 +724,8 @@ impl Collector {
             position: DUMMY_SP,
             codemap,
             filename,
+            combined,
+            cache,
             render_type,
         }
     }
@@ -479,10 +762,8 @@ impl Collector {
         entry.push(test.trim().to_owned());
     }
 
-    pub fn add_test(&mut self, test: String,
-                    should_panic: bool, no_run: bool, should_ignore: bool,
-                    as_test_harness: bool, compile_fail: bool, error_codes: Vec<String>,
-                    line: usize, filename: String, allow_fail: bool) {
+    pub fn add_test(&mut self, test: String, block_info: LangString, line: usize,
+                    filename: String) {
         let name = self.generate_name(line, &filename);
         // to be removed when hoedown is removed
         if self.render_type == RenderType::Pulldown {
@@ -501,22 +782,68 @@ impl Collector {
                 return
             }
         }
-        let cfgs = self.cfgs.clone();
+        let mut cfgs = self.cfgs.clone();
+        cfgs.extend(block_info.cfgs.iter().cloned());
         let libs = self.libs.clone();
         let externs = self.externs.clone();
         let cratename = self.cratename.to_string();
-        let opts = self.opts.clone();
+        let mut opts = self.opts.clone();
+        opts.attrs.extend(block_info.features.iter().map(|f| format!("feature({})", f)));
         let maybe_sysroot = self.maybe_sysroot.clone();
+        // Doctests are built for and run on the host.
+        let should_ignore = block_info.ignore || block_info.ignore_targets.iter().any(|target| {
+            ignores_target(target, config::host_triple())
+        });
+        // Doctests which passed before with the same inputs aren't compiled
+        // nor run again, they're reported as ignored, and marked as cached.
+        // They're still run with `--ignored`.
+        let cache_entry = match self.cache {
+            Some(ref cache) if !should_ignore => Some(cache.entry(&test, &block_info)),
+            _ => None,
+        };
+        let cached = cache_entry.as_ref().map_or(false, |entry| entry.exists());
+        let name = if cached {
+            debug!("Skipping test {}, which passed before", name);
+            format!("{} - cached", name)
+        } else {
+            name
+        };
+        let combined = if !should_ignore && !cached && block_info.error_codes.is_empty() &&
+                          can_combine(&test, &block_info, &opts) {
+            let test_name = self.combined.add(&test);
+            Some((self.combined.clone(), test_name))
+        } else {
+            None
+        };
+        let LangString {
+            should_panic, panic_message, no_run, test_harness: as_test_harness, compile_fail,
+            error_codes, allow_fail, ..
+        } = block_info;
         debug!("Creating test {}: {}", name, test);
         self.tests.push(testing::TestDescAndFn {
             desc: testing::TestDesc {
                 name: testing::DynTestName(name),
-                ignore: should_ignore,
+                ignore: should_ignore || cached,
                 // compiler failures are test failures
                 should_panic: testing::ShouldPanic::No,
                 allow_fail,
+                timeout: None,
             },
             testfn: testing::DynTestFn(box move |()| {
+                if let Some((combined, test_name)) = combined {
+                    if let Some((binary, libdir)) = combined.binary() {
+                        if !no_run {
+                            let args = vec!["--exact".to_owned(), test_name,
+                                            "--quiet".to_owned(), "--nocapture".to_owned()];
+                            run_binary(&binary, &args, &libdir, should_panic,
+                                       panic_message.as_ref().map(|s| &**s));
+                        }
+                        if let Some(ref entry) = cache_entry {
+                            DoctestCache::record_pass(entry);
+                        }
+                        return;
+                    }
+                }
                 let panic = io::set_panic(None);
                 let print = io::set_print(None);
                 match {
@@ -530,6 +857,7 @@ impl Collector {
                                  libs,
                                  externs,
                                  should_panic,
+                                 panic_message.as_ref().map(|s| &**s),
                                  no_run,
                                  as_test_harness,
                                  compile_fail,
@@ -541,6 +869,9 @@ impl Collector {
                     Ok(()) => (),
                     Err(err) => panic::resume_unwind(err),
                 }
+                if let Some(ref entry) = cache_entry {
+                    DoctestCache::record_pass(entry);
+                }
             }),
         });
     }
@@ -697,3 +1028,34 @@ impl<'a, 'hir> intravisit::Visitor<'hir> for HirCollector<'a, 'hir> {
         self.visit_testable(macro_def.name.to_string(), &macro_def.attrs, |_| ());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ignores_target;
+
+    #[test]
+    fn test_ignores_target() {
+        assert!(ignores_target("linux", "x86_64-unknown-linux-gnu"));
+        assert!(ignores_target("x86_64", "x86_64-unknown-linux-gnu"));
+        assert!(ignores_target("gnu", "x86_64-unknown-linux-gnu"));
+        assert!(ignores_target("unix", "x86_64-unknown-linux-gnu"));
+        assert!(ignores_target("64bit", "x86_64-unknown-linux-gnu"));
+        assert!(!ignores_target("32bit", "x86_64-unknown-linux-gnu"));
+        assert!(!ignores_target("windows", "x86_64-unknown-linux-gnu"));
+
+        assert!(ignores_target("macos", "x86_64-apple-darwin"));
+        assert!(ignores_target("darwin", "x86_64-apple-darwin"));
+        assert!(!ignores_target("linux", "x86_64-apple-darwin"));
+
+        assert!(ignores_target("x86", "i686-pc-windows-gnu"));
+        assert!(ignores_target("windows", "i686-pc-windows-gnu"));
+        assert!(ignores_target("32bit", "i686-pc-windows-gnu"));
+        assert!(!ignores_target("unix", "i686-pc-windows-gnu"));
+
+        assert!(ignores_target("aarch64", "aarch64-linux-android"));
+        assert!(ignores_target("android", "aarch64-linux-android"));
+
+        assert!(!ignores_target("nonexistent_target", "x86_64-unknown-linux-gnu"));
+        assert!(!ignores_target("macos", "not-a-real-target"));
+    }
+}
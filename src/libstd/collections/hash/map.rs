@@ -20,7 +20,6 @@ use hash::{Hash, Hasher, BuildHasher, SipHasher13};
 use iter::{FromIterator, FusedIterator};
 use mem::{self, replace};
 use ops::{Deref, Index, InPlace, Place, Placer};
-use rand::{self, Rng};
 use ptr;
 
 use super::table::{self, Bucket, EmptyBucket, FullBucket, FullBucketMut, RawTable, SafeHash};
@@ -2424,11 +2423,7 @@ impl RandomState {
         // iteration order allows a form of DOS attack. To counter that we
         // increment one of the seeds on every RandomState creation, giving
         // every corresponding HashMap a different iteration order.
-        thread_local!(static KEYS: Cell<(u64, u64)> = {
-            let r = rand::OsRng::new();
-            let mut r = r.expect("failed to create an OS RNG");
-            Cell::new((r.gen(), r.gen()))
-        });
+        thread_local!(static KEYS: Cell<(u64, u64)> = Cell::new(random_keys()));
 
         KEYS.with(|keys| {
             let (k0, k1) = keys.get();
@@ -2438,6 +2433,22 @@ impl RandomState {
     }
 }
 
+#[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+#[allow(deprecated)] // rand
+fn random_keys() -> (u64, u64) {
+    use rand::{self, Rng};
+
+    let r = rand::OsRng::new();
+    let mut r = r.expect("failed to create an OS RNG");
+    (r.gen(), r.gen())
+}
+
+// wasm32-unknown-unknown has no source of randomness, see `sys::rand`.
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+fn random_keys() -> (u64, u64) {
+    ::sys::rand::hashmap_random_keys()
+}
+
 #[stable(feature = "hashmap_build_hasher", since = "1.7.0")]
 impl BuildHasher for RandomState {
     type Hasher = DefaultHasher;
@@ -13,7 +13,8 @@
 //! The `std::sys` module is the abstracted interface through which
 //! `std` talks to the underlying operating system. It has different
 //! implementations for different operating system families, today
-//! just Unix and Windows, and initial support for Redox.
+//! just Unix and Windows, and initial support for Redox and for wasm
+//! without an Emscripten runtime.
 //!
 //! The centralization of platform-specific code in this module is
 //! enforced by the "platform abstraction layer" tidy script in
@@ -46,6 +47,10 @@ mod imp;
 #[path = "redox/mod.rs"]
 mod imp;
 
+#[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+#[path = "wasm/mod.rs"]
+mod imp;
+
 
 // Import essential modules from both platforms when documenting.
 
@@ -0,0 +1,111 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use fmt;
+use time::Duration;
+
+// There's no clock available to a bare wasm module, so `now` can't be
+// implemented. The arithmetic still works though, which is what lets
+// `UNIX_EPOCH + duration` and friends be used on this target.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+pub struct Instant(Time);
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SystemTime(Time);
+
+pub const UNIX_EPOCH: SystemTime = SystemTime(Time { secs: 0, nanos: 0 });
+
+// `Duration` can't be built in a constant outside of `time::duration`, so
+// this is stored as its parts instead.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Hash)]
+struct Time {
+    secs: u64,
+    nanos: u32,
+}
+
+impl Time {
+    fn to_duration(&self) -> Duration {
+        Duration::new(self.secs, self.nanos)
+    }
+
+    fn checked_sub(&self, other: &Time) -> Option<Duration> {
+        self.to_duration().checked_sub(other.to_duration())
+    }
+
+    fn checked_add_duration(&self, other: &Duration) -> Option<Time> {
+        self.to_duration().checked_add(*other).map(Time::from)
+    }
+
+    fn checked_sub_duration(&self, other: &Duration) -> Option<Time> {
+        self.to_duration().checked_sub(*other).map(Time::from)
+    }
+}
+
+impl From<Duration> for Time {
+    fn from(dur: Duration) -> Time {
+        Time { secs: dur.as_secs(), nanos: dur.subsec_nanos() }
+    }
+}
+
+impl Instant {
+    pub fn now() -> Instant {
+        panic!("Instant::now is not supported on wasm32-unknown-unknown: \
+                there is no clock to read")
+    }
+
+    pub fn sub_instant(&self, other: &Instant) -> Duration {
+        self.0.checked_sub(&other.0).unwrap_or_else(|| {
+            panic!("other was less than the current instant")
+        })
+    }
+
+    pub fn add_duration(&self, other: &Duration) -> Instant {
+        Instant(self.0.checked_add_duration(other)
+                      .expect("overflow when adding duration to instant"))
+    }
+
+    pub fn sub_duration(&self, other: &Duration) -> Instant {
+        Instant(self.0.checked_sub_duration(other)
+                      .expect("overflow when subtracting duration from instant"))
+    }
+}
+
+impl SystemTime {
+    pub fn now() -> SystemTime {
+        panic!("SystemTime::now is not supported on wasm32-unknown-unknown: \
+                there is no clock to read")
+    }
+
+    pub fn sub_time(&self, other: &SystemTime)
+                    -> Result<Duration, Duration> {
+        self.0.checked_sub(&other.0).ok_or_else(|| {
+            other.0.checked_sub(&self.0).unwrap()
+        })
+    }
+
+    pub fn add_duration(&self, other: &Duration) -> SystemTime {
+        SystemTime(self.0.checked_add_duration(other)
+                         .expect("overflow when adding duration to time"))
+    }
+
+    pub fn sub_duration(&self, other: &Duration) -> SystemTime {
+        SystemTime(self.0.checked_sub_duration(other)
+                         .expect("overflow when subtracting duration from time"))
+    }
+}
+
+impl fmt::Debug for SystemTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SystemTime")
+         .field("secs", &self.0.secs)
+         .field("nanos", &self.0.nanos)
+         .finish()
+    }
+}
@@ -44,10 +44,12 @@ pub mod thread_local;
 pub mod util;
 pub mod wtf8;
 
-#[cfg(any(target_os = "redox", target_os = "l4re"))]
+#[cfg(any(target_os = "redox", target_os = "l4re",
+          all(target_arch = "wasm32", not(target_os = "emscripten"))))]
 pub use sys::net;
 
-#[cfg(not(any(target_os = "redox", target_os = "l4re")))]
+#[cfg(not(any(target_os = "redox", target_os = "l4re",
+              all(target_arch = "wasm32", not(target_os = "emscripten")))))]
 pub mod net;
 
 #[cfg(feature = "backtrace")]
@@ -395,6 +395,12 @@ declare_features! (
 
     // allow `..=` in patterns (RFC 1192)
     (active, dotdoteq_in_patterns, "1.22.0", Some(28237)),
+
+    // Allows `#[timeout = "SECS"]` on tests
+    (active, test_timeout, "1.22.0", None),
+
+    // Allows `let`, loops and `match` in the body of a `const fn`
+    (active, const_fn_control_flow, "1.22.0", None),
 );
 
 declare_features! (
@@ -895,6 +901,11 @@ pub const BUILTIN_ATTRIBUTES: &'static [(&'static str, AttributeType, AttributeG
                                  "allow_fail attribute is currently unstable",
                                  cfg_fn!(allow_fail))),
 
+    ("timeout", Normal, Gated(Stability::Unstable,
+                              "test_timeout",
+                              "the timeout attribute is currently unstable",
+                              cfg_fn!(test_timeout))),
+
     // Crate level attributes
     ("crate_name", CrateLevel, Ungated),
     ("crate_type", CrateLevel, Ungated),
@@ -42,7 +42,7 @@ use ast::{RangeEnd, RangeSyntax};
 use {ast, attr};
 use codemap::{self, CodeMap, Spanned, respan};
 use syntax_pos::{self, Span, BytePos};
-use errors::{self, DiagnosticBuilder};
+use errors::{self, Applicability, DiagnosticBuilder};
 use parse::{self, classify, token};
 use parse::common::SeqSep;
 use parse::lexer::TokenAndSpan;
@@ -1567,7 +1567,10 @@ impl<'a> Parser<'a> {
                     s.print_bounds(" +", &bounds)?;
                     s.pclose()
                 });
-                err.span_suggestion(sum_span, "try adding parentheses", sum_with_parens);
+                err.span_suggestion_with_applicability(sum_span,
+                                                       "try adding parentheses",
+                                                       sum_with_parens,
+                                                       Applicability::MachineApplicable);
             }
             TyKind::Ptr(..) | TyKind::BareFn(..) => {
                 err.span_label(sum_span, "perhaps you forgot parentheses?");
@@ -3514,7 +3517,10 @@ impl<'a> Parser<'a> {
                 let binding_mode = if self.eat_keyword(keywords::Ref) {
                     self.diagnostic()
                         .struct_span_err(mutref_span, "the order of `mut` and `ref` is incorrect")
-                        .span_suggestion(mutref_span, "try switching the order", "ref mut".into())
+                        .span_suggestion_with_applicability(mutref_span,
+                                                            "try switching the order",
+                                                            "ref mut".into(),
+                                                            Applicability::MachineApplicable)
                         .emit();
                     BindingMode::ByRef(Mutability::Mutable)
                 } else {
@@ -6085,7 +6091,10 @@ impl<'a> Parser<'a> {
             if self.token.is_keyword(keywords::Const) {
                 self.diagnostic()
                     .struct_span_err(self.span, "extern items cannot be `const`")
-                    .span_suggestion(self.span, "instead try using", "static".to_owned())
+                    .span_suggestion_with_applicability(self.span,
+                                                        "instead try using",
+                                                        "static".to_owned(),
+                                                        Applicability::MachineApplicable)
                     .emit();
             }
             self.bump(); // `static` or `const`
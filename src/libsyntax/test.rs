@@ -53,6 +53,7 @@ struct Test {
     ignore: bool,
     should_panic: ShouldPanic,
     allow_fail: bool,
+    timeout: Option<u64>,
 }
 
 struct TestCtxt<'a> {
@@ -134,6 +135,7 @@ impl<'a> fold::Folder for TestHarnessGenerator<'a> {
                         ignore: is_ignored(&i),
                         should_panic: should_panic(&i, &self.cx),
                         allow_fail: is_allowed_fail(&i),
+                        timeout: test_timeout(&i, &self.cx),
                     };
                     self.cx.testfns.push(test);
                     self.tests.push(i.ident);
@@ -391,6 +393,23 @@ fn is_allowed_fail(i: &ast::Item) -> bool {
     i.attrs.iter().any(|attr| attr.check_name("allow_fail"))
 }
 
+fn test_timeout(i: &ast::Item, cx: &TestCtxt) -> Option<u64> {
+    let attr = match i.attrs.iter().find(|attr| attr.check_name("timeout")) {
+        Some(attr) => attr,
+        None => return None,
+    };
+    match attr.value_str().and_then(|secs| secs.as_str().parse::<u64>().ok()) {
+        Some(secs) if secs > 0 => Some(secs),
+        _ => {
+            cx.span_diagnostic.span_err(attr.span(),
+                                        "attribute must be of the form: \
+                                         `#[timeout = \"<seconds>\"]` with a non-zero \
+                                         number of seconds");
+            None
+        }
+    }
+}
+
 fn should_panic(i: &ast::Item, cx: &TestCtxt) -> ShouldPanic {
     match i.attrs.iter().find(|attr| attr.check_name("should_panic")) {
         Some(attr) => {
@@ -674,6 +693,14 @@ fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test) -> P<ast::Expr> {
         }
     };
     let allow_fail_expr = ecx.expr_bool(span, test.allow_fail);
+    let timeout_expr = match test.timeout {
+        Some(secs) => {
+            let lit_ty = ast::LitIntType::Unsigned(ast::UintTy::U64);
+            let secs = ecx.expr_lit(span, ast::LitKind::Int(secs as u128, lit_ty));
+            ecx.expr_some(span, secs)
+        }
+        None => ecx.expr_none(span),
+    };
 
     // self::test::TestDesc { ... }
     let desc_expr = ecx.expr_struct(
@@ -682,7 +709,8 @@ fn mk_test_desc_and_fn_rec(cx: &TestCtxt, test: &Test) -> P<ast::Expr> {
         vec![field("name", name_expr),
              field("ignore", ignore_expr),
              field("should_panic", fail_expr),
-             field("allow_fail", allow_fail_expr)]);
+             field("allow_fail", allow_fail_expr),
+             field("timeout", timeout_expr)]);
 
 
     let mut visible_path = match cx.toplevel_reexport {
@@ -37,14 +37,12 @@
 #![feature(asm)]
 #![cfg_attr(unix, feature(libc))]
 #![feature(set_stdio)]
-#![feature(panic_unwind)]
 #![feature(staged_api)]
 
 extern crate getopts;
 extern crate term;
 #[cfg(unix)]
 extern crate libc;
-extern crate panic_unwind;
 
 pub use self::TestFn::*;
 pub use self::ColorConfig::*;
@@ -54,7 +52,7 @@ use self::TestEvent::*;
 use self::NamePadding::*;
 use self::OutputLocation::*;
 
-use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::panic::{self, catch_unwind, AssertUnwindSafe};
 use std::any::Any;
 use std::cmp;
 use std::collections::BTreeMap;
@@ -65,6 +63,7 @@ use std::io::prelude::*;
 use std::io;
 use std::iter::repeat;
 use std::path::PathBuf;
+use std::process;
 use std::sync::mpsc::{channel, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
@@ -77,7 +76,7 @@ pub mod test {
     pub use {Bencher, TestName, TestResult, TestDesc, TestDescAndFn, TestOpts, TrFailed,
              TrFailedMsg, TrIgnored, TrOk, Metric, MetricMap, StaticTestFn, StaticTestName,
              DynTestName, DynTestFn, run_test, test_main, test_main_static, filter_tests,
-             parse_opts, StaticBenchFn, ShouldPanic, Options};
+             parse_opts, StaticBenchFn, ShouldPanic, Options, OutputFormat};
 }
 
 pub mod stats;
@@ -214,6 +213,9 @@ pub struct TestDesc {
     pub ignore: bool,
     pub should_panic: ShouldPanic,
     pub allow_fail: bool,
+    /// Number of seconds after which the test is considered to have failed,
+    /// overriding `--test-timeout`.
+    pub timeout: Option<u64>,
 }
 
 #[derive(Clone)]
@@ -276,6 +278,9 @@ impl Options {
 // The default console test runner. It accepts the command line
 // arguments and a vector of test_descs.
 pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Options) {
+    if let Ok(name) = env::var(ISOLATED_TEST_ENV) {
+        run_isolated_child(&name, tests);
+    }
     let mut opts = match parse_opts(args) {
         Some(Ok(o)) => o,
         Some(Err(msg)) => panic!("{:?}", msg),
@@ -289,7 +294,7 @@ pub fn test_main(args: &[String], tests: Vec<TestDescAndFn>, options: Options) {
     } else {
         match run_tests_console(&opts, tests) {
             Ok(true) => {}
-            Ok(false) => std::process::exit(101),
+            Ok(false) => process::exit(101),
             Err(e) => panic!("io error when running tests: {:?}", e),
         }
     }
@@ -333,6 +338,18 @@ pub enum ColorConfig {
     NeverColor,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// One line per test, human readable.
+    Pretty,
+    /// One character per test, human readable.
+    Terse,
+    /// One JSON object per event and line, for consumption by other tools.
+    Json,
+    /// A JUnit XML report, written once all tests have finished.
+    Junit,
+}
+
 #[derive(Debug)]
 pub struct TestOpts {
     pub list: bool,
@@ -344,8 +361,10 @@ pub struct TestOpts {
     pub logfile: Option<PathBuf>,
     pub nocapture: bool,
     pub color: ColorConfig,
-    pub quiet: bool,
+    pub format: OutputFormat,
     pub test_threads: Option<usize>,
+    pub test_timeout: Option<u64>,
+    pub isolate: bool,
     pub skip: Vec<String>,
     pub options: Options,
 }
@@ -363,8 +382,10 @@ impl TestOpts {
             logfile: None,
             nocapture: false,
             color: AutoColor,
-            quiet: false,
+            format: OutputFormat::Pretty,
             test_threads: None,
+            test_timeout: None,
+            isolate: false,
             skip: vec![],
             options: Options::new(),
         }
@@ -387,14 +408,26 @@ fn optgroups() -> getopts::Options {
                                    task, allow printing directly")
         .optopt("", "test-threads", "Number of threads used for running tests \
                                      in parallel", "n_threads")
+        .optopt("", "test-timeout", "Fail tests which run for longer than this many \
+                                     seconds", "SECS")
+        .optflag("", "isolate", "Run each test in its own process, so that a test \
+                                 which crashes or aborts only fails itself")
         .optmulti("", "skip", "Skip tests whose names contain FILTER (this flag can \
                                be used multiple times)","FILTER")
-        .optflag("q", "quiet", "Display one character per test instead of one line")
+        .optflag("q", "quiet", "Display one character per test instead of one line. \
+                                Alias to --format=terse")
         .optflag("", "exact", "Exactly match filters rather than by substring")
         .optopt("", "color", "Configure coloring of output:
             auto   = colorize if stdout is a tty and tests are run on serially (default);
             always = always colorize output;
-            never  = never colorize output;", "auto|always|never");
+            never  = never colorize output;", "auto|always|never")
+        .optopt("", "format", "Configure formatting of output (unstable):
+            pretty = Print verbose output;
+            terse  = Display one character per test;
+            json   = Output a json document;
+            junit  = Output a JUnit XML report", "pretty|terse|json|junit")
+        .optopt("Z", "", "Enable nightly-only flags:
+            unstable-options = Allow use of experimental features", "unstable-options");
     return opts
 }
 
@@ -413,6 +446,17 @@ All tests have their standard output and standard error captured by default.
 This can be overridden with the --nocapture flag or setting RUST_TEST_NOCAPTURE
 environment variable to a value other than "0". Logging is not captured by default.
 
+With --isolate, the test binary is executed again for each test. A test that
+segfaults, aborts or is killed by a signal is reported as failed without
+affecting the other tests, which also makes it possible to test crates built
+with -C panic=abort.
+
+A test with a time limit (--test-timeout or #[timeout]) is always run in its
+own process, as with --isolate. Once it exceeds the limit the process is
+killed and the test is reported as failed along with the output it printed so
+far. Time limits need child processes, so --test-timeout is rejected on
+targets which can't start them, and #[timeout] is ignored there.
+
 Test Attributes:
 
     #[test]        - Indicates a function is a test to be run. This function
@@ -426,10 +470,23 @@ Test Attributes:
     #[ignore]      - When applied to a function which is already attributed as a
                      test, then the test runner will ignore these tests during
                      normal test runs. Running with --ignored will run these
-                     tests."#,
+                     tests.
+    #[timeout = "SECS"] - Fails the test if it runs for longer than SECS
+                     seconds, overriding --test-timeout. Requires
+                     #![feature(test_timeout)]."#,
              usage = options.usage(&message));
 }
 
+// FIXME: Copied from libsyntax until linkage errors are resolved.
+fn is_nightly() -> bool {
+    // Whether this is a feature-staged build, i.e. on the beta or stable channel
+    let disable_unstable_features = option_env!("CFG_DISABLE_UNSTABLE_FEATURES").is_some();
+    // Whether we should enable unstable features for bootstrapping
+    let bootstrap = env::var("RUSTC_BOOTSTRAP").is_ok();
+
+    bootstrap || !disable_unstable_features
+}
+
 // Parses command line arguments into test options
 pub fn parse_opts(args: &[String]) -> Option<OptRes> {
     let opts = optgroups();
@@ -449,6 +506,18 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         None
     };
 
+    let allow_unstable = match matches.opt_str("Z") {
+        Some(ref opt) if opt == "unstable-options" => {
+            if !is_nightly() {
+                return Some(Err("the option `Z` is only accepted on the nightly compiler"
+                                    .to_string()));
+            }
+            true
+        }
+        Some(opt) => return Some(Err(format!("unrecognized option to `Z`: {}", opt))),
+        None => false,
+    };
+
     let run_ignored = matches.opt_present("ignored");
     let quiet = matches.opt_present("quiet");
     let exact = matches.opt_present("exact");
@@ -482,6 +551,24 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
             None,
     };
 
+    let test_timeout = match matches.opt_str("test-timeout") {
+        Some(secs) =>
+            match secs.parse::<u64>() {
+                Ok(0) =>
+                    return Some(Err(format!("argument for --test-timeout must not be 0"))),
+                Ok(n) => Some(n),
+                Err(e) =>
+                    return Some(Err(format!("argument for --test-timeout must be a number > 0 \
+                                             (error: {})", e)))
+            },
+        None =>
+            None,
+    };
+    if test_timeout.is_some() && !supports_isolation() {
+        return Some(Err("--test-timeout is not supported on this target, \
+                         which cannot run tests in processes of their own".to_string()));
+    }
+
     let color = match matches.opt_str("color").as_ref().map(|s| &**s) {
         Some("auto") | None => AutoColor,
         Some("always") => AlwaysColor,
@@ -494,6 +581,24 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         }
     };
 
+    if matches.opt_present("format") && !allow_unstable {
+        return Some(Err("the `-Z unstable-options` flag must also be passed to enable \
+                         the flag `format`".to_string()));
+    }
+    let format = match matches.opt_str("format").as_ref().map(|s| &**s) {
+        None if quiet => OutputFormat::Terse,
+        Some("pretty") | None => OutputFormat::Pretty,
+        Some("terse") => OutputFormat::Terse,
+        Some("json") => OutputFormat::Json,
+        Some("junit") => OutputFormat::Junit,
+
+        Some(v) => {
+            return Some(Err(format!("argument for --format must be pretty, terse, json, or junit \
+                                     (was {})",
+                                    v)))
+        }
+    };
+
     let test_opts = TestOpts {
         list,
         filter,
@@ -504,8 +609,10 @@ pub fn parse_opts(args: &[String]) -> Option<OptRes> {
         logfile,
         nocapture,
         color,
-        quiet,
+        format,
         test_threads,
+        test_timeout,
+        isolate: matches.opt_present("isolate"),
         skip: matches.opt_strs("skip"),
         options: Options::new(),
     };
@@ -526,6 +633,7 @@ pub enum TestResult {
     TrFailedMsg(String),
     TrIgnored,
     TrAllowedFail,
+    TrTimedFail,
     TrMetrics(MetricMap),
     TrBench(BenchSamples),
 }
@@ -541,7 +649,7 @@ struct ConsoleTestState<T> {
     log_out: Option<File>,
     out: OutputLocation<T>,
     use_color: bool,
-    quiet: bool,
+    format: OutputFormat,
     total: usize,
     passed: usize,
     failed: usize,
@@ -552,6 +660,7 @@ struct ConsoleTestState<T> {
     metrics: MetricMap,
     failures: Vec<(TestDesc, Vec<u8>)>,
     not_failures: Vec<(TestDesc, Vec<u8>)>,
+    junit_results: Vec<(TestDesc, TestResult, Duration, Vec<u8>)>,
     max_name_len: usize, // number of columns to fill when aligning names
     options: Options,
 }
@@ -571,7 +680,7 @@ impl<T: Write> ConsoleTestState<T> {
             out,
             log_out,
             use_color: use_color(opts),
-            quiet: opts.quiet,
+            format: opts.format,
             total: 0,
             passed: 0,
             failed: 0,
@@ -582,6 +691,7 @@ impl<T: Write> ConsoleTestState<T> {
             metrics: MetricMap::new(),
             failures: Vec::new(),
             not_failures: Vec::new(),
+            junit_results: Vec::new(),
             max_name_len: 0,
             options: opts.options,
         })
@@ -599,6 +709,10 @@ impl<T: Write> ConsoleTestState<T> {
         self.write_short_result("ignored", "i", term::color::YELLOW)
     }
 
+    pub fn write_timed_fail(&mut self) -> io::Result<()> {
+        self.write_short_result("FAILED (time limit exceeded)", "T", term::color::RED)
+    }
+
     pub fn write_allowed_fail(&mut self) -> io::Result<()> {
         self.write_short_result("FAILED (allowed)", "a", term::color::YELLOW)
     }
@@ -613,7 +727,7 @@ impl<T: Write> ConsoleTestState<T> {
 
     pub fn write_short_result(&mut self, verbose: &str, quiet: &str, color: term::color::Color)
                               -> io::Result<()> {
-        if self.quiet {
+        if self.format == OutputFormat::Terse {
             self.write_pretty(quiet, color)
         } else {
             self.write_pretty(verbose, color)?;
@@ -656,6 +770,14 @@ impl<T: Write> ConsoleTestState<T> {
 
     pub fn write_run_start(&mut self, len: usize) -> io::Result<()> {
         self.total = len;
+        match self.format {
+            OutputFormat::Json => {
+                return self.write_json(format!(
+                    r#"{{ "type": "suite", "event": "started", "test_count": {} }}"#, len));
+            }
+            OutputFormat::Junit => return Ok(()),
+            OutputFormat::Pretty | OutputFormat::Terse => {}
+        }
         let noun = if len != 1 {
             "tests"
         } else {
@@ -665,7 +787,11 @@ impl<T: Write> ConsoleTestState<T> {
     }
 
     pub fn write_test_start(&mut self, test: &TestDesc, align: NamePadding) -> io::Result<()> {
-        if self.quiet && align != PadOnRight {
+        if self.format == OutputFormat::Json {
+            self.write_json(format!(r#"{{ "type": "test", "event": "started", "name": "{}" }}"#,
+                                    EscapedString(test.name.as_slice())))
+        } else if self.format == OutputFormat::Junit ||
+                  (self.format == OutputFormat::Terse && align != PadOnRight) {
             Ok(())
         } else {
             let name = test.padded_name(self.max_name_len, align);
@@ -679,6 +805,7 @@ impl<T: Write> ConsoleTestState<T> {
             TrFailed | TrFailedMsg(_) => self.write_failed(),
             TrIgnored => self.write_ignored(),
             TrAllowedFail => self.write_allowed_fail(),
+            TrTimedFail => self.write_timed_fail(),
             TrMetrics(ref mm) => {
                 self.write_metric()?;
                 self.write_plain(&format!(": {}\n", mm.fmt_metrics()))
@@ -690,7 +817,160 @@ impl<T: Write> ConsoleTestState<T> {
         }
     }
 
+    pub fn write_json<S: AsRef<str>>(&mut self, event: S) -> io::Result<()> {
+        self.write_plain(event)?;
+        self.write_plain("\n")
+    }
+
+    pub fn write_json_result(&mut self,
+                             desc: &TestDesc,
+                             result: &TestResult,
+                             stdout: &[u8],
+                             exec_time: Duration)
+                             -> io::Result<()> {
+        let name = EscapedString(desc.name.as_slice());
+        let exec_time = fmt_secs(exec_time);
+        let stdout = String::from_utf8_lossy(stdout);
+        // The captured output of successful tests is only interesting when
+        // it was asked for.
+        let output = |show: bool| if show && !stdout.is_empty() {
+            format!(r#", "stdout": "{}""#, EscapedString(&*stdout))
+        } else {
+            String::new()
+        };
+        let event = match *result {
+            TrOk => {
+                format!(r#"{{ "type": "test", "event": "ok", "name": "{}", "exec_time": {}{} }}"#,
+                        name, exec_time, output(self.options.display_output))
+            }
+            TrFailed => {
+                format!(concat!(r#"{{ "type": "test", "event": "failed", "name": "{}", "#,
+                                r#""exec_time": {}{} }}"#),
+                        name, exec_time, output(true))
+            }
+            TrFailedMsg(ref msg) => {
+                format!(concat!(r#"{{ "type": "test", "event": "failed", "name": "{}", "#,
+                                r#""exec_time": {}, "message": "{}"{} }}"#),
+                        name, exec_time, EscapedString(msg), output(true))
+            }
+            TrTimedFail => {
+                format!(concat!(r#"{{ "type": "test", "event": "failed", "name": "{}", "#,
+                                r#""exec_time": {}, "reason": "time limit exceeded"{} }}"#),
+                        name, exec_time, output(true))
+            }
+            TrIgnored => {
+                format!(r#"{{ "type": "test", "event": "ignored", "name": "{}" }}"#, name)
+            }
+            TrAllowedFail => {
+                format!(concat!(r#"{{ "type": "test", "event": "allowed_failure", "name": "{}", "#,
+                                r#""exec_time": {}{} }}"#),
+                        name, exec_time, output(true))
+            }
+            TrMetrics(ref mm) => {
+                let MetricMap(ref mm) = *mm;
+                let metrics = mm.iter().map(|(k, v)| {
+                    format!(r#""{}": {{ "value": {}, "noise": {} }}"#,
+                            EscapedString(k), v.value, v.noise)
+                }).collect::<Vec<_>>();
+                format!(r#"{{ "type": "metric", "name": "{}", "metrics": {{ {} }} }}"#,
+                        name, metrics.join(", "))
+            }
+            TrBench(ref bs) => {
+                let summ = &bs.ns_iter_summ;
+                format!(concat!(r#"{{ "type": "bench", "name": "{}", "median": {}, "#,
+                                r#""deviation": {}, "min": {}, "max": {}, "mean": {}, "#,
+                                r#""std_dev": {}, "mb_s": {} }}"#),
+                        name,
+                        summ.median,
+                        summ.max - summ.min,
+                        summ.min,
+                        summ.max,
+                        summ.mean,
+                        summ.std_dev,
+                        bs.mb_s)
+            }
+        };
+        self.write_json(event)
+    }
+
+    pub fn write_junit_report(&mut self) -> io::Result<()> {
+        // Test binaries are named after their crate, which makes for a
+        // reasonable suite name.
+        let suite = env::args()
+            .next()
+            .and_then(|arg0| {
+                PathBuf::from(arg0).file_stem().map(|s| s.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| "rust-tests".to_string());
+        let total_time = self.junit_results.iter()
+                                           .fold(Duration::new(0, 0), |acc, r| acc + r.2);
+
+        let mut report = String::new();
+        report.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        report.push_str("<testsuites>\n");
+        report.push_str(&format!("<testsuite name=\"{}\" tests=\"{}\" errors=\"0\" \
+                                  failures=\"{}\" skipped=\"{}\" time=\"{}\">\n",
+                                 XmlEscapedString(&suite),
+                                 self.junit_results.len(),
+                                 self.failed,
+                                 self.ignored,
+                                 fmt_secs(total_time)));
+        for &(ref desc, ref result, exec_time, ref stdout) in &self.junit_results {
+            // JUnit consumers expect a class and a name; use the module path
+            // of the test as the class.
+            let name = desc.name.as_slice();
+            let (class, name) = match name.rfind("::") {
+                Some(i) => (format!("{}::{}", suite, &name[..i]), &name[i + 2..]),
+                None => (suite.clone(), name),
+            };
+            report.push_str(&format!("<testcase classname=\"{}\" name=\"{}\" time=\"{}\"",
+                                     XmlEscapedString(&class),
+                                     XmlEscapedString(name),
+                                     fmt_secs(exec_time)));
+            let stdout = String::from_utf8_lossy(stdout);
+            match *result {
+                TrIgnored => report.push_str("><skipped/></testcase>\n"),
+                TrFailed | TrFailedMsg(_) | TrTimedFail => {
+                    let (ty, msg) = match *result {
+                        TrFailedMsg(ref msg) => ("assert", &msg[..]),
+                        TrTimedFail => ("timeout", "time limit exceeded"),
+                        _ => ("assert", "test failed"),
+                    };
+                    report.push_str(&format!(">\n<failure type=\"{}\" message=\"{}\"/>\n",
+                                             ty,
+                                             XmlEscapedString(msg)));
+                    if !stdout.is_empty() {
+                        report.push_str(&format!("<system-out>{}</system-out>\n",
+                                                 XmlEscapedString(&*stdout)));
+                    }
+                    report.push_str("</testcase>\n");
+                }
+                TrOk | TrAllowedFail | TrMetrics(_) | TrBench(_) => {
+                    if self.options.display_output && !stdout.is_empty() {
+                        report.push_str(&format!(">\n<system-out>{}</system-out>\n</testcase>\n",
+                                                 XmlEscapedString(&*stdout)));
+                    } else {
+                        report.push_str("/>\n");
+                    }
+                }
+            }
+        }
+        report.push_str("</testsuite>\n");
+        report.push_str("</testsuites>\n");
+        self.write_plain(report)
+    }
+
     pub fn write_timeout(&mut self, desc: &TestDesc) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                return self.write_json(format!(
+                    r#"{{ "type": "test", "event": "timeout", "name": "{}" }}"#,
+                    EscapedString(desc.name.as_slice())));
+            }
+            // Anything on stdout would corrupt the report.
+            OutputFormat::Junit => return Ok(()),
+            OutputFormat::Pretty | OutputFormat::Terse => {}
+        }
         self.write_plain(&format!("test {} has been running for over {} seconds\n",
                                   desc.name,
                                   TEST_WARN_TIMEOUT_S))
@@ -713,6 +993,7 @@ impl<T: Write> ConsoleTestState<T> {
                         TrFailedMsg(ref msg) => format!("failed: {}", msg),
                         TrIgnored => "ignored".to_owned(),
                         TrAllowedFail => "failed (allowed)".to_owned(),
+                        TrTimedFail => "failed (time limit exceeded)".to_owned(),
                         TrMetrics(ref mm) => mm.fmt_metrics(),
                         TrBench(ref bs) => fmt_bench_samples(bs),
                     },
@@ -775,6 +1056,27 @@ impl<T: Write> ConsoleTestState<T> {
         assert!(self.passed + self.failed + self.ignored + self.measured +
                     self.allowed_fail == self.total);
 
+        if self.format == OutputFormat::Json {
+            let success = self.failed == 0;
+            let event = format!(concat!(r#"{{ "type": "suite", "event": "{}", "passed": {}, "#,
+                                        r#""failed": {}, "allowed_fail": {}, "ignored": {}, "#,
+                                        r#""measured": {}, "filtered_out": {} }}"#),
+                                if success { "ok" } else { "failed" },
+                                self.passed,
+                                self.failed,
+                                self.allowed_fail,
+                                self.ignored,
+                                self.measured,
+                                self.filtered_out);
+            self.write_json(event)?;
+            return Ok(success);
+        }
+
+        if self.format == OutputFormat::Junit {
+            self.write_junit_report()?;
+            return Ok(self.failed == 0);
+        }
+
         if self.options.display_output {
             self.write_outputs()?;
         }
@@ -813,6 +1115,64 @@ impl<T: Write> ConsoleTestState<T> {
     }
 }
 
+/// A formatting utility used to print strings with characters in need of
+/// escaping in JSON.
+struct EscapedString<S: AsRef<str>>(S);
+
+impl<S: AsRef<str>> fmt::Display for EscapedString<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = self.0.as_ref();
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            let escaped = match c {
+                '"' => "\\\"",
+                '\\' => "\\\\",
+                '\n' => "\\n",
+                '\r' => "\\r",
+                '\t' => "\\t",
+                c if (c as u32) < 0x20 => {
+                    f.write_str(&s[start..i])?;
+                    write!(f, "\\u{:04x}", c as u32)?;
+                    start = i + 1;
+                    continue
+                }
+                _ => continue,
+            };
+            f.write_str(&s[start..i])?;
+            f.write_str(escaped)?;
+            start = i + 1;
+        }
+        f.write_str(&s[start..])
+    }
+}
+
+/// A formatting utility used to print strings with characters in need of
+/// escaping in XML text and attribute values.
+struct XmlEscapedString<S: AsRef<str>>(S);
+
+impl<S: AsRef<str>> fmt::Display for XmlEscapedString<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for c in self.0.as_ref().chars() {
+            match c {
+                '<' => f.write_str("&lt;")?,
+                '>' => f.write_str("&gt;")?,
+                '&' => f.write_str("&amp;")?,
+                '"' => f.write_str("&quot;")?,
+                '\'' => f.write_str("&apos;")?,
+                '\n' | '\r' | '\t' => write!(f, "{}", c)?,
+                // Other control characters can't be represented in XML 1.0
+                c if (c as u32) < 0x20 => f.write_str("&#xFFFD;")?,
+                c => write!(f, "{}", c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+fn fmt_secs(d: Duration) -> String {
+    format!("{}.{:03}", d.as_secs(), d.subsec_nanos() / 1_000_000)
+}
+
 // Format a number with thousands separators
 fn fmt_thousands_sep(mut n: usize, sep: char) -> String {
     use std::fmt::Write;
@@ -873,7 +1233,12 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
             StaticMetricFn(..) | DynMetricFn(..) => { nmetric += 1; "metric" },
         };
 
-        st.write_plain(format!("{}: {}\n", name, fntype))?;
+        if opts.format == OutputFormat::Json {
+            st.write_json(format!(r#"{{ "type": "{}", "event": "discovered", "name": "{}" }}"#,
+                                  fntype, EscapedString(name.as_slice())))?;
+        } else {
+            st.write_plain(format!("{}: {}\n", name, fntype))?;
+        }
         st.write_log(format!("{} {}\n", fntype, name))?;
     }
 
@@ -884,7 +1249,7 @@ pub fn list_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Res
         }
     }
 
-    if !opts.quiet {
+    if opts.format == OutputFormat::Pretty {
         if ntest != 0 || nbench != 0 || nmetric != 0 {
             st.write_plain("\n")?;
         }
@@ -906,9 +1271,18 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
             TeFilteredOut(filtered_out) => Ok(st.filtered_out = filtered_out),
             TeWait(ref test, padding) => st.write_test_start(test, padding),
             TeTimeout(ref test) => st.write_timeout(test),
-            TeResult(test, result, stdout) => {
+            TeResult(test, result, stdout, exec_time) => {
                 st.write_log_result(&test, &result)?;
-                st.write_result(&result)?;
+                match st.format {
+                    OutputFormat::Json => {
+                        st.write_json_result(&test, &result, &stdout, exec_time)?
+                    }
+                    OutputFormat::Junit => {
+                        st.junit_results.push((test.clone(), result.clone(), exec_time,
+                                               stdout.clone()))
+                    }
+                    OutputFormat::Pretty | OutputFormat::Terse => st.write_result(&result)?,
+                }
                 match result {
                     TrOk => {
                         st.passed += 1;
@@ -931,7 +1305,7 @@ pub fn run_tests_console(opts: &TestOpts, tests: Vec<TestDescAndFn>) -> io::Resu
                                                  bs.ns_iter_summ.max - bs.ns_iter_summ.min);
                         st.measured += 1
                     }
-                    TrFailed => {
+                    TrFailed | TrTimedFail => {
                         st.failed += 1;
                         st.failures.push((test, stdout));
                     }
@@ -971,6 +1345,7 @@ fn should_sort_failures_before_printing_them() {
         ignore: false,
         should_panic: ShouldPanic::No,
         allow_fail: false,
+        timeout: None,
     };
 
     let test_b = TestDesc {
@@ -978,13 +1353,14 @@ fn should_sort_failures_before_printing_them() {
         ignore: false,
         should_panic: ShouldPanic::No,
         allow_fail: false,
+        timeout: None,
     };
 
     let mut st = ConsoleTestState {
         log_out: None,
         out: Raw(Vec::new()),
         use_color: false,
-        quiet: false,
+        format: OutputFormat::Pretty,
         total: 0,
         passed: 0,
         failed: 0,
@@ -997,6 +1373,7 @@ fn should_sort_failures_before_printing_them() {
         failures: vec![(test_b, Vec::new()), (test_a, Vec::new())],
         options: Options::new(),
         not_failures: Vec::new(),
+        junit_results: Vec::new(),
     };
 
     st.write_failures().unwrap();
@@ -1049,7 +1426,7 @@ fn stdout_isatty() -> bool {
 pub enum TestEvent {
     TeFiltered(Vec<TestDesc>),
     TeWait(TestDesc, NamePadding),
-    TeResult(TestDesc, TestResult, Vec<u8>),
+    TeResult(TestDesc, TestResult, Vec<u8>, Duration),
     TeTimeout(TestDesc),
     TeFilteredOut(usize),
 }
@@ -1099,6 +1476,7 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
     let (tx, rx) = channel::<MonitorMsg>();
 
     let mut running_tests: HashMap<TestDesc, Instant> = HashMap::new();
+    let mut start_times: HashMap<TestDesc, Instant> = HashMap::new();
 
     fn get_timed_out_tests(running_tests: &mut HashMap<TestDesc, Instant>) -> Vec<TestDesc> {
         let now = Instant::now();
@@ -1130,8 +1508,10 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
                 // that hang forever.
                 callback(TeWait(test.desc.clone(), test.testfn.padding()))?;
             }
-            let timeout = Instant::now() + Duration::from_secs(TEST_WARN_TIMEOUT_S);
+            let now = Instant::now();
+            let timeout = now + Duration::from_secs(TEST_WARN_TIMEOUT_S);
             running_tests.insert(test.desc.clone(), timeout);
+            start_times.insert(test.desc.clone(), now);
             run_test(opts, !opts.run_tests, test, tx.clone());
             pending += 1;
         }
@@ -1154,11 +1534,12 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
 
         let (desc, result, stdout) = res.unwrap();
         running_tests.remove(&desc);
+        let exec_time = start_times.remove(&desc).unwrap().elapsed();
 
         if concurrency != 1 {
             callback(TeWait(desc.clone(), PadNone))?;
         }
-        callback(TeResult(desc, result, stdout))?;
+        callback(TeResult(desc, result, stdout, exec_time))?;
         pending -= 1;
     }
 
@@ -1167,9 +1548,10 @@ pub fn run_tests<F>(opts: &TestOpts, tests: Vec<TestDescAndFn>, mut callback: F)
         // (this includes metric fns)
         for b in filtered_benchs_and_metrics {
             callback(TeWait(b.desc.clone(), b.testfn.padding()))?;
+            let start = Instant::now();
             run_test(opts, false, b, tx.clone());
             let (test, result, stdout) = rx.recv().unwrap();
-            callback(TeResult(test, result, stdout))?;
+            callback(TeResult(test, result, stdout, start.elapsed()))?;
         }
     }
     Ok(())
@@ -1375,6 +1757,12 @@ pub fn convert_benchmarks_to_tests(tests: Vec<TestDescAndFn>) -> Vec<TestDescAnd
     }).collect()
 }
 
+/// Whether tests can be run in processes of their own, which is how a test
+/// exceeding its time limit is stopped.
+fn supports_isolation() -> bool {
+    !cfg!(target_os = "emscripten") && !cfg!(target_arch = "wasm32")
+}
+
 pub fn run_test(opts: &TestOpts,
                 force_ignore: bool,
                 test: TestDescAndFn,
@@ -1446,7 +1834,18 @@ pub fn run_test(opts: &TestOpts,
         }
     }
 
+    // A thread can't be stopped, so tests with a time limit run in a process
+    // of their own, which is killed once they exceed it.
+    let timeout = if supports_isolation() {
+        desc.timeout.or(opts.test_timeout)
+    } else {
+        None
+    };
     match testfn {
+        DynTestFn(_) | StaticTestFn(_) if opts.isolate || timeout.is_some() => {
+            run_test_isolated(desc, monitor_ch, opts.nocapture, timeout);
+            return;
+        }
         DynBenchFn(bencher) => {
             let bs = ::bench::benchmark(|harness| bencher.run(harness));
             monitor_ch.send((desc, TrBench(bs), Vec::new())).unwrap();
@@ -1487,6 +1886,174 @@ fn __rust_begin_short_backtrace<F: FnOnce()>(f: F) {
     f()
 }
 
+/// Set in the environment of the processes started by `--isolate`, naming the
+/// single test that process should run.
+const ISOLATED_TEST_ENV: &'static str = "__RUST_TEST_ISOLATED_TEST";
+
+/// Exit code of an isolated test process whose test failed normally.
+const ISOLATED_TEST_FAILED: i32 = 101;
+
+/// Runs the test in a new process of the current test binary, waiting for it
+/// on a separate thread like the in-process tests.
+fn run_test_isolated(desc: TestDesc,
+                     monitor_ch: Sender<MonitorMsg>,
+                     nocapture: bool,
+                     timeout: Option<u64>) {
+    let name = desc.name.as_slice().to_owned();
+    let cfg = thread::Builder::new().name(name.clone());
+    let runtest = move || {
+        let (result, output) = match spawn_isolated_test(&name, nocapture, timeout) {
+            Ok((Some(status), output)) => (calc_isolated_result(&desc, status), output),
+            Ok((None, output)) => (TrTimedFail, output),
+            Err(e) => {
+                let msg = format!("failed to run test process: {}", e);
+                (TrFailedMsg(msg), Vec::new())
+            }
+        };
+        let _ = monitor_ch.send((desc, result, output));
+    };
+    cfg.spawn(runtest).unwrap();
+}
+
+/// Runs the test in a child process and waits for it, killing it once it
+/// exceeds `timeout` seconds. Returns `None` as the exit status if it had to
+/// be killed, along with everything it printed.
+fn spawn_isolated_test(name: &str, nocapture: bool, timeout: Option<u64>)
+                       -> io::Result<(Option<process::ExitStatus>, Vec<u8>)> {
+    let mut cmd = process::Command::new(env::current_exe()?);
+    // Pass the original arguments along as well, so that test runners
+    // which build their tests from them (e.g. rustdoc) see the same ones.
+    cmd.args(env::args_os().skip(1))
+       .env(ISOLATED_TEST_ENV, name)
+       .stdin(process::Stdio::null());
+    if !nocapture {
+        cmd.stdout(process::Stdio::piped()).stderr(process::Stdio::piped());
+    }
+    let mut child = cmd.spawn()?;
+
+    fn read_all<R: Read + Send + 'static>(r: Option<R>) -> Option<thread::JoinHandle<Vec<u8>>> {
+        r.map(|mut r| thread::spawn(move || {
+            let mut buf = Vec::new();
+            let _ = r.read_to_end(&mut buf);
+            buf
+        }))
+    }
+    let stdout = read_all(child.stdout.take());
+    let stderr = read_all(child.stderr.take());
+
+    let status = match timeout {
+        None => Some(child.wait()?),
+        Some(secs) => {
+            let deadline = Instant::now() + Duration::from_secs(secs);
+            loop {
+                if let Some(status) = child.try_wait()? {
+                    break Some(status);
+                }
+                if Instant::now() >= deadline {
+                    child.kill()?;
+                    child.wait()?;
+                    break None;
+                }
+                thread::sleep(Duration::from_millis(10));
+            }
+        }
+    };
+
+    let mut output = Vec::new();
+    for reader in stdout.into_iter().chain(stderr) {
+        output.extend(reader.join().unwrap_or_default());
+    }
+    Ok((status, output))
+}
+
+fn calc_isolated_result(desc: &TestDesc, status: process::ExitStatus) -> TestResult {
+    if status.success() {
+        return TrOk;
+    }
+    let failure = match status.code() {
+        Some(ISOLATED_TEST_FAILED) => TrFailed,
+        Some(code) => TrFailedMsg(format!("test process exited with code {}", code)),
+        None => TrFailedMsg(abnormal_termination_msg(status)),
+    };
+    if desc.allow_fail { TrAllowedFail } else { failure }
+}
+
+#[cfg(unix)]
+fn abnormal_termination_msg(status: process::ExitStatus) -> String {
+    use std::os::unix::process::ExitStatusExt;
+    match status.signal() {
+        Some(signal) => format!("test process terminated by signal {}", signal),
+        None => format!("test process terminated abnormally ({})", status),
+    }
+}
+
+#[cfg(not(unix))]
+fn abnormal_termination_msg(status: process::ExitStatus) -> String {
+    format!("test process terminated abnormally ({})", status)
+}
+
+/// The `--isolate` side of a test process: runs the single test `name` on
+/// the main thread, without capturing its output, and reports the outcome
+/// through the exit code.
+fn run_isolated_child(name: &str, tests: Vec<TestDescAndFn>) -> ! {
+    // Tests spawning the test binary themselves shouldn't end up here.
+    env::remove_var(ISOLATED_TEST_ENV);
+
+    let test = match tests.into_iter().find(|t| t.desc.name.as_slice() == name) {
+        Some(test) => test,
+        None => {
+            let _ = writeln!(io::stderr(), "no test named `{}` in this test binary", name);
+            process::exit(ISOLATED_TEST_FAILED);
+        }
+    };
+    let TestDescAndFn { desc, testfn } = test;
+
+    // With `-C panic=abort` the process is gone before `catch_unwind` could
+    // tell whether a `#[should_panic]` test did what it should, so decide
+    // on that from the panic hook instead.
+    if desc.should_panic != ShouldPanic::No {
+        let should_panic = desc.should_panic;
+        let test_thread = thread::current().id();
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            default_hook(info);
+            if thread::current().id() != test_thread {
+                return;
+            }
+            let payload = info.payload();
+            let expected = match should_panic {
+                ShouldPanic::YesWithMessage(msg) => {
+                    payload.downcast_ref::<String>()
+                           .map(|e| &**e)
+                           .or_else(|| payload.downcast_ref::<&'static str>().map(|e| *e))
+                           .map(|e| e.contains(msg))
+                           .unwrap_or(false)
+                }
+                _ => true,
+            };
+            if expected {
+                process::exit(0);
+            }
+        }));
+    }
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        match testfn {
+            StaticTestFn(f) => __rust_begin_short_backtrace(f),
+            DynTestFn(f) => __rust_begin_short_backtrace(|| f.call_box(())),
+            _ => panic!("`{}` is not a test", name),
+        }
+    }));
+    match calc_result(&desc, result) {
+        TrOk => process::exit(0),
+        TrFailedMsg(msg) => {
+            let _ = writeln!(io::stderr(), "{}", msg);
+            process::exit(ISOLATED_TEST_FAILED)
+        }
+        _ => process::exit(ISOLATED_TEST_FAILED),
+    }
+}
+
 fn calc_result(desc: &TestDesc, task_result: Result<(), Box<Any + Send>>) -> TestResult {
     match (&desc.should_panic, task_result) {
         (&ShouldPanic::No, Ok(())) |
@@ -1724,10 +2291,11 @@ pub mod bench {
 mod tests {
     use test::{TrFailed, TrFailedMsg, TrIgnored, TrOk, filter_tests, parse_opts, TestDesc,
                TestDescAndFn, TestOpts, run_test, MetricMap, StaticTestName, DynTestName,
-               DynTestFn, ShouldPanic};
+               DynTestFn, ShouldPanic, OutputFormat};
     use std::sync::mpsc::channel;
     use bench;
     use Bencher;
+    use {EscapedString, XmlEscapedString};
 
     #[test]
     pub fn do_not_run_ignored_tests() {
@@ -1740,6 +2308,7 @@ mod tests {
                 ignore: true,
                 should_panic: ShouldPanic::No,
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1758,6 +2327,7 @@ mod tests {
                 ignore: true,
                 should_panic: ShouldPanic::No,
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1778,6 +2348,7 @@ mod tests {
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1798,6 +2369,7 @@ mod tests {
                 ignore: false,
                 should_panic: ShouldPanic::YesWithMessage("error message"),
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1820,6 +2392,7 @@ mod tests {
                 ignore: false,
                 should_panic: ShouldPanic::YesWithMessage(expected),
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1838,6 +2411,7 @@ mod tests {
                 ignore: false,
                 should_panic: ShouldPanic::Yes,
                 allow_fail: false,
+                timeout: None,
             },
             testfn: DynTestFn(Box::new(move |()| f())),
         };
@@ -1857,6 +2431,68 @@ mod tests {
         assert!((opts.run_ignored));
     }
 
+    #[test]
+    fn parse_format_flag() {
+        let args = vec!["progname".to_string(),
+                        "-Zunstable-options".to_string(),
+                        "--format=json".to_string()];
+        let opts = match parse_opts(&args) {
+            Some(Ok(o)) => o,
+            _ => panic!("Malformed arg in parse_format_flag"),
+        };
+        assert_eq!(opts.format, OutputFormat::Json);
+
+        let args = vec!["progname".to_string(), "-q".to_string()];
+        let opts = parse_opts(&args).unwrap().unwrap();
+        assert_eq!(opts.format, OutputFormat::Terse);
+
+        let args = vec!["progname".to_string(),
+                        "-Zunstable-options".to_string(),
+                        "--format=junit".to_string()];
+        let opts = parse_opts(&args).unwrap().unwrap();
+        assert_eq!(opts.format, OutputFormat::Junit);
+
+        let args = vec!["progname".to_string(),
+                        "-Zunstable-options".to_string(),
+                        "--format=xml".to_string()];
+        assert!(parse_opts(&args).unwrap().is_err());
+
+        // `--format` is unstable
+        let args = vec!["progname".to_string(), "--format=pretty".to_string()];
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+
+    #[test]
+    fn xml_escaped_string() {
+        assert_eq!(XmlEscapedString("plain").to_string(), "plain");
+        assert_eq!(XmlEscapedString("<a href=\"x\">&'").to_string(),
+                   "&lt;a href=&quot;x&quot;&gt;&amp;&apos;");
+    }
+
+    #[test]
+    fn parse_test_timeout() {
+        let args = vec!["progname".to_string(), "--test-timeout=30".to_string()];
+        let opts = parse_opts(&args).unwrap().unwrap();
+        assert_eq!(opts.test_timeout, Some(30));
+
+        let args = vec!["progname".to_string(), "--test-timeout=0".to_string()];
+        assert!(parse_opts(&args).unwrap().is_err());
+    }
+
+    #[test]
+    fn parse_isolate_flag() {
+        let args = vec!["progname".to_string(), "--isolate".to_string()];
+        let opts = parse_opts(&args).unwrap().unwrap();
+        assert!(opts.isolate);
+    }
+
+    #[test]
+    fn json_escaped_string() {
+        assert_eq!(EscapedString("plain").to_string(), "plain");
+        assert_eq!(EscapedString("a \"b\" \\ c").to_string(), r#"a \"b\" \\ c"#);
+        assert_eq!(EscapedString("line\n\ttab\u{1}").to_string(), r"line\n\ttab\u0001");
+    }
+
     #[test]
     pub fn filter_for_ignored_option() {
         // When we run ignored tests the test filter should filter out all the
@@ -1872,6 +2508,7 @@ mod tests {
                                  ignore: true,
                                  should_panic: ShouldPanic::No,
                                  allow_fail: false,
+                                 timeout: None,
                              },
                              testfn: DynTestFn(Box::new(move |()| {})),
                          },
@@ -1881,6 +2518,7 @@ mod tests {
                                  ignore: false,
                                  should_panic: ShouldPanic::No,
                                  allow_fail: false,
+                                 timeout: None,
                              },
                              testfn: DynTestFn(Box::new(move |()| {})),
                          }];
@@ -1905,6 +2543,7 @@ mod tests {
                     ignore: false,
                     should_panic: ShouldPanic::No,
                     allow_fail: false,
+                    timeout: None,
                 },
                 testfn: DynTestFn(Box::new(move |()| {}))
             })
@@ -1987,6 +2626,7 @@ mod tests {
                         ignore: false,
                         should_panic: ShouldPanic::No,
                         allow_fail: false,
+                        timeout: None,
                     },
                     testfn: DynTestFn(Box::new(move |()| testfn())),
                 };
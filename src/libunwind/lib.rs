@@ -18,14 +18,21 @@
 #![feature(unwind_attributes)]
 #![feature(static_nobundle)]
 
-#![cfg_attr(not(target_env = "msvc"), feature(libc))]
+#![cfg_attr(not(any(target_env = "msvc",
+                    all(target_arch = "wasm32", not(target_os = "emscripten")))),
+            feature(libc))]
 
-#[cfg(not(target_env = "msvc"))]
+// MSVC doesn't need any extra unwinder support, and there's no unwinder at all
+// on wasm outside of emscripten.
+#[cfg(not(any(target_env = "msvc",
+              all(target_arch = "wasm32", not(target_os = "emscripten")))))]
 extern crate libc;
 
-#[cfg(not(target_env = "msvc"))]
+#[cfg(not(any(target_env = "msvc",
+              all(target_arch = "wasm32", not(target_os = "emscripten")))))]
 mod libunwind;
-#[cfg(not(target_env = "msvc"))]
+#[cfg(not(any(target_env = "msvc",
+              all(target_arch = "wasm32", not(target_os = "emscripten")))))]
 pub use libunwind::*;
 
 #[cfg(all(target_env = "musl", not(target_arch = "mips")))]
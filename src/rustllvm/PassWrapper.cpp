@@ -10,6 +10,7 @@
 
 #include <stdio.h>
 
+#include <algorithm>
 #include <vector>
 
 #include "rustllvm.h"
@@ -29,8 +30,25 @@
 #include "llvm/Transforms/IPO/AlwaysInliner.h"
 #endif
 
+#if LLVM_VERSION_GE(5, 0)
+#include "llvm/ADT/StringSet.h"
+#include "llvm/Bitcode/BitcodeReader.h"
+#include "llvm/IR/ModuleSummaryIndex.h"
+#include "llvm/LTO/LTO.h"
+#include "llvm/Transforms/IPO.h"
+#include "llvm/Transforms/IPO/FunctionImport.h"
+#include "llvm/Transforms/Utils/FunctionImportUtils.h"
+#endif
+
 #include "llvm-c/Transforms/PassManagerBuilder.h"
 
+#ifdef LLVM_COMPONENT_PNACL
+#include "llvm/ADT/Triple.h"
+#include "llvm/Analysis/NaCl.h"
+#include "llvm/Bitcode/NaCl/NaClReaderWriter.h"
+#include "llvm/Transforms/NaCl.h"
+#endif
+
 using namespace llvm;
 using namespace llvm::legacy;
 
@@ -682,6 +700,16 @@ extern "C" void LLVMRustAddAlwaysInlinePass(LLVMPassManagerBuilderRef PMBR,
 #endif
 }
 
+extern "C" void
+LLVMRustPassManagerBuilderPopulateThinLTOPassManager(LLVMPassManagerBuilderRef PMBR,
+                                                     LLVMPassManagerRef PMR) {
+#if LLVM_VERSION_GE(4, 0)
+  unwrap(PMBR)->populateThinLTOPassManager(*unwrap(PMR));
+#else
+  report_fatal_error("ThinLTO not available");
+#endif
+}
+
 extern "C" void LLVMRustRunRestrictionPass(LLVMModuleRef M, char **Symbols,
                                            size_t Len) {
   llvm::legacy::PassManager passes;
@@ -740,3 +768,534 @@ extern "C" void LLVMRustSetModulePIELevel(LLVMModuleRef M) {
   unwrap(M)->setPIELevel(PIELevel::Level::Large);
 #endif
 }
+
+// Here you'll find an implementation of ThinLTO as used by the Rust compiler
+// right now. This ThinLTO support is only enabled on "recent ish" versions of
+// LLVM, and otherwise it's just blanket rejected from other compilers.
+//
+// Most of this implementation is straight copied from LLVM. At the time of
+// this writing it wasn't *quite* suitable to reuse more code from upstream
+// for our purposes, but we should strive to upstream this support once it's
+// ready to go! I figure we may want a bit of testing locally first before
+// sending this upstream to LLVM. I hear though they're quite eager to receive
+// feedback like this!
+//
+// If you're reading this code and wondering "what in the world" or you're
+// working "good lord my LLVM upgrade is *still* failing due to these bindings"
+// then fear not! (ok maybe fear a little). All code here is mostly based
+// on `lib/LTO/ThinLTOCodeGenerator.cpp` in LLVM.
+//
+// You'll find that the general layout here roughly corresponds to the `run`
+// method in that file as well as `ProcessThinLTOModule`. Functions are
+// specifically commented below as well, but if you're updating this code
+// or otherwise trying to understand it, the LLVM source will be useful in
+// interpreting the mysteries within.
+#if LLVM_VERSION_GE(5, 0)
+
+// This is a shared data structure which *must* be threadsafe to share
+// read-only amongst threads. This also corresponds basically to the arguments
+// of the `ProcessThinLTOModule` function in the LLVM source.
+struct LLVMRustThinLTOData {
+  // The combined index that is the global analysis over all modules we're
+  // performing ThinLTO for. This is mostly managed by LLVM.
+  ModuleSummaryIndex Index;
+
+  // All modules we may look at, stored as in-memory serialized versions. This
+  // is later used when inlining to ensure we can extract any module to inline
+  // from.
+  StringMap<MemoryBufferRef> ModuleMap;
+
+  // A set that we manage of everything we *don't* want internalized. Note that
+  // this includes all transitive references right now as well, but it may not
+  // always!
+  DenseSet<GlobalValue::GUID> GUIDPreservedSymbols;
+
+  // The cross-module import and export lists computed from the combined
+  // index, which drive what gets imported into which module and what has to
+  // stay exported as a result.
+  StringMap<FunctionImporter::ImportMapTy> ImportLists;
+  StringMap<FunctionImporter::ExportSetTy> ExportLists;
+  StringMap<GVSummaryMapTy> ModuleToDefinedGVSummaries;
+};
+
+// Just an argument to the `LLVMRustCreateThinLTOData` function below.
+struct LLVMRustThinLTOModule {
+  const char *identifier;
+  const char *data;
+  size_t len;
+};
+
+// Picks the copy of a symbol the linker would consider prevailing: a strong
+// definition if there is one, otherwise the first copy which isn't just
+// `available_externally`. Copied from `lib/LTO/ThinLTOCodeGenerator.cpp`.
+static const GlobalValueSummary *
+getFirstDefinitionForLinker(const GlobalValueSummaryList &GVSummaryList) {
+  auto StrongDefForLinker = llvm::find_if(
+      GVSummaryList, [](const std::unique_ptr<GlobalValueSummary> &Summary) {
+        auto Linkage = Summary->linkage();
+        return !GlobalValue::isAvailableExternallyLinkage(Linkage) &&
+               !GlobalValue::isWeakForLinker(Linkage);
+      });
+  if (StrongDefForLinker != GVSummaryList.end())
+    return StrongDefForLinker->get();
+
+  auto FirstDefForLinker = llvm::find_if(
+      GVSummaryList, [](const std::unique_ptr<GlobalValueSummary> &Summary) {
+        auto Linkage = Summary->linkage();
+        return !GlobalValue::isAvailableExternallyLinkage(Linkage);
+      });
+  if (FirstDefForLinker == GVSummaryList.end())
+    return nullptr;
+  return FirstDefForLinker->get();
+}
+
+// The main entry point for creating the global ThinLTO analysis. The structure
+// here is basically the same as before threads are spawned in the `run`
+// function of `lib/LTO/ThinLTOCodeGenerator.cpp`.
+extern "C" LLVMRustThinLTOData*
+LLVMRustCreateThinLTOData(LLVMRustThinLTOModule *modules,
+                          int num_modules,
+                          const char **preserved_symbols,
+                          int num_symbols) {
+  auto Ret = llvm::make_unique<LLVMRustThinLTOData>();
+
+  // Load each module's summary and merge it into one combined index
+  for (int i = 0; i < num_modules; i++) {
+    auto module = &modules[i];
+    StringRef buffer(module->data, module->len);
+    MemoryBufferRef mem_buffer(buffer, module->identifier);
+
+    Ret->ModuleMap[module->identifier] = mem_buffer;
+
+    if (Error Err = readModuleSummaryIndex(mem_buffer, Ret->Index, i)) {
+      LLVMRustSetLastError(toString(std::move(Err)).c_str());
+      return nullptr;
+    }
+  }
+
+  // Collect for each module the list of function it defines (GUID -> Summary)
+  Ret->Index.collectDefinedGVSummariesPerModule(Ret->ModuleToDefinedGVSummaries);
+
+  // Convert the preserved symbols set from string to GUID, this is then needed
+  // for internalization.
+  for (int i = 0; i < num_symbols; i++) {
+    auto GUID = GlobalValue::getGUID(preserved_symbols[i]);
+    Ret->GUIDPreservedSymbols.insert(GUID);
+  }
+
+  // Collect the import/export lists for all modules from the call-graph in the
+  // combined index
+  //
+  // This is copied from `lib/LTO/ThinLTOCodeGenerator.cpp`
+  computeDeadSymbols(Ret->Index, Ret->GUIDPreservedSymbols);
+  ComputeCrossModuleImport(
+    Ret->Index,
+    Ret->ModuleToDefinedGVSummaries,
+    Ret->ImportLists,
+    Ret->ExportLists
+  );
+
+  // Resolve LinkOnce/Weak symbols, this has to be computed early because it
+  // impacts the caching.
+  //
+  // This is copied from `lib/LTO/ThinLTOCodeGenerator.cpp`
+  StringMap<std::map<GlobalValue::GUID, GlobalValue::LinkageTypes>> ResolvedODR;
+  DenseMap<GlobalValue::GUID, const GlobalValueSummary *> PrevailingCopy;
+  for (auto &I : Ret->Index) {
+    if (I.second.SummaryList.size() > 1)
+      PrevailingCopy[I.first] = getFirstDefinitionForLinker(I.second.SummaryList);
+  }
+  auto isPrevailing = [&](GlobalValue::GUID GUID, const GlobalValueSummary *S) {
+    const auto &Prevailing = PrevailingCopy.find(GUID);
+    if (Prevailing == PrevailingCopy.end())
+      return true;
+    return Prevailing->second == S;
+  };
+  auto recordNewLinkage = [&](StringRef ModuleIdentifier,
+                              GlobalValue::GUID GUID,
+                              GlobalValue::LinkageTypes NewLinkage) {
+    ResolvedODR[ModuleIdentifier][GUID] = NewLinkage;
+  };
+  thinLTOResolveWeakForLinkerInIndex(Ret->Index, isPrevailing, recordNewLinkage);
+
+  // Here we calculate an `ExportedGUIDs` set for use in the `isExported`
+  // callback below. This callback below will dictate the linkage for all
+  // summaries in the index, and we basically just only want to ensure that dead
+  // symbols are internalized. Otherwise everything that's already external
+  // linkage will stay as external, and internal will stay as internal.
+  std::set<GlobalValue::GUID> ExportedGUIDs;
+  for (auto &List : Ret->Index) {
+    for (auto &GVS: List.second.SummaryList) {
+      if (GlobalValue::isLocalLinkage(GVS->linkage()))
+        continue;
+      auto GUID = GVS->getOriginalName();
+      if (GVS->flags().Live)
+        ExportedGUIDs.insert(GUID);
+    }
+  }
+  auto isExported = [&](StringRef ModuleIdentifier, GlobalValue::GUID GUID) {
+    const auto &ExportList = Ret->ExportLists.find(ModuleIdentifier);
+    return (ExportList != Ret->ExportLists.end() &&
+      ExportList->second.count(GUID)) ||
+      ExportedGUIDs.count(GUID);
+  };
+  thinLTOInternalizeAndPromoteInIndex(Ret->Index, isExported);
+
+  return Ret.release();
+}
+
+extern "C" void
+LLVMRustFreeThinLTOData(LLVMRustThinLTOData *Data) {
+  delete Data;
+}
+
+// Below are the various passes that happen *per module* when doing ThinLTO.
+//
+// In other words, these are the functions that are all run concurrently
+// with one another, one per module. The passes here correspond to the analysis
+// passes in `lib/LTO/ThinLTOCodeGenerator.cpp`, currently found in the
+// `ProcessThinLTOModule` function. Here they're split up into separate steps
+// so rustc can save off the intermediate bytecode between each step.
+
+extern "C" bool
+LLVMRustPrepareThinLTORename(const LLVMRustThinLTOData *Data, LLVMModuleRef M) {
+  Module &Mod = *unwrap(M);
+  if (renameModuleForThinLTO(Mod, Data->Index)) {
+    LLVMRustSetLastError("renameModuleForThinLTO failed");
+    return false;
+  }
+  return true;
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTOResolveWeak(const LLVMRustThinLTOData *Data, LLVMModuleRef M) {
+  Module &Mod = *unwrap(M);
+  const auto &DefinedGlobals = Data->ModuleToDefinedGVSummaries.lookup(Mod.getModuleIdentifier());
+  thinLTOResolveWeakForLinkerModule(Mod, DefinedGlobals);
+  return true;
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTOInternalize(const LLVMRustThinLTOData *Data, LLVMModuleRef M) {
+  Module &Mod = *unwrap(M);
+  const auto &DefinedGlobals = Data->ModuleToDefinedGVSummaries.lookup(Mod.getModuleIdentifier());
+  thinLTOInternalizeModule(Mod, DefinedGlobals);
+  return true;
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTOImport(const LLVMRustThinLTOData *Data, LLVMModuleRef M) {
+  Module &Mod = *unwrap(M);
+  const auto &ImportList = Data->ImportLists.lookup(Mod.getModuleIdentifier());
+  auto Loader = [&](StringRef Identifier) {
+    const auto &Memory = Data->ModuleMap.lookup(Identifier);
+    auto &Context = Mod.getContext();
+    return getLazyBitcodeModule(Memory, Context, true, true);
+  };
+  FunctionImporter Importer(Data->Index, Loader);
+  Expected<bool> Result = Importer.importFunctions(Mod, ImportList);
+  if (!Result) {
+    LLVMRustSetLastError(toString(Result.takeError()).c_str());
+    return false;
+  }
+  return true;
+}
+
+// Reports, for every module, each of the modules it imports from. rustc uses
+// this to decide whether a module's previous post-LTO artifacts can be reused
+// by incremental compilation.
+typedef void (*LLVMRustModuleNameCallback)(void*, // payload
+                                           const char*, // importing module name
+                                           const char*); // imported module name
+
+extern "C" void
+LLVMRustGetThinLTOModuleImports(const LLVMRustThinLTOData *Data,
+                                LLVMRustModuleNameCallback ModuleNameCallback,
+                                void* CallbackPayload) {
+  for (const auto &ImportingModule : Data->ImportLists) {
+    const std::string ImportingModuleId = ImportingModule.getKey().str();
+    const auto &Imports = ImportingModule.getValue();
+    for (const auto &ImportedModule : Imports) {
+      const std::string ImportedModuleId = ImportedModule.getKey().str();
+      ModuleNameCallback(CallbackPayload,
+                         ImportingModuleId.c_str(),
+                         ImportedModuleId.c_str());
+    }
+  }
+}
+
+// Describes everything the global analysis decided about the definitions of
+// a module: the linkage each one ended up with after weak symbol resolution
+// and internalization, whether it is live, and which of them other modules
+// import. Together with the import lists above this determines what the
+// per-module ThinLTO steps do to the module, so rustc hashes it to decide
+// whether a previous post-LTO object is still valid.
+extern "C" void
+LLVMRustGetThinLTOModuleLinkageKey(const LLVMRustThinLTOData *Data,
+                                   const char *ModuleId,
+                                   RustStringRef Key) {
+  RawRustStringOstream OS(Key);
+
+  const auto &DefinedGlobals = Data->ModuleToDefinedGVSummaries.lookup(ModuleId);
+  std::vector<std::pair<GlobalValue::GUID, GlobalValueSummary *>> Defined(
+      DefinedGlobals.begin(), DefinedGlobals.end());
+  std::sort(Defined.begin(), Defined.end(),
+            [](const std::pair<GlobalValue::GUID, GlobalValueSummary *> &A,
+               const std::pair<GlobalValue::GUID, GlobalValueSummary *> &B) {
+              return A.first < B.first;
+            });
+  for (const auto &Def : Defined)
+    OS << "def " << Def.first << " " << (unsigned)Def.second->linkage() << " "
+       << (Def.second->flags().Live ? "live" : "dead") << "\n";
+
+  const auto &ExportList = Data->ExportLists.find(ModuleId);
+  if (ExportList != Data->ExportLists.end()) {
+    std::vector<GlobalValue::GUID> Exported(ExportList->second.begin(),
+                                            ExportList->second.end());
+    std::sort(Exported.begin(), Exported.end());
+    for (auto GUID : Exported)
+      OS << "export " << GUID << "\n";
+  }
+}
+
+// This struct and various functions are sort of a hack right now, but the
+// problem is that we've got in-memory LLVM modules after we generate and
+// optimize all codegen-units for one compilation in rustc. To be compatible
+// with the LTO support above we need to serialize the modules plus their
+// ThinLTO summary into memory.
+//
+// This structure is basically an owned version of a serialize module, with
+// a ThinLTO summary attached.
+struct LLVMRustThinLTOBuffer {
+  std::string data;
+};
+
+extern "C" LLVMRustThinLTOBuffer*
+LLVMRustThinLTOBufferCreate(LLVMModuleRef M) {
+  auto Ret = llvm::make_unique<LLVMRustThinLTOBuffer>();
+  {
+    raw_string_ostream OS(Ret->data);
+    {
+      legacy::PassManager PM;
+      PM.add(createWriteThinLTOBitcodePass(OS));
+      PM.run(*unwrap(M));
+    }
+  }
+  return Ret.release();
+}
+
+extern "C" void
+LLVMRustThinLTOBufferFree(LLVMRustThinLTOBuffer *Buffer) {
+  delete Buffer;
+}
+
+extern "C" const void*
+LLVMRustThinLTOBufferPtr(const LLVMRustThinLTOBuffer *Buffer) {
+  return Buffer->data.data();
+}
+
+extern "C" size_t
+LLVMRustThinLTOBufferLen(const LLVMRustThinLTOBuffer *Buffer) {
+  return Buffer->data.length();
+}
+
+// This is what we use to parse upstream bitcode for actual ThinLTO
+// processing. We'll call this once per module optimized through ThinLTO, and
+// it'll be called concurrently on many threads.
+extern "C" LLVMModuleRef
+LLVMRustParseBitcodeForThinLTO(LLVMContextRef Context,
+                               const char *data,
+                               size_t len,
+                               const char *identifier) {
+  StringRef Data(data, len);
+  MemoryBufferRef Buffer(Data, identifier);
+  unwrap(Context)->enableDebugTypeODRUniquing();
+  Expected<std::unique_ptr<Module>> SrcOrError =
+      parseBitcodeFile(Buffer, *unwrap(Context));
+  if (!SrcOrError) {
+    LLVMRustSetLastError(toString(SrcOrError.takeError()).c_str());
+    return nullptr;
+  }
+  return wrap(std::move(*SrcOrError).release());
+}
+
+extern "C" bool
+LLVMRustThinLTOAvailable() {
+  return true;
+}
+
+#else
+
+extern "C" bool
+LLVMRustThinLTOAvailable() {
+  return false;
+}
+
+struct LLVMRustThinLTOData {
+};
+
+struct LLVMRustThinLTOModule {
+};
+
+extern "C" LLVMRustThinLTOData*
+LLVMRustCreateThinLTOData(LLVMRustThinLTOModule *modules,
+                          int num_modules,
+                          const char **preserved_symbols,
+                          int num_symbols) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTORename(const LLVMRustThinLTOData *Data, LLVMModuleRef M) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTOResolveWeak(const LLVMRustThinLTOData *Data, LLVMModuleRef M) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTOInternalize(const LLVMRustThinLTOData *Data, LLVMModuleRef M) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" bool
+LLVMRustPrepareThinLTOImport(const LLVMRustThinLTOData *Data, LLVMModuleRef M) {
+  report_fatal_error("ThinLTO not available");
+}
+
+typedef void (*LLVMRustModuleNameCallback)(void*, const char*, const char*);
+
+extern "C" void
+LLVMRustGetThinLTOModuleImports(const LLVMRustThinLTOData *Data,
+                                LLVMRustModuleNameCallback ModuleNameCallback,
+                                void* CallbackPayload) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" void
+LLVMRustGetThinLTOModuleLinkageKey(const LLVMRustThinLTOData *Data,
+                                   const char *ModuleId,
+                                   RustStringRef Key) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" void
+LLVMRustFreeThinLTOData(LLVMRustThinLTOData *Data) {
+  report_fatal_error("ThinLTO not available");
+}
+
+struct LLVMRustThinLTOBuffer {
+};
+
+extern "C" LLVMRustThinLTOBuffer*
+LLVMRustThinLTOBufferCreate(LLVMModuleRef M) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" void
+LLVMRustThinLTOBufferFree(LLVMRustThinLTOBuffer *Buffer) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" const void*
+LLVMRustThinLTOBufferPtr(const LLVMRustThinLTOBuffer *Buffer) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" size_t
+LLVMRustThinLTOBufferLen(const LLVMRustThinLTOBuffer *Buffer) {
+  report_fatal_error("ThinLTO not available");
+}
+
+extern "C" LLVMModuleRef
+LLVMRustParseBitcodeForThinLTO(LLVMContextRef Context,
+                               const char *data,
+                               size_t len,
+                               const char *identifier) {
+  report_fatal_error("ThinLTO not available");
+}
+#endif // LLVM_VERSION_GE(5, 0)
+
+// PNaCl's stable bitcode ABI is a strict subset of LLVM IR: things like i128,
+// varargs, aggregate arguments/returns and most intrinsics have to be lowered
+// away before a module can be frozen. The PNaCl LLVM fork provides the passes
+// doing so (split in two halves, before and after the regular optimization
+// pipeline), a verifier for the resulting ABI, and the frozen bitcode writer.
+#ifdef LLVM_COMPONENT_PNACL
+extern "C" bool LLVMRustPNaClAvailable() {
+  return true;
+}
+
+extern "C" LLVMRustResult LLVMRustPNaClSimplifyABI(LLVMModuleRef M,
+                                                   bool PostOpt) {
+  Module *Mod = unwrap(M);
+  Triple T(Mod->getTargetTriple());
+  llvm::legacy::PassManager PM;
+  if (PostOpt)
+    PNaClABISimplifyAddPostOptPasses(&T, PM);
+  else
+    PNaClABISimplifyAddPreOptPasses(&T, PM);
+  PM.run(*Mod);
+  return LLVMRustResult::Success;
+}
+
+extern "C" bool LLVMRustPNaClVerifyModule(LLVMModuleRef M,
+                                          RustStringRef Errors) {
+  Module *Mod = unwrap(M);
+  PNaClABIErrorReporter Reporter;
+  Reporter.setNonFatal();
+
+  llvm::legacy::PassManager PM;
+  PM.add(createPNaClABIVerifyModulePass(&Reporter, false));
+  PM.add(createPNaClABIVerifyFunctionsPass(&Reporter));
+  PM.run(*Mod);
+
+  if (Reporter.getErrorCount() == 0)
+    return true;
+
+  RawRustStringOstream OS(Errors);
+  Reporter.printErrors(OS);
+  return false;
+}
+
+extern "C" LLVMRustResult LLVMRustPNaClWriteBitcodeToFile(LLVMModuleRef M,
+                                                          const char *Path) {
+  std::error_code EC;
+  raw_fd_ostream OS(Path, EC, sys::fs::F_None);
+  if (EC) {
+    LLVMRustSetLastError(EC.message().c_str());
+    return LLVMRustResult::Failure;
+  }
+
+  NaClWriteBitcodeToFile(unwrap(M), OS, /* AcceptSupportedOnly = */ true);
+  return LLVMRustResult::Success;
+}
+#else
+// Upstream LLVM (including the one bundled with rustc) has none of this, so
+// rustc refuses to target le32-unknown-nacl at all when built against it. See
+// `LLVMRustPNaClAvailable` in rustc_trans's `llvm_util::init`.
+extern "C" bool LLVMRustPNaClAvailable() {
+  return false;
+}
+
+extern "C" LLVMRustResult LLVMRustPNaClSimplifyABI(LLVMModuleRef M,
+                                                   bool PostOpt) {
+  LLVMRustSetLastError("this LLVM was built without PNaCl support");
+  return LLVMRustResult::Failure;
+}
+
+extern "C" bool LLVMRustPNaClVerifyModule(LLVMModuleRef M,
+                                          RustStringRef Errors) {
+  RawRustStringOstream OS(Errors);
+  OS << "this LLVM was built without PNaCl support";
+  return false;
+}
+
+extern "C" LLVMRustResult LLVMRustPNaClWriteBitcodeToFile(LLVMModuleRef M,
+                                                          const char *Path) {
+  LLVMRustSetLastError("this LLVM was built without PNaCl support");
+  return LLVMRustResult::Failure;
+}
+#endif
@@ -10,11 +10,8 @@
 
 // compile-flags: -Zforce-overflow-checks=on
 
-// these errors are not actually "const_err", they occur in trans/consts
-// and are unconditional warnings that can't be denied or allowed
-
 #![allow(exceeding_bitshifts)]
-#![allow(const_err)]
+#![warn(const_err)]
 
 fn black_box<T>(_: T) {
     unimplemented!()
@@ -29,18 +26,23 @@ const FOO: u8 = [5u8][1];
 
 fn main() {
     let a = -std::i8::MIN;
-    //~^ WARN this expression will panic at run-time
+    //~^ WARN constant evaluation error: attempt to negate with overflow
+    //~| WARN this expression will panic at run-time
     //~| attempt to negate with overflow
     let b = 200u8 + 200u8 + 200u8;
-    //~^ WARN this expression will panic at run-time
+    //~^ WARN constant evaluation error: attempt to add with overflow
+    //~| WARN constant evaluation error: attempt to add with overflow
+    //~| WARN this expression will panic at run-time
+    //~| WARN this expression will panic at run-time
     //~| attempt to add with overflow
-    //~^^^ WARN this expression will panic at run-time
     //~| attempt to add with overflow
     let c = 200u8 * 4;
-    //~^ WARN this expression will panic at run-time
+    //~^ WARN constant evaluation error: attempt to multiply with overflow
+    //~| WARN this expression will panic at run-time
     //~| attempt to multiply with overflow
     let d = 42u8 - (42u8 + 1);
-    //~^ WARN this expression will panic at run-time
+    //~^ WARN constant evaluation error: attempt to subtract with overflow
+    //~| WARN this expression will panic at run-time
     //~| attempt to subtract with overflow
     let _e = [5u8][1];
     //~^ WARN this expression will panic at run-time
@@ -8,20 +8,23 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-#![feature(const_fn)]
+#![feature(const_fn, const_fn_control_flow)]
 
 const X : usize = 2;
 
 const fn f(x: usize) -> usize {
     let mut sum = 0;
-    for i in 0..x {
+    let mut i = 0;
+    while i < x {
         sum += i;
+        i += 1;
     }
-    sum //~ ERROR E0080
-        //~| non-constant path in constant
+    sum - 3 //~ ERROR constant evaluation error
+            //~| attempt to subtract with overflow
 }
 
 #[allow(unused_variables)]
 fn main() {
     let a : [i32; f(X)]; //~ NOTE for constant expression here
+                         //~| NOTE inside call to `f`
 }
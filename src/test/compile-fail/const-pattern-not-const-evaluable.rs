@@ -10,19 +10,19 @@
 
 #![feature(const_fn)]
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 enum Cake {
     BlackForest,
     Marmor,
 }
 use Cake::*;
 
-struct Pair<A, B>(A, B);
+const BOO: &Cake = &Marmor;
+const FOO: Cake = *BOO;
 
-const BOO: Pair<Cake, Cake> = Pair(Marmor, BlackForest);
+const SOME: Option<Cake> = Some(Marmor);
 //~^ ERROR: constant evaluation error [E0080]
-//~| unimplemented constant expression: tuple struct constructors
-const FOO: Cake = BOO.1;
+//~| unimplemented constant expression: enum variants with fields
 
 const fn foo() -> Cake {
     Marmor
@@ -34,9 +34,13 @@ const GOO: Cake = foo();
 
 fn main() {
     match BlackForest {
-        FOO => println!("hi"), //~ NOTE: for pattern here
+        FOO => println!("hi"),
         GOO => println!("meh"),
         WORKS => println!("möp"),
         _ => println!("bye"),
     }
+    match Some(BlackForest) {
+        SOME => println!("hi"), //~ NOTE: for pattern here
+        _ => println!("bye"),
+    }
 }
@@ -0,0 +1,43 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Zforce-overflow-checks=on
+
+// Test that arithmetic overflow and out of bounds indexing that always
+// happens at run-time is reported, including through local variables.
+
+#![deny(const_err)]
+
+fn black_box<T>(_: T) {
+    unimplemented!()
+}
+
+fn main() {
+    let x = 200u8;
+    let y = x + 100;
+    //~^ ERROR this expression will panic at run-time
+    //~| attempt to add with overflow
+    let arr = [1, 2, 3];
+    let i = 3;
+    let z = arr[i];
+    //~^ ERROR this expression will panic at run-time
+    //~| index out of bounds: the len is 3 but the index is 3
+    if x < 100 {
+        // Never reached, so never panics.
+        black_box(x * 2);
+    }
+    let mut n = 0u8;
+    while n < 10 {
+        n += 1;
+    }
+    black_box(n * 100);
+    black_box(y);
+    black_box(z);
+}
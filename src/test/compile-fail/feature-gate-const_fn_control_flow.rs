@@ -0,0 +1,22 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that `let` bindings in a `const fn` need `#![feature(const_fn_control_flow)]`.
+
+#![feature(const_fn)]
+
+const fn double(x: u32) -> u32 {
+    let y = x * 2; //~ ERROR blocks in constant functions are limited to items and tail expressions
+    y
+}
+
+fn main() {
+    assert_eq!(double(2), 4);
+}
@@ -0,0 +1,61 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that a branch on a known condition becomes a `goto` and that the block
+// it no longer reaches is removed.
+
+fn test() -> u32 {
+    let x = 3;
+    if x > 1 {
+        10
+    } else {
+        20
+    }
+}
+
+fn main() {
+    test();
+}
+
+// END RUST SOURCE
+// START rustc.node4.ConstProp.before.mir
+//  bb0: {
+//      _1 = const 3i32;
+//      _3 = _1;
+//      _2 = Gt(_3, const 1i32);
+//      switchInt(_2) -> [0u8: bb2, otherwise: bb1];
+//  }
+//  bb1: {
+//      _0 = const 10u32;
+//      goto -> bb3;
+//  }
+//  bb2: {
+//      _0 = const 20u32;
+//      goto -> bb3;
+//  }
+//  bb3: {
+//      return;
+//  }
+// END rustc.node4.ConstProp.before.mir
+// START rustc.node4.ConstProp.after.mir
+//  bb0: {
+//      _1 = const 3i32;
+//      _3 = const 3i32;
+//      _2 = const true;
+//      goto -> bb1;
+//  }
+//  bb1: {
+//      _0 = const 10u32;
+//      goto -> bb2;
+//  }
+//  bb2: {
+//      return;
+//  }
+// END rustc.node4.ConstProp.after.mir
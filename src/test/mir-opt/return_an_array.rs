@@ -8,11 +8,27 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-// this tests move up progration, which is not yet implemented
+// Test that the array is built directly in the return place.
 
 fn foo() -> [u8; 1024] {
         let x = [0; 1024];
         return x;
 }
 
-fn main() { }
\ No newline at end of file
+fn main() { }
+
+// END RUST SOURCE
+// START rustc.node4.DestinationPropagation.before.mir
+// bb0: {
+//     _0 = _1;
+//     return;
+// }
+// END rustc.node4.DestinationPropagation.before.mir
+// START rustc.node4.DestinationPropagation.after.mir
+// bb0: {
+//     nop;
+//     nop;
+//     nop;
+//     return;
+// }
+// END rustc.node4.DestinationPropagation.after.mir
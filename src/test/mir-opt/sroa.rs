@@ -0,0 +1,36 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn foo(a: u32, b: u32) -> u32 {
+    let t = (a, b);
+    t.0 + t.1
+}
+
+fn main() {
+    foo(1, 2);
+}
+
+// END RUST SOURCE
+// START rustc.node4.ScalarReplacementOfAggregates.before.mir
+// bb0: {
+//     StorageLive(_3);
+//     _3 = (_4, _5);
+//     _6 = (_3.0: u32);
+//     _7 = (_3.1: u32);
+// END rustc.node4.ScalarReplacementOfAggregates.before.mir
+// START rustc.node4.ScalarReplacementOfAggregates.after.mir
+// bb0: {
+//     StorageLive(_9);
+//     StorageLive(_10);
+//     _9 = _4;
+//     _10 = _5;
+//     _6 = _9;
+//     _7 = _10;
+// END rustc.node4.ScalarReplacementOfAggregates.after.mir
@@ -0,0 +1,35 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use std::process;
+
+#[test]
+fn passes() {}
+
+#[test]
+fn aborts() {
+    process::abort();
+}
+
+#[test]
+fn exits() {
+    process::exit(3);
+}
+
+#[test]
+#[should_panic(expected = "boom")]
+fn panics() {
+    panic!("boom");
+}
+
+#[test]
+fn fails() {
+    assert_eq!(1, 2);
+}
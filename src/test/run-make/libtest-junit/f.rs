@@ -0,0 +1,55 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Tests named and failing the ways a JUnit report has to escape and explain,
+// which `#[test]` functions can't be named, so they're run by hand.
+
+#![feature(test)]
+
+extern crate test;
+
+use std::env;
+use test::{DynTestFn, DynTestName, Options, ShouldPanic, TestDesc, TestDescAndFn};
+
+fn desc(name: &str, ignore: bool, should_panic: ShouldPanic) -> TestDesc {
+    TestDesc {
+        name: DynTestName(name.to_string()),
+        ignore,
+        should_panic,
+        allow_fail: false,
+        timeout: None,
+    }
+}
+
+fn main() {
+    let args = env::args().collect::<Vec<_>>();
+    let tests = vec![
+        TestDescAndFn {
+            desc: desc("passes", false, ShouldPanic::No),
+            testfn: DynTestFn(Box::new(|()| {})),
+        },
+        TestDescAndFn {
+            desc: desc("fails <&>", false, ShouldPanic::No),
+            testfn: DynTestFn(Box::new(|()| {
+                println!("print <&> from failing test");
+                panic!();
+            })),
+        },
+        TestDescAndFn {
+            desc: desc("panics::wrong", false, ShouldPanic::YesWithMessage("<expected>")),
+            testfn: DynTestFn(Box::new(|()| panic!("something else"))),
+        },
+        TestDescAndFn {
+            desc: desc("ignored", true, ShouldPanic::No),
+            testfn: DynTestFn(Box::new(|()| panic!())),
+        },
+    ];
+    test::test_main(&args, tests, Options::new());
+}
@@ -0,0 +1,52 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(test_timeout)]
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+/// The length of the file the hanging test keeps writing to.
+fn hang_log_len() -> u64 {
+    fs::metadata(env::var("HANG_LOG").unwrap()).map(|m| m.len()).unwrap_or(0)
+}
+
+#[test]
+fn quick() {}
+
+#[test]
+#[timeout = "1"]
+fn hangs() {
+    println!("about to hang");
+    let mut log = OpenOptions::new().create(true).append(true)
+                                    .open(env::var("HANG_LOG").unwrap()).unwrap();
+    loop {
+        log.write_all(b"still running\n").unwrap();
+        thread::sleep(Duration::from_millis(100));
+    }
+}
+
+#[test]
+#[timeout = "120"]
+fn slow_but_allowed() {
+    thread::sleep(Duration::from_secs(2));
+}
+
+// Runs after `hangs` with --test-threads 1: it must have been stopped.
+#[test]
+fn then_hanging_test_is_stopped() {
+    let len = hang_log_len();
+    assert!(len > 0);
+    thread::sleep(Duration::from_millis(500));
+    assert_eq!(hang_log_len(), len);
+}
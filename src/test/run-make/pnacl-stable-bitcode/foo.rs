@@ -0,0 +1,26 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(i128_type)]
+
+#[inline(never)]
+fn wide(a: u128, b: u128) -> u128 {
+    a.wrapping_mul(b)
+}
+
+#[inline(never)]
+fn pair(a: u64) -> (u64, u64) {
+    (a, a + 1)
+}
+
+fn main() {
+    let (a, b) = pair(std::env::args().count() as u64);
+    std::process::exit(wide(a as u128, b as u128) as i32);
+}
@@ -0,0 +1,59 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// ```
+/// assert_eq!(foo::add(1, 2), 3);
+/// ```
+///
+/// ```
+/// assert_eq!(::foo::add(2, 2), 4);
+/// ```
+///
+/// ```should_panic
+/// foo::add(!0, 1);
+/// ```
+///
+/// ```
+/// assert_eq!(foo::add(1, 1), 3);
+/// ```
+///
+/// ```
+/// fn main() {
+///     assert_eq!(foo::add(0, 0), 0);
+/// }
+/// ```
+///
+/// ```compile_fail
+/// foo::add(1);
+/// ```
+///
+/// ```no_run
+/// loop { foo::add(1, 1); }
+/// ```
+pub fn add(a: u32, b: u32) -> u32 {
+    a.checked_add(b).unwrap()
+}
+
+/// ```
+/// foo::log_exe();
+/// ```
+///
+/// ```
+/// foo::log_exe();
+/// ```
+pub fn log_exe() {
+    use std::env;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    let path = env::var_os("DOCTEST_EXE_LOG").unwrap();
+    let mut log = OpenOptions::new().create(true).append(true).open(path).unwrap();
+    writeln!(log, "{}", env::current_exe().unwrap().display()).unwrap();
+}
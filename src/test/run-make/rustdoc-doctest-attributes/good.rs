@@ -0,0 +1,29 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// ```should_panic = "boom"
+/// panic!("boom");
+/// ```
+///
+/// ```cfg=foo, cfg = "bar=\"baz\""
+/// #[cfg(not(all(foo, bar = "baz")))]
+/// compile_error!("not compiled with the cfgs of the block");
+/// fn main() {}
+/// ```
+///
+/// ```feature=box_syntax
+/// let five = box 5;
+/// assert_eq!(*five, 5);
+/// ```
+///
+/// ```ignore-nonexistent_target
+/// assert_eq!(1 + 1, 2);
+/// ```
+pub fn foo() {}
@@ -0,0 +1,21 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// ```
+/// extern crate bar;
+/// extern crate foo;
+///
+/// assert_eq!(foo::one() + 1, bar::two());
+/// // Only fails when the example is run again.
+/// assert!(std::env::var("DOCTEST_FAIL").is_err());
+/// ```
+pub fn one() -> u32 {
+    1
+}
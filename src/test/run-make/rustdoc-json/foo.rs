@@ -0,0 +1,31 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![feature(doc_cfg)]
+#![crate_name = "foo"]
+
+use std::fmt;
+
+/// Some documentation for `Foo`.
+pub struct Foo<T: Clone> {
+    pub bar: T,
+}
+
+impl<T: Clone> fmt::Display for Foo<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("foo")
+    }
+}
+
+pub mod inner {
+    /// Only on Unix.
+    #[doc(cfg(unix))]
+    pub fn unix_only() {}
+}
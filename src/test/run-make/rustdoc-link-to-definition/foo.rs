@@ -0,0 +1,23 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate bar;
+
+pub struct Counter { pub count: u32 }
+
+pub fn make() -> Counter { Counter { count: 0 } }
+
+pub fn bump(c: &mut Counter) -> bar::Bar {
+    c.count += 1;
+    let _ = make();
+    bar::helper();
+    bar::hidden();
+    bar::Bar::new()
+}
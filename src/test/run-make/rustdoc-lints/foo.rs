@@ -0,0 +1,35 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// ```should-panic
+/// panic!();
+/// ```
+pub fn misspelled() {}
+
+/// ```
+/// let x = 1;
+pub fn unclosed() {}
+
+///
+pub fn empty() {}
+
+#[doc(hiden)]
+pub fn unknown_doc_attribute() {}
+
+#[allow(empty_docs)]
+pub mod allowed {
+    ///
+    pub fn empty() {}
+}
+
+/// ```text
+/// fine
+/// ```
+pub fn fine() {}
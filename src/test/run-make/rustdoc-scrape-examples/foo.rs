@@ -0,0 +1,50 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_name = "foo"]
+
+// Only in the library the examples are compiled against, shifting the indices
+// of the definitions after it.
+#[cfg(shifted)]
+pub fn shifted() {}
+
+/// Adds one.
+pub fn add_one(x: u32) -> u32 {
+    x + 1
+}
+
+/// Never called.
+pub fn unused() {}
+
+pub use inner::double;
+
+mod inner {
+    /// Doubles, documented at the crate root through a re-export.
+    pub fn double(x: u32) -> u32 {
+        x * 2
+    }
+}
+
+pub struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    /// Creates a counter starting at zero.
+    pub fn new() -> Counter {
+        Counter { count: 0 }
+    }
+
+    /// Counts one more.
+    pub fn bump(&mut self) -> u32 {
+        self.count = add_one(self.count);
+        self.count
+    }
+}
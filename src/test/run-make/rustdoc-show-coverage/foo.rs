@@ -0,0 +1,46 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The crate root.
+
+/// Documented, with an example.
+///
+/// ```
+/// foo::bar();
+/// ```
+pub fn bar() {}
+
+pub fn baz() {}
+
+/// Documented.
+pub struct Foo {
+    /// Documented.
+    pub a: u32,
+    pub b: u32,
+    c: u32,
+}
+
+impl Foo {
+    /// Documented.
+    pub fn new() -> Foo { Foo { a: 0, b: 0, c: 0 } }
+}
+
+impl Clone for Foo {
+    fn clone(&self) -> Foo { Foo::new() }
+}
+
+pub mod inner {
+    /// ```text
+    /// not an example
+    /// ```
+    pub fn qux() {}
+
+    fn private() {}
+}
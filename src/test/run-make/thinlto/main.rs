@@ -0,0 +1,24 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+extern crate lib;
+
+mod first {
+    pub fn run() -> u32 { ::lib::b::quadruple(3) }
+}
+
+mod second {
+    pub fn run() -> u32 { ::lib::a::double(::first::run()) }
+}
+
+fn main() {
+    let expected = std::env::args().nth(1).map_or(24, |s| s.parse().unwrap());
+    assert_eq!(second::run(), expected);
+}
@@ -0,0 +1,27 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -C overflow-checks=off -Z mir-opt-level=2
+
+#![feature(rustc_attrs)]
+
+#[inline]
+#[rustc_inherit_overflow_checks]
+pub fn add_one() -> u8 {
+    let x = 255u8;
+    x + 1
+}
+
+#[inline]
+#[rustc_inherit_overflow_checks]
+pub fn negate() -> i8 {
+    let x = -128i8;
+    -x
+}
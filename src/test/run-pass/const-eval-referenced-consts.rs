@@ -0,0 +1,38 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that constants whose value can't be the result of constant
+// evaluation, such as enum variants with fields or references to
+// aggregates, can still be used by other constants.
+
+#![feature(const_fn, const_fn_control_flow)]
+
+const SOME: Option<u32> = Some(7);
+const NONE: Option<u32> = None;
+
+const fn unwrap_or(o: Option<u32>, default: u32) -> u32 {
+    match o {
+        Some(x) => x,
+        None => default,
+    }
+}
+
+const SEVEN: usize = unwrap_or(SOME, 0) as usize;
+const THREE: usize = unwrap_or(NONE, 3) as usize;
+
+const PAIR: &(u32, u32) = &(2, 5);
+const FIVE: usize = PAIR.1 as usize;
+
+fn main() {
+    let a: [u8; SEVEN] = [0; 7];
+    let b: [u8; THREE] = [0; 3];
+    let c: [u8; FIVE] = [0; 5];
+    assert_eq!(a.len() + b.len() + c.len(), 15);
+}
@@ -0,0 +1,63 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Test that a `const fn` with local bindings, loops and `match` can be
+// evaluated in array lengths and constants.
+
+#![feature(const_fn, const_fn_control_flow, const_indexing)]
+
+const fn triangle(n: usize) -> usize {
+    let mut sum = 0;
+    let mut i = 1;
+    while i <= n {
+        sum += i;
+        i += 1;
+    }
+    sum
+}
+
+const fn first_odd(xs: [u32; 4]) -> u32 {
+    let mut i = 0;
+    loop {
+        if i == 4 {
+            return 0;
+        }
+        if xs[i] % 2 == 1 {
+            return xs[i];
+        }
+        i += 1;
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Add,
+    Mul,
+}
+
+const fn apply(op: Op, a: u32, b: u32) -> u32 {
+    match op {
+        Op::Add => a + b,
+        Op::Mul => a * b,
+    }
+}
+
+const TRIANGLE: usize = triangle(4);
+const ODD: u32 = first_odd([2, 4, 7, 9]);
+const PRODUCT: u32 = apply(Op::Mul, 6, 7);
+
+fn main() {
+    let arr: [u8; triangle(3)] = [0; 6];
+    assert_eq!(arr.len(), 6);
+    assert_eq!(TRIANGLE, 10);
+    assert_eq!(ODD, 7);
+    assert_eq!(PRODUCT, 42);
+    assert_eq!(apply(Op::Add, 1, 2), 3);
+}
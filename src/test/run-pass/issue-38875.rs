@@ -14,4 +14,5 @@ extern crate issue_38875_b;
 
 fn main() {
     let test_x = [0; issue_38875_b::FOO];
+    assert_eq!(test_x.len(), 0);
 }
@@ -0,0 +1,25 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// aux-build:inherit_overflow_checks.rs
+// compile-flags: -C overflow-checks=on
+
+// Test that constant propagation in a crate without overflow checks does not
+// remove the overflow checks of `#[rustc_inherit_overflow_checks]` functions,
+// which are translated with the overflow checks of the crate using them.
+
+extern crate inherit_overflow_checks;
+
+use std::panic;
+
+fn main() {
+    assert!(panic::catch_unwind(|| inherit_overflow_checks::add_one()).is_err());
+    assert!(panic::catch_unwind(|| inherit_overflow_checks::negate()).is_err());
+}
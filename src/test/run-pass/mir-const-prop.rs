@@ -0,0 +1,67 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=2
+
+// Test that constant propagation keeps the behaviour of locals assigned in
+// loops and branches, and of known switches.
+
+#[derive(Clone, Copy)]
+enum Shape {
+    Circle(u32),
+    Square(u32),
+}
+
+fn area(shape: Shape) -> u32 {
+    match shape {
+        Shape::Circle(r) => 3 * r * r,
+        Shape::Square(s) => s * s,
+    }
+}
+
+fn known_variant() -> u32 {
+    let shape = Shape::Square(4);
+    match shape {
+        Shape::Circle(_) => 0,
+        Shape::Square(s) => s,
+    }
+}
+
+fn main() {
+    let mut i = 0;
+    let mut sum = 0;
+    while i < 10 {
+        sum += i;
+        i += 1;
+    }
+    assert_eq!(i, 10);
+    assert_eq!(sum, 45);
+
+    let x = 7;
+    let y = if x > 5 { x * 2 } else { x };
+    assert_eq!(y, 14);
+
+    let mut z = 1;
+    for _ in 0..3 {
+        z *= 2;
+    }
+    assert_eq!(z, 8);
+
+    let nan = 0.0f64 / 0.0;
+    assert!(nan != nan);
+    assert!(!(nan < 1.0));
+
+    let c = 'a';
+    assert!(c < 'b');
+
+    assert_eq!(known_variant(), 4);
+    assert_eq!(area(Shape::Circle(2)), 12);
+    assert_eq!(area(Shape::Square(3)), 9);
+}
@@ -0,0 +1,81 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=3
+
+// Test that destination propagation does not merge locals whose live ranges overlap.
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct Big([u64; 8]);
+
+struct Builder {
+    data: Big,
+    len: usize,
+}
+
+impl Builder {
+    fn new() -> Builder {
+        Builder { data: Big([0; 8]), len: 0 }
+    }
+
+    fn push(mut self, x: u64) -> Builder {
+        self.data.0[self.len] = x;
+        self.len += 1;
+        self
+    }
+
+    fn build(self) -> Big {
+        let b = self.data;
+        b
+    }
+}
+
+fn swap(a: Big, b: Big) -> (Big, Big) {
+    let mut x = a;
+    let mut y = b;
+    let t = x;
+    x = y;
+    y = t;
+    (x, y)
+}
+
+fn returned_early(flag: bool) -> Big {
+    let a = Big([1; 8]);
+    let b = Big([2; 8]);
+    if flag {
+        return a;
+    }
+    let c = b;
+    c
+}
+
+fn overwritten() -> Big {
+    let a = Big([3; 8]);
+    let mut r = a;
+    let b = Big([4; 8]);
+    if r.0[0] == 3 {
+        r = b;
+    }
+    assert_eq!(a.0[0], 3);
+    r
+}
+
+fn main() {
+    let big = Builder::new().push(1).push(2).push(3).build();
+    assert_eq!(big, Big([1, 2, 3, 0, 0, 0, 0, 0]));
+
+    let (x, y) = swap(Big([5; 8]), Big([6; 8]));
+    assert_eq!(x, Big([6; 8]));
+    assert_eq!(y, Big([5; 8]));
+
+    assert_eq!(returned_early(true), Big([1; 8]));
+    assert_eq!(returned_early(false), Big([2; 8]));
+    assert_eq!(overwritten(), Big([4; 8]));
+}
@@ -0,0 +1,30 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-Zalways-encode-mir
+
+#![crate_type = "rlib"]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn private_helper(x: u32) -> u32 {
+    CALLS.fetch_add(1, Ordering::SeqCst);
+    x.wrapping_mul(3)
+}
+
+pub fn triple(x: u32) -> u32 {
+    private_helper(x)
+}
+
+pub fn calls() -> usize {
+    CALLS.load(Ordering::SeqCst)
+}
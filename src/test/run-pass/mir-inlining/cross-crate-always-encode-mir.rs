@@ -0,0 +1,24 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-O
+// aux-build:always_encode_mir.rs
+
+// Test that a non-generic function from a crate built with
+// `-Z always-encode-mir` can be inlined even though it refers to
+// private items of that crate.
+
+extern crate always_encode_mir;
+
+fn main() {
+    assert_eq!(always_encode_mir::triple(14), 42);
+    assert_eq!(always_encode_mir::triple(1), 3);
+    assert_eq!(always_encode_mir::calls(), 2);
+}
@@ -0,0 +1,31 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-Zmir-opt-level=2
+
+// Test that a trait method called from an inlined generic function is not
+// replaced by the trait's default body once the type is known (see #40473).
+
+pub trait Foo {
+    fn bar(&self) -> usize { 2 }
+}
+
+impl Foo for () {
+    fn bar(&self) -> usize { 3 }
+}
+
+#[inline]
+fn call_bar<T: Foo>(t: &T) -> usize {
+    t.bar()
+}
+
+fn main() {
+    assert_eq!(call_bar(&()), 3);
+}
@@ -0,0 +1,45 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags:-O
+
+// Test that the MIR inliner, which runs by default at -O, terminates on
+// recursive and mutually recursive functions, and respects
+// `#[inline(never)]`.
+
+#[inline(always)]
+fn fact(n: u64) -> u64 {
+    if n == 0 { 1 } else { n * fact(n - 1) }
+}
+
+#[inline]
+fn is_even<T: Copy + Into<u64>>(n: T) -> bool {
+    let n = n.into();
+    if n == 0 { true } else { is_odd(n - 1) }
+}
+
+#[inline]
+fn is_odd<T: Copy + Into<u64>>(n: T) -> bool {
+    let n = n.into();
+    if n == 0 { false } else { is_even(n - 1) }
+}
+
+#[inline(never)]
+fn never<T: Into<u64>>(x: T) -> u64 {
+    x.into() + 1
+}
+
+fn main() {
+    assert_eq!(fact(10), 3628800);
+    assert!(is_even(10u32));
+    assert!(is_odd(7u8));
+    assert!(!is_odd(100u64));
+    assert_eq!(never(41u32), 42);
+}
@@ -0,0 +1,80 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// compile-flags: -Z mir-opt-level=3
+
+// Test scalar replacement of struct and tuple locals.
+
+#[derive(Clone, Copy)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+struct Pair(u8, (u16, u32));
+
+#[repr(C)]
+struct Header {
+    len: u32,
+    cap: u32,
+}
+
+fn tuple(a: u32, b: u32) -> u32 {
+    let t = (a, b);
+    t.0 * 10 + t.1
+}
+
+fn fields(p: Point) -> i32 {
+    let mut q = Point { x: 0, y: 0 };
+    q.x = p.y;
+    q.y = p.x;
+    let r = &mut q.x;
+    *r += 1;
+    q.x * 100 + q.y
+}
+
+fn nested() -> u32 {
+    let p = Pair(1, (2, 3));
+    let mut inner = p.1;
+    inner.0 += 1;
+    p.0 as u32 + (p.1).1 + inner.0 as u32
+}
+
+fn whole(p: Point) -> Point {
+    // `q` is used as a whole, so it can't be split.
+    let mut q = p;
+    q.x += 1;
+    q
+}
+
+fn borrowed_field() -> u32 {
+    // The borrow of `h.len` is used to reach `h.cap`, so `h` can't be split.
+    let h = Header { len: 1, cap: 2 };
+    let p = &h.len as *const u32;
+    unsafe { *p.offset(1) * 10 + h.len }
+}
+
+fn in_loop(n: u32) -> u32 {
+    let mut acc = (0, 1);
+    for _ in 0..n {
+        acc = (acc.1, acc.0 + acc.1);
+    }
+    acc.0
+}
+
+fn main() {
+    assert_eq!(tuple(4, 2), 42);
+    assert_eq!(fields(Point { x: 3, y: 4 }), 503);
+    assert_eq!(nested(), 7);
+    let q = whole(Point { x: 1, y: 2 });
+    assert_eq!((q.x, q.y), (2, 2));
+    assert_eq!(in_loop(10), 55);
+    assert_eq!(borrowed_field(), 21);
+}
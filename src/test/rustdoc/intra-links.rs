@@ -0,0 +1,60 @@
+// Copyright 2017 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// @has intra_links/index.html
+// @has - '//a/@href' 'struct.ThisType.html'
+// @has - '//a/@href' 'enum.ThisEnum.html'
+// @has - '//a/@href' 'trait.ThisTrait.html'
+// @has - '//a/@href' 'fn.this_function.html'
+// @has - '//a/@href' 'struct.ThisType.html#method.this_method'
+// @has - '//a/@href' 'enum.ThisEnum.html#variant.ThisVariant'
+// @has - '//a/@href' 'trait.ThisTrait.html#tymethod.this_required_method'
+// @has - '//a/@href' 'submod/struct.Inner.html'
+// @has - '//a/@href' 'shared/index.html'
+// @has - '//a/@href' 'fn.shared.html'
+//! In this crate we would like to link to:
+//!
+//! * [`ThisType`], [`ThisEnum`] and [ThisTrait]
+//! * [the function](this_function)
+//! * [`ThisType::this_method`]
+//! * [`ThisEnum::ThisVariant`]
+//! * [`ThisTrait::this_required_method`]
+//! * [`submod::Inner`] and [`Reexported`], which both go to the same page
+//! * the module [`shared`] and the function of the same name, [`shared()`]
+//! * [an actual URL](https://www.rust-lang.org) and an [unrelated] bracket
+
+pub struct ThisType;
+
+impl ThisType {
+    pub fn this_method() {}
+}
+
+pub enum ThisEnum { ThisVariant, }
+
+pub trait ThisTrait {
+    fn this_required_method();
+}
+
+pub fn this_function() {}
+
+pub mod shared {}
+
+pub fn shared() {}
+
+pub mod submod {
+    pub struct Inner;
+
+    // @has intra_links/submod/struct.Sibling.html '//a/@href' 'struct.Inner.html'
+    // @has - '//a/@href' '../struct.ThisType.html'
+    /// Links are resolved in the scope of this module: [`Inner`], [`super::ThisType`].
+    pub struct Sibling;
+}
+
+pub use submod::Inner as Reexported;
@@ -332,7 +332,7 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         filter: config.filter.clone(),
         filter_exact: config.filter_exact,
         run_ignored: config.run_ignored,
-        quiet: config.quiet,
+        format: if config.quiet { test::OutputFormat::Terse } else { test::OutputFormat::Pretty },
         logfile: config.logfile.clone(),
         run_tests: true,
         bench_benchmarks: true,
@@ -342,6 +342,8 @@ pub fn test_opts(config: &Config) -> test::TestOpts {
         },
         color: config.color,
         test_threads: None,
+        test_timeout: None,
+        isolate: false,
         skip: vec![],
         list: false,
         options: test::Options::new(),
@@ -473,6 +475,7 @@ pub fn make_test(config: &Config, testpaths: &TestPaths) -> test::TestDescAndFn
             ignore,
             should_panic,
             allow_fail: false,
+            timeout: None,
         },
         testfn: make_test_closure(config, testpaths),
     }
@@ -1121,6 +1121,7 @@ actual:\n\
         rustc.arg("-L").arg(&self.aux_output_dir_name());
 
         match self.config.mode {
+            Ui if self.config.src_base.ends_with("rustdoc-ui") => {}
             CompileFail | Ui => {
                 // compile-fail and ui tests tend to have tons of unused code as
                 // it's just testing various pieces of the compile, but we don't
@@ -1365,7 +1366,14 @@ actual:\n\
     }
 
     fn make_compile_args(&self, input_file: &Path, output_file: TargetLocation) -> Command {
-        let mut rustc = Command::new(&self.config.rustc_path);
+        // The tests of rustdoc's diagnostics are UI tests which are
+        // documented rather than compiled.
+        let is_rustdoc = self.config.src_base.ends_with("rustdoc-ui");
+        let mut rustc = if !is_rustdoc {
+            Command::new(&self.config.rustc_path)
+        } else {
+            Command::new(self.config.rustdoc_path.as_ref().expect("--rustdoc-path passed"))
+        };
         rustc.arg(input_file)
             .arg("-L").arg(&self.config.build_base);
 
@@ -1432,6 +1440,18 @@ actual:\n\
             }
         }
 
+        if is_rustdoc {
+            // rustdoc only knows `-o`, the directory to document into, and
+            // doesn't take codegen options.
+            match output_file {
+                TargetLocation::ThisFile(path) | TargetLocation::ThisDirectory(path) => {
+                    rustc.arg("-o").arg(path);
+                }
+            }
+            rustc.args(&self.props.compile_flags);
+            return rustc;
+        }
+
         if !self.props.no_prefer_dynamic {
             rustc.args(&["-C", "prefer-dynamic"]);
         }